@@ -1,13 +1,21 @@
-use std::{collections::HashMap, fmt::Debug};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{Debug, Display},
+    str::FromStr,
+};
 
 use chrono::{DateTime, Utc};
 use rand::distributions::{Alphanumeric, DistString};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use ulid::Ulid;
 use url::Url;
 
 use shared::utc_now;
 
+use crate::{VotingMethod, DEFAULT_TALK_DURATION_MINUTES};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MeetUpMetadata {
     pub id: Ulid,
@@ -21,6 +29,56 @@ pub struct MeetUp {
     pub state: MeetUpState,
     pub location: Location,
     pub date: DateTime<Utc>,
+    pub voting_method: VotingMethod,
+    /// How many of the best voted papers make the lineup.
+    pub number_of_talks: u8,
+    /// Seats available for on site meet ups, registrations past it go to the waitlist.
+    pub capacity: Option<u32>,
+    pub settings: MeetUpSettings,
+}
+
+/// How often a meet up changed, for calendars to replace their copy of the event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MeetUpRevision {
+    /// Starts at 0 and grows with every change of the date, location, state or lineup.
+    pub sequence: u32,
+    pub last_modified: DateTime<Utc>,
+}
+
+pub const DEFAULT_PAPERS_PER_USER_PER_MEET_UP: u8 = 2;
+
+/// Limits and deadlines for the call for papers and the voting of a meet up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MeetUpSettings {
+    pub papers_per_user: u8,
+    /// Papers the meet up accepts in total, `None` means no limit.
+    pub max_papers: Option<u32>,
+    /// Papers submitted after it are rejected.
+    pub call_for_papers_deadline: Option<DateTime<Utc>>,
+    /// Votes stored after it are rejected.
+    pub voting_deadline: Option<DateTime<Utc>>,
+}
+
+impl Default for MeetUpSettings {
+    fn default() -> Self {
+        Self {
+            papers_per_user: DEFAULT_PAPERS_PER_USER_PER_MEET_UP,
+            max_papers: None,
+            call_for_papers_deadline: None,
+            voting_deadline: None,
+        }
+    }
+}
+
+impl MeetUpSettings {
+    pub fn is_call_for_papers_closed(&self, now: DateTime<Utc>) -> bool {
+        self.call_for_papers_deadline
+            .is_some_and(|deadline| now > deadline)
+    }
+
+    pub fn is_voting_closed(&self, now: DateTime<Utc>) -> bool {
+        self.voting_deadline.is_some_and(|deadline| now > deadline)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -36,8 +94,9 @@ pub enum Location {
 pub enum MeetUpState {
     CallForPapers,
     Voting,
-    Scheduled(Paper),
-    Done { paper: Paper, link: Url },
+    Scheduled(Vec<Talk>),
+    Done { talks: Vec<Talk>, link: Url },
+    Cancelled,
 }
 
 impl std::fmt::Display for MeetUpState {
@@ -47,19 +106,55 @@ impl std::fmt::Display for MeetUpState {
             MeetUpState::Voting => write!(f, "Voting"),
             MeetUpState::Scheduled { .. } => write!(f, "Scheduled"),
             MeetUpState::Done { .. } => write!(f, "Done"),
+            MeetUpState::Cancelled => write!(f, "Cancelled"),
         }
     }
 }
 
 impl MeetUp {
-    pub fn new(id: Ulid, state: MeetUpState, location: Location, date: DateTime<Utc>) -> Self {
+    pub fn new(
+        id: Ulid,
+        state: MeetUpState,
+        location: Location,
+        date: DateTime<Utc>,
+        voting_method: VotingMethod,
+        number_of_talks: u8,
+        capacity: Option<u32>,
+    ) -> Self {
         Self {
             id,
             state,
             location,
             date,
+            voting_method,
+            number_of_talks,
+            capacity,
+            settings: MeetUpSettings::default(),
         }
     }
+
+    pub fn with_settings(self, settings: MeetUpSettings) -> Self {
+        Self { settings, ..self }
+    }
+
+    /// The lineup, empty until the meet up is scheduled.
+    pub fn talks(&self) -> &[Talk] {
+        match &self.state {
+            MeetUpState::Scheduled(talks) | MeetUpState::Done { talks, .. } => talks,
+            _ => &[],
+        }
+    }
+
+    /// When the last talk ends. Until the lineup is known, the talks are assumed to take the
+    /// default duration.
+    pub fn end(&self) -> DateTime<Utc> {
+        self.talks().iter().map(Talk::end).max().unwrap_or_else(|| {
+            self.date
+                + chrono::Duration::minutes(
+                    (u32::from(self.number_of_talks) * DEFAULT_TALK_DURATION_MINUTES).into(),
+                )
+        })
+    }
 }
 
 impl MeetUpMetadata {
@@ -68,33 +163,65 @@ impl MeetUpMetadata {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RegistrationStatus {
+    Attending,
+    /// Position in the waitlist, starting at 1.
+    Waitlisted(usize),
+}
+
+/// Someone registered to a meet up, as listed for the organizers at the door.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attendee {
+    pub user_id: Ulid,
+    pub nickname: String,
+    pub email: String,
+    pub waitlisted: bool,
+    pub registered_at: DateTime<Utc>,
+    pub checked_in_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct User {
     pub id: Ulid,
     pub nickname: String,
     pub email: String,
-    pub access_token: AccessToken,
     pub login_method: LoginMethod,
 }
 
 impl User {
-    pub fn new(
-        id: Ulid,
-        nickname: String,
-        email: String,
-        access_token: AccessToken,
-        login_method: LoginMethod,
-    ) -> Self {
+    pub fn new(id: Ulid, nickname: String, email: String, login_method: LoginMethod) -> Self {
         Self {
             id,
             nickname,
             email,
-            access_token,
             login_method,
         }
     }
 }
 
+/// A browser logged in as a user. Only the hash of the token in its cookie is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub id: Ulid,
+    pub user_id: Ulid,
+    /// The `User-Agent` of the browser, so the user can tell the sessions apart.
+    pub device: String,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expire_at: DateTime<Utc>,
+}
+
+impl Session {
+    pub fn is_expired(&self) -> bool {
+        utc_now() > self.expire_at
+    }
+
+    pub fn hash_token(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AccessToken {
     token: String,
@@ -140,9 +267,156 @@ impl Debug for AccessToken {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum LoginMethod {
     Github {
+        /// Stable id of the GitHub account. `None` only for users that have not logged in since
+        /// the ids are stored and that the backfill could not look up yet.
+        github_id: Option<i64>,
         access_token: AccessToken,
         refresh_token: AccessToken,
     },
+    /// A standard OpenID Connect provider, for the members without a GitHub account.
+    Oidc {
+        /// Id of the provider in the configuration.
+        provider: String,
+        /// Stable id of the account in the provider, the `sub` claim.
+        subject: String,
+    },
+}
+
+/// The account that logged in on an OpenID Connect provider.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OidcUser {
+    pub provider: String,
+    pub subject: String,
+    pub nickname: String,
+    pub email: String,
+}
+
+/// The account that logged in on GitHub.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GithubUser {
+    pub id: i64,
+    pub login: String,
+    /// The primary email, which the user can change on GitHub.
+    pub email: String,
+}
+
+/// Users with a role can manage the meet ups in the admin pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Role {
+    /// Can also grant and revoke roles.
+    Admin,
+    Organizer,
+}
+
+impl Role {
+    pub const ALL: [Role; 2] = [Role::Admin, Role::Organizer];
+}
+
+impl Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Role::Admin => write!(f, "Admin"),
+            Role::Organizer => write!(f, "Organizer"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Role::ALL
+            .into_iter()
+            .find(|role| role.to_string() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown role `{s}`"))
+    }
+}
+
+/// The role of the user changed on `date`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleChange {
+    pub user_id: Ulid,
+    /// `None` when the role was revoked.
+    pub role: Option<Role>,
+    /// `None` when granted from the `ADMIN_EMAILS` list.
+    pub changed_by: Option<Ulid>,
+    pub date: DateTime<Utc>,
+}
+
+/// What a personal API token is allowed to do. The public meet up routes need no token at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ApiTokenScope {
+    /// Reading the data of the user, like their papers and votes.
+    ReadOnly,
+    SubmitPaper,
+    Vote,
+    /// Only admins can create tokens with this scope.
+    Admin,
+}
+
+impl ApiTokenScope {
+    pub const ALL: [ApiTokenScope; 4] = [
+        ApiTokenScope::ReadOnly,
+        ApiTokenScope::SubmitPaper,
+        ApiTokenScope::Vote,
+        ApiTokenScope::Admin,
+    ];
+}
+
+impl Display for ApiTokenScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiTokenScope::ReadOnly => write!(f, "ReadOnly"),
+            ApiTokenScope::SubmitPaper => write!(f, "SubmitPaper"),
+            ApiTokenScope::Vote => write!(f, "Vote"),
+            ApiTokenScope::Admin => write!(f, "Admin"),
+        }
+    }
+}
+
+impl FromStr for ApiTokenScope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ApiTokenScope::ALL
+            .into_iter()
+            .find(|scope| scope.to_string() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown API token scope `{s}`"))
+    }
+}
+
+/// A named personal token for scripts. Only the hash of its secret is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiToken {
+    pub id: Ulid,
+    pub user_id: Ulid,
+    pub name: String,
+    pub scopes: Vec<ApiTokenScope>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiToken {
+    const SECRET_PREFIX: &'static str = "rcwb_";
+
+    /// A new secret, shown to the user only once.
+    pub fn generate_secret() -> String {
+        format!(
+            "{}{}",
+            Self::SECRET_PREFIX,
+            Alphanumeric.sample_string(&mut rand::thread_rng(), 40)
+        )
+    }
+
+    /// Secrets are random enough that a fast hash is enough to keep the stored ones useless.
+    pub fn hash_secret(secret: &str) -> String {
+        hex::encode(Sha256::digest(secret.as_bytes()))
+    }
+
+    pub fn has_scope(&self, scope: ApiTokenScope) -> bool {
+        self.scopes.contains(&scope)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -153,44 +427,230 @@ pub struct Paper {
     pub title: String,
     pub description: String,
     pub speaker: String,
+    /// Opt-in to have the paper carried over to the next meet up when it is not selected.
+    pub keep_in_pool: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// A paper in a meet up lineup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Talk {
+    pub paper: Paper,
+    pub start: DateTime<Utc>,
+    pub duration_minutes: u32,
+}
+
+impl Talk {
+    pub fn end(&self) -> DateTime<Utc> {
+        self.start + chrono::Duration::minutes(self.duration_minutes.into())
+    }
+}
+
+/// One entry of a voter's ballot. `position` 0 is the voter's favourite paper.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Vote {
     pub paper_id: Ulid,
     pub meet_up_id: Ulid,
     pub user_id: Ulid,
-    pub vote: f64,
+    pub position: u32,
+}
+
+/// A meet up state without its data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeetUpStateKind {
+    CallForPapers,
+    Voting,
+    Scheduled,
+    Done,
+    Cancelled,
+}
+
+impl From<&MeetUpState> for MeetUpStateKind {
+    fn from(state: &MeetUpState) -> Self {
+        match state {
+            MeetUpState::CallForPapers => MeetUpStateKind::CallForPapers,
+            MeetUpState::Voting => MeetUpStateKind::Voting,
+            MeetUpState::Scheduled(_) => MeetUpStateKind::Scheduled,
+            MeetUpState::Done { .. } => MeetUpStateKind::Done,
+            MeetUpState::Cancelled => MeetUpStateKind::Cancelled,
+        }
+    }
+}
+
+/// The meet up moved to `state` on `date`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeetUpStateChange {
+    pub meet_up_id: Ulid,
+    pub state: MeetUpStateKind,
+    pub date: DateTime<Utc>,
+}
+
+/// Something that happened to a meet up, published in the site feed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeedEntry {
+    pub meet_up: MeetUp,
+    pub kind: FeedEntryKind,
+    pub date: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeedEntryKind {
+    CallForPapersOpened,
+    VotingOpened,
+    TalkScheduled(Talk),
+    /// The meet up happened and its recording is at the link.
+    Done(Url),
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleOverride {
+    pub meet_up_id: Ulid,
+    pub previous_paper_id: Option<Ulid>,
+    /// `None` when the admin moved the meet up back to voting.
+    pub paper_id: Option<Ulid>,
+    pub date: DateTime<Utc>,
+}
+
+/// Why an email is sent to a user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notification {
+    PaperReceived {
+        meet_up: MeetUp,
+        paper: Paper,
+    },
+    PaperSelected {
+        meet_up: MeetUp,
+        paper: Paper,
+    },
+    PaperNotSelected {
+        meet_up: MeetUp,
+        paper: Paper,
+    },
+    VotingOpened(MeetUp),
+    RegistrationConfirmed(MeetUp),
+    /// Sent the day before the meet up to the attending users.
+    MeetUpReminder(MeetUp),
 }
 
 #[derive(Debug)]
 pub struct VoteDecider {
     votes: Vec<Vote>,
+    method: VotingMethod,
 }
 
 impl VoteDecider {
-    pub fn new(votes: Vec<Vote>) -> Self {
-        Self { votes }
+    pub fn new(votes: Vec<Vote>, method: VotingMethod) -> Self {
+        Self { votes, method }
     }
 
-    /// We use harmonic positional voting https://en.wikipedia.org/wiki/Positional_voting
     pub fn decide(&self) -> Option<Ulid> {
-        let votes_per_paper_id: HashMap<Ulid, f64> =
-            self.votes
-                .iter()
-                .fold(HashMap::new(), |mut acc: HashMap<Ulid, f64>, vote| {
-                    *acc.entry(vote.paper_id).or_default() += vote.vote;
-                    acc
-                });
-        let (paper_id, _) = votes_per_paper_id
+        Some(self.results().ranking.first()?.paper_id)
+    }
+
+    /// The `n` best voted papers, best first.
+    pub fn decide_top(&self, n: usize) -> Vec<Ulid> {
+        self.results()
+            .ranking
             .into_iter()
-            .max_by(|(_, vote_a), (_, vote_b)| {
-                vote_a
-                    .partial_cmp(vote_b)
-                    .unwrap_or(std::cmp::Ordering::Less)
-            })?;
-        Some(paper_id)
+            .take(n)
+            .map(|ranked| ranked.paper_id)
+            .collect()
     }
+
+    /// Ranks every voted paper, best first.
+    ///
+    /// Papers with the same score are ordered by who got more first-place votes and then by
+    /// submission order. Paper ids are ULIDs created on submission, so the smaller id was
+    /// submitted first.
+    pub fn results(&self) -> VotingResult {
+        self.results_with_papers(&[])
+    }
+
+    /// Same as [`VoteDecider::results`], but also ranks `paper_ids` nobody voted for.
+    pub fn results_with_papers(&self, paper_ids: &[Ulid]) -> VotingResult {
+        let ballots = self.ballots();
+        let mut candidates = paper_ids
+            .iter()
+            .chain(self.votes.iter().map(|vote| &vote.paper_id))
+            .copied()
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates.dedup();
+
+        let mut first_place_votes: HashMap<Ulid, usize> = HashMap::new();
+        for ballot in &ballots {
+            if let Some(paper_id) = ballot.first() {
+                *first_place_votes.entry(*paper_id).or_default() += 1;
+            }
+        }
+        let mut ranking = self
+            .method
+            .scores(&ballots, &candidates)
+            .into_iter()
+            .map(|(paper_id, score)| RankedPaper {
+                paper_id,
+                score,
+                first_place_votes: first_place_votes.get(&paper_id).copied().unwrap_or(0),
+                tied: false,
+            })
+            .collect::<Vec<_>>();
+        ranking.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(Ordering::Equal)
+                .then(b.first_place_votes.cmp(&a.first_place_votes))
+                .then(a.paper_id.cmp(&b.paper_id))
+        });
+        for pos in 1..ranking.len() {
+            if same_score(ranking[pos - 1].score, ranking[pos].score) {
+                ranking[pos - 1].tied = true;
+                ranking[pos].tied = true;
+            }
+        }
+
+        VotingResult {
+            ranking,
+            voters: ballots.len(),
+        }
+    }
+
+    /// Each voter's papers, from the most to the least preferred one.
+    fn ballots(&self) -> Vec<Vec<Ulid>> {
+        let mut votes_per_user: HashMap<Ulid, Vec<&Vote>> = HashMap::new();
+        for vote in &self.votes {
+            votes_per_user.entry(vote.user_id).or_default().push(vote);
+        }
+        let mut ballots = votes_per_user.into_iter().collect::<Vec<_>>();
+        ballots.sort_by_key(|(user_id, _)| *user_id);
+        ballots
+            .into_iter()
+            .map(|(_, mut votes)| {
+                votes.sort_by_key(|vote| (vote.position, vote.paper_id));
+                votes.into_iter().map(|vote| vote.paper_id).collect()
+            })
+            .collect()
+    }
+}
+
+/// Scores are sums of fractions, so the same score can come out slightly different depending
+/// on the order the votes were added.
+fn same_score(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VotingResult {
+    pub ranking: Vec<RankedPaper>,
+    pub voters: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RankedPaper {
+    pub paper_id: Ulid,
+    pub score: f64,
+    pub first_place_votes: usize,
+    /// Another paper has the same score, so the tie-break rule decided this position.
+    pub tied: bool,
 }
 
 #[cfg(test)]
@@ -203,7 +663,7 @@ mod test {
                 paper_id: Ulid::new(),
                 meet_up_id: Ulid::new(),
                 user_id: Ulid::new(),
-                vote: 1.0,
+                position: 0,
             }
         };
         ($x:ident: $y:expr) => {
@@ -223,10 +683,10 @@ mod test {
                 vote
             }
         };
-        (vote: $vote_value:expr, $($x:ident: $y:expr),*) => {
+        (position: $position:expr, $($x:ident: $y:expr),*) => {
             {
                 let mut vote = vote!($($x: $y),*);
-                vote.vote = $vote_value;
+                vote.position = $position;
                 vote
             }
         };
@@ -235,7 +695,7 @@ mod test {
     #[test]
     fn no_votes() {
         let votes = vec![];
-        let vote_decider = VoteDecider::new(votes);
+        let vote_decider = VoteDecider::new(votes, VotingMethod::Harmonic);
         assert_eq!(vote_decider.decide(), None);
     }
 
@@ -244,7 +704,7 @@ mod test {
         let paper_id = Ulid::new();
         let votes = vec![vote!(paper_id: paper_id)];
 
-        let vote_decider = VoteDecider::new(votes);
+        let vote_decider = VoteDecider::new(votes, VotingMethod::Harmonic);
         assert_eq!(vote_decider.decide(), Some(paper_id));
     }
 
@@ -253,39 +713,68 @@ mod test {
         let paper_id = Ulid::new();
         let votes = vec![vote!(paper_id: paper_id,), vote!(paper_id: paper_id)];
 
-        let vote_decider = VoteDecider::new(votes);
+        let vote_decider = VoteDecider::new(votes, VotingMethod::Harmonic);
         assert_eq!(vote_decider.decide(), Some(paper_id));
     }
 
-    /// In case of a draw, any paper can be selected due to HashMap ordering.
+    /// In case of a draw, the paper submitted first wins.
     #[test]
     fn multiple_single_votes_for_different_paper_draw() {
-        let paper_id_1 = Ulid::new();
-        let paper_id_2 = Ulid::new();
-        let votes = vec![vote!(paper_id: paper_id_1,), vote!(paper_id: paper_id_2)];
+        let paper_id_1 = Ulid::from_parts(1, 0);
+        let paper_id_2 = Ulid::from_parts(2, 0);
+        let votes = vec![vote!(paper_id: paper_id_2,), vote!(paper_id: paper_id_1)];
 
-        let vote_decider = VoteDecider::new(votes);
-        let winner = vote_decider.decide();
-        assert!(winner == Some(paper_id_1) || winner == Some(paper_id_2));
+        let vote_decider = VoteDecider::new(votes, VotingMethod::Harmonic);
+        assert_eq!(vote_decider.decide(), Some(paper_id_1));
     }
 
     #[test]
     fn draw_with_more_losers() {
-        let paper_id_1 = Ulid::new();
-        let paper_id_2 = Ulid::new();
-        let paper_id_3 = Ulid::new();
+        let paper_id_1 = Ulid::from_parts(1, 0);
+        let paper_id_2 = Ulid::from_parts(2, 0);
+        let paper_id_3 = Ulid::from_parts(3, 0);
         let votes = vec![
-            vote!(paper_id: paper_id_1),
-            vote!(paper_id: paper_id_1),
             vote!(paper_id: paper_id_2),
             vote!(paper_id: paper_id_2),
+            vote!(paper_id: paper_id_1),
+            vote!(paper_id: paper_id_1),
             vote!(paper_id: paper_id_3),
         ];
 
-        let vote_decider = VoteDecider::new(votes);
-        let winner = vote_decider.decide();
-        assert!(winner == Some(paper_id_1) || winner == Some(paper_id_2));
-        assert_ne!(winner, Some(paper_id_3));
+        let vote_decider = VoteDecider::new(votes, VotingMethod::Harmonic);
+        let results = vote_decider.results();
+        assert_eq!(
+            results
+                .ranking
+                .iter()
+                .map(|ranked| (ranked.paper_id, ranked.tied))
+                .collect::<Vec<_>>(),
+            vec![(paper_id_1, true), (paper_id_2, true), (paper_id_3, false)]
+        );
+    }
+
+    /// Same score, but paper 2 was the favourite of more voters.
+    #[test]
+    fn draw_decided_by_first_place_votes() {
+        let paper_id_1 = Ulid::from_parts(1, 0);
+        let paper_id_2 = Ulid::from_parts(2, 0);
+        let user_id_1 = Ulid::new();
+        let user_id_2 = Ulid::new();
+        let user_id_3 = Ulid::new();
+        let votes = vec![
+            vote!(paper_id: paper_id_2, user_id: user_id_1, position: 0),
+            vote!(paper_id: paper_id_1, user_id: user_id_1, position: 1),
+            vote!(paper_id: paper_id_2, user_id: user_id_2, position: 0),
+            vote!(paper_id: paper_id_1, user_id: user_id_2, position: 1),
+            vote!(paper_id: paper_id_1, user_id: user_id_3, position: 0),
+        ];
+
+        let vote_decider = VoteDecider::new(votes, VotingMethod::Harmonic);
+        let results = vote_decider.results();
+        assert_eq!(vote_decider.decide(), Some(paper_id_2));
+        assert_eq!(results.ranking[0].first_place_votes, 2);
+        assert_eq!(results.ranking[1].first_place_votes, 1);
+        assert!(results.ranking.iter().all(|ranked| ranked.tied));
     }
 
     #[test]
@@ -296,15 +785,15 @@ mod test {
         let user_id_3 = Ulid::new();
         let paper_id_2 = Ulid::new();
         let votes = vec![
-            vote!(paper_id: paper_id_1, user_id: user_id_1, vote: 1.0),
-            vote!(paper_id: paper_id_2, user_id: user_id_1, vote: 0.5),
-            vote!(paper_id: paper_id_2, user_id: user_id_2, vote: 1.0),
-            vote!(paper_id: paper_id_1, user_id: user_id_2, vote: 0.5),
-            vote!(paper_id: paper_id_1, user_id: user_id_3, vote: 1.0),
-            vote!(paper_id: paper_id_2, user_id: user_id_3, vote: 0.5),
+            vote!(paper_id: paper_id_1, user_id: user_id_1, position: 0),
+            vote!(paper_id: paper_id_2, user_id: user_id_1, position: 1),
+            vote!(paper_id: paper_id_2, user_id: user_id_2, position: 0),
+            vote!(paper_id: paper_id_1, user_id: user_id_2, position: 1),
+            vote!(paper_id: paper_id_1, user_id: user_id_3, position: 0),
+            vote!(paper_id: paper_id_2, user_id: user_id_3, position: 1),
         ];
 
-        let vote_decider = VoteDecider::new(votes);
+        let vote_decider = VoteDecider::new(votes, VotingMethod::Harmonic);
         assert_eq!(vote_decider.decide(), Some(paper_id_1));
     }
 
@@ -322,32 +811,101 @@ mod test {
         let user_id_7 = Ulid::new();
 
         let votes = vec![
-            vote!(paper_id: paper_id_1, user_id: user_id_1, vote: 1.0),
-            vote!(paper_id: paper_id_2, user_id: user_id_1, vote: 0.5),
-            vote!(paper_id: paper_id_3, user_id: user_id_1, vote: 0.25),
-            vote!(paper_id: paper_id_2, user_id: user_id_2, vote: 1.0),
-            vote!(paper_id: paper_id_1, user_id: user_id_2, vote: 0.5),
-            vote!(paper_id: paper_id_3, user_id: user_id_2, vote: 0.25),
-            vote!(paper_id: paper_id_1, user_id: user_id_3, vote: 1.0),
-            vote!(paper_id: paper_id_2, user_id: user_id_3, vote: 0.5),
-            vote!(paper_id: paper_id_3, user_id: user_id_3, vote: 0.25),
-            vote!(paper_id: paper_id_3, user_id: user_id_4, vote: 1.0),
-            vote!(paper_id: paper_id_2, user_id: user_id_4, vote: 0.5),
-            vote!(paper_id: paper_id_1, user_id: user_id_4, vote: 0.25),
-            vote!(paper_id: paper_id_3, user_id: user_id_5, vote: 1.0),
-            vote!(paper_id: paper_id_2, user_id: user_id_5, vote: 0.5),
-            vote!(paper_id: paper_id_3, user_id: user_id_5, vote: 0.25),
-            vote!(paper_id: paper_id_1, user_id: user_id_6, vote: 1.0),
-            vote!(paper_id: paper_id_3, user_id: user_id_6, vote: 0.5),
-            vote!(paper_id: paper_id_2, user_id: user_id_6, vote: 0.25),
-            vote!(paper_id: paper_id_3, user_id: user_id_7, vote: 1.0),
-            vote!(paper_id: paper_id_2, user_id: user_id_7, vote: 0.5),
-            vote!(paper_id: paper_id_1, user_id: user_id_7, vote: 0.25),
+            vote!(paper_id: paper_id_1, user_id: user_id_1, position: 0),
+            vote!(paper_id: paper_id_2, user_id: user_id_1, position: 1),
+            vote!(paper_id: paper_id_3, user_id: user_id_1, position: 2),
+            vote!(paper_id: paper_id_2, user_id: user_id_2, position: 0),
+            vote!(paper_id: paper_id_1, user_id: user_id_2, position: 1),
+            vote!(paper_id: paper_id_3, user_id: user_id_2, position: 2),
+            vote!(paper_id: paper_id_1, user_id: user_id_3, position: 0),
+            vote!(paper_id: paper_id_2, user_id: user_id_3, position: 1),
+            vote!(paper_id: paper_id_3, user_id: user_id_3, position: 2),
+            vote!(paper_id: paper_id_3, user_id: user_id_4, position: 0),
+            vote!(paper_id: paper_id_2, user_id: user_id_4, position: 1),
+            vote!(paper_id: paper_id_1, user_id: user_id_4, position: 2),
+            vote!(paper_id: paper_id_3, user_id: user_id_5, position: 0),
+            vote!(paper_id: paper_id_2, user_id: user_id_5, position: 1),
+            vote!(paper_id: paper_id_3, user_id: user_id_5, position: 2),
+            vote!(paper_id: paper_id_1, user_id: user_id_6, position: 0),
+            vote!(paper_id: paper_id_3, user_id: user_id_6, position: 1),
+            vote!(paper_id: paper_id_2, user_id: user_id_6, position: 2),
+            vote!(paper_id: paper_id_3, user_id: user_id_7, position: 0),
+            vote!(paper_id: paper_id_2, user_id: user_id_7, position: 1),
+            vote!(paper_id: paper_id_1, user_id: user_id_7, position: 2),
         ];
-        let vote_decider = VoteDecider::new(votes);
+        let vote_decider = VoteDecider::new(votes, VotingMethod::Harmonic);
         assert_eq!(vote_decider.decide(), Some(paper_id_3));
     }
 
+    #[test]
+    fn results_rank_every_paper() {
+        let paper_id_1 = Ulid::new();
+        let paper_id_2 = Ulid::new();
+        let paper_id_3 = Ulid::new();
+        let paper_id_4 = Ulid::new();
+        let user_id_1 = Ulid::new();
+        let user_id_2 = Ulid::new();
+        let votes = vec![
+            vote!(paper_id: paper_id_1, user_id: user_id_1, position: 1),
+            vote!(paper_id: paper_id_2, user_id: user_id_1, position: 0),
+            vote!(paper_id: paper_id_3, user_id: user_id_1, position: 2),
+            vote!(paper_id: paper_id_1, user_id: user_id_2, position: 1),
+            vote!(paper_id: paper_id_2, user_id: user_id_2, position: 0),
+            vote!(paper_id: paper_id_3, user_id: user_id_2, position: 2),
+        ];
+
+        let vote_decider = VoteDecider::new(votes, VotingMethod::Harmonic);
+        assert_eq!(
+            vote_decider.results_with_papers(&[paper_id_4]),
+            VotingResult {
+                ranking: vec![
+                    RankedPaper {
+                        paper_id: paper_id_2,
+                        score: 2.0,
+                        first_place_votes: 2,
+                        tied: false,
+                    },
+                    RankedPaper {
+                        paper_id: paper_id_1,
+                        score: 1.0,
+                        first_place_votes: 0,
+                        tied: false,
+                    },
+                    RankedPaper {
+                        paper_id: paper_id_3,
+                        score: 2.0 / 3.0,
+                        first_place_votes: 0,
+                        tied: false,
+                    },
+                    RankedPaper {
+                        paper_id: paper_id_4,
+                        score: 0.0,
+                        first_place_votes: 0,
+                        tied: false,
+                    },
+                ],
+                voters: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn decide_top_papers() {
+        let paper_id_1 = Ulid::new();
+        let paper_id_2 = Ulid::new();
+        let paper_id_3 = Ulid::new();
+        let user_id = Ulid::new();
+        let votes = vec![
+            vote!(paper_id: paper_id_1, user_id: user_id, position: 2),
+            vote!(paper_id: paper_id_2, user_id: user_id, position: 0),
+            vote!(paper_id: paper_id_3, user_id: user_id, position: 1),
+        ];
+
+        let vote_decider = VoteDecider::new(votes, VotingMethod::Harmonic);
+        assert_eq!(vote_decider.decide_top(2), vec![paper_id_2, paper_id_3]);
+        assert_eq!(vote_decider.decide_top(5).len(), 3);
+    }
+
     /// We don't really expect this to be true. But it is important to support this case.
     #[test]
     fn different_number_of_votes_per_user() {
@@ -358,13 +916,128 @@ mod test {
         let user_id_3 = Ulid::new();
 
         let votes = vec![
-            vote!(paper_id: paper_id_1, user_id: user_id_1, vote: 1.0),
-            vote!(paper_id: paper_id_2, user_id: user_id_1, vote: 0.5),
-            vote!(paper_id: paper_id_2, user_id: user_id_2, vote: 1.0),
-            vote!(paper_id: paper_id_1, user_id: user_id_3, vote: 1.0),
+            vote!(paper_id: paper_id_1, user_id: user_id_1, position: 0),
+            vote!(paper_id: paper_id_2, user_id: user_id_1, position: 1),
+            vote!(paper_id: paper_id_2, user_id: user_id_2, position: 0),
+            vote!(paper_id: paper_id_1, user_id: user_id_3, position: 0),
         ];
 
-        let vote_decider = VoteDecider::new(votes);
+        let vote_decider = VoteDecider::new(votes, VotingMethod::Harmonic);
         assert_eq!(vote_decider.decide(), Some(paper_id_1));
     }
 }
+
+/// What an [`AuditEvent`] recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AuditAction {
+    CreateMeetUp,
+    UpdateMeetUp,
+    OpenVoting,
+    ScheduleMeetUp,
+    SchedulePaper,
+    ScheduleNextPaper,
+    UpdateTalk,
+    ReopenVoting,
+    FinishMeetUp,
+    CancelMeetUp,
+    CheckIn,
+    UndoCheckIn,
+    SubmitPaper,
+    EditPaper,
+    WithdrawPaper,
+    Vote,
+    Register,
+    Unregister,
+    GrantRole,
+    RevokeRole,
+    CreateApiToken,
+    RevokeApiToken,
+    RevokeSession,
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 23] = [
+        AuditAction::CreateMeetUp,
+        AuditAction::UpdateMeetUp,
+        AuditAction::OpenVoting,
+        AuditAction::ScheduleMeetUp,
+        AuditAction::SchedulePaper,
+        AuditAction::ScheduleNextPaper,
+        AuditAction::UpdateTalk,
+        AuditAction::ReopenVoting,
+        AuditAction::FinishMeetUp,
+        AuditAction::CancelMeetUp,
+        AuditAction::CheckIn,
+        AuditAction::UndoCheckIn,
+        AuditAction::SubmitPaper,
+        AuditAction::EditPaper,
+        AuditAction::WithdrawPaper,
+        AuditAction::Vote,
+        AuditAction::Register,
+        AuditAction::Unregister,
+        AuditAction::GrantRole,
+        AuditAction::RevokeRole,
+        AuditAction::CreateApiToken,
+        AuditAction::RevokeApiToken,
+        AuditAction::RevokeSession,
+    ];
+}
+
+impl Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AuditAction::ALL
+            .into_iter()
+            .find(|action| action.to_string() == s)
+            .ok_or_else(|| anyhow::anyhow!("Unknown audit action `{s}`"))
+    }
+}
+
+/// An administrative or state changing action, kept forever in the audit log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEvent {
+    pub id: Ulid,
+    /// `None` when done by the server itself, e.g. the scheduler or `ADMIN_EMAILS`.
+    pub actor_id: Option<Ulid>,
+    pub action: AuditAction,
+    pub meet_up_id: Option<Ulid>,
+    /// The paper, user or API token the action changed.
+    pub target_id: Option<Ulid>,
+    /// The details of the action.
+    pub payload: serde_json::Value,
+    pub date: DateTime<Utc>,
+}
+
+impl AuditEvent {
+    pub fn new(
+        actor_id: Option<Ulid>,
+        action: AuditAction,
+        meet_up_id: Option<Ulid>,
+        target_id: Option<Ulid>,
+        payload: serde_json::Value,
+    ) -> Self {
+        Self {
+            id: Ulid::new(),
+            actor_id,
+            action,
+            meet_up_id,
+            target_id,
+            payload,
+            date: utc_now(),
+        }
+    }
+}
+
+/// Which events to list, every filter set must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditFilter {
+    pub meet_up_id: Option<Ulid>,
+    pub actor_id: Option<Ulid>,
+}
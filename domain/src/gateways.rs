@@ -5,7 +5,11 @@ use thiserror::Error;
 use ulid::Ulid;
 use url::Url;
 
-use crate::{AccessToken, Location, MeetUp, MeetUpMetadata, Paper, User, Vote};
+use crate::{
+    AccessToken, ApiToken, Attendee, AuditEvent, AuditFilter, GithubUser, Location, MeetUp,
+    MeetUpMetadata, MeetUpRevision, MeetUpStateChange, Notification, OidcUser, Paper,
+    RegistrationStatus, Role, RoleChange, ScheduleOverride, Session, Talk, User, Vote,
+};
 
 #[derive(Debug, Error)]
 pub enum ListPastMeetUpsError {
@@ -22,26 +26,57 @@ pub enum GetMeetUpError {
 }
 
 pub trait MeetUpGateway {
-    async fn get_future_meet_up(&self) -> Result<Option<MeetUp>, GetFutureMeetUpError>;
+    async fn list_future_meet_ups(&self) -> Result<Vec<MeetUp>, ListFutureMeetUpsError>;
     async fn list_past_meet_ups(&self) -> Result<Vec<MeetUpMetadata>, ListPastMeetUpsError>;
     async fn get_meet_up(&self, id: &Ulid) -> Result<MeetUp, GetMeetUpError>;
     async fn get_meet_up_metadata(
         &self,
         id: Ulid,
     ) -> anyhow::Result<MeetUpMetadata, GetMeetUpError>;
-    async fn new_meet_up(
-        &self,
-        id: Ulid,
-        location: Location,
-        date: DateTime<Utc>,
-    ) -> Result<MeetUp, NewMeetUpError>;
+    /// Stores the meet up in call for papers, its state is ignored.
+    async fn new_meet_up(&self, meet_up: &MeetUp) -> Result<MeetUp, NewMeetUpError>;
     async fn update_meet_up_to_voting(&self, id: &Ulid) -> Result<MeetUp, UpdateMeetUpError>;
     async fn update_meet_up_to_scheduled(
         &self,
         id: &Ulid,
-        paper_id: &Ulid,
+        talks: &[Talk],
     ) -> Result<MeetUp, UpdateMeetUpError>;
     async fn finish_meet_up(&self, id: &Ulid, link: Url) -> Result<(), UpdateMeetUpError>;
+    async fn update_meet_up(
+        &self,
+        id: &Ulid,
+        location: Location,
+        date: DateTime<Utc>,
+        capacity: Option<u32>,
+    ) -> Result<MeetUp, UpdateMeetUpError>;
+    async fn cancel_meet_up(&self, id: &Ulid) -> Result<MeetUp, UpdateMeetUpError>;
+    /// Replaces `previous_paper_id` in the lineup with `talk`, or adds `talk` to the lineup when
+    /// there is no previous paper.
+    async fn override_meet_up_schedule(
+        &self,
+        id: &Ulid,
+        previous_paper_id: Option<&Ulid>,
+        talk: &Talk,
+    ) -> Result<MeetUp, UpdateMeetUpError>;
+    /// Changes the start and duration of a talk already in the lineup.
+    async fn update_meet_up_talk(
+        &self,
+        id: &Ulid,
+        talk: &Talk,
+    ) -> Result<MeetUp, UpdateMeetUpError>;
+    async fn reopen_meet_up_voting(&self, id: &Ulid) -> Result<MeetUp, UpdateMeetUpError>;
+    async fn list_schedule_overrides(
+        &self,
+        id: &Ulid,
+    ) -> Result<Vec<ScheduleOverride>, GetMeetUpError>;
+    /// Every state the meet up went through, oldest first.
+    async fn list_meet_up_state_changes(
+        &self,
+        id: &Ulid,
+    ) -> Result<Vec<MeetUpStateChange>, GetMeetUpError>;
+    async fn get_meet_up_revision(&self, id: &Ulid) -> Result<MeetUpRevision, GetMeetUpError>;
+    /// Marks the reminder of the meet up as sent. Returns `false` when it was already sent.
+    async fn claim_meet_up_reminder(&self, id: &Ulid) -> Result<bool, UpdateMeetUpError>;
 }
 
 #[derive(Debug, Error)]
@@ -55,7 +90,7 @@ pub enum UpdateMeetUpError {
 }
 
 #[derive(Debug, Error)]
-pub enum GetFutureMeetUpError {
+pub enum ListFutureMeetUpsError {
     #[error("Unknown error: `{0}`")]
     Unknown(#[from] anyhow::Error),
 }
@@ -67,9 +102,28 @@ pub enum NewMeetUpError {
 }
 
 pub trait UserGateway {
-    async fn get_user_with_token(&self, access_token: &str) -> Result<User, GetUserError>;
-    async fn get_user_with_email(&self, email: &str) -> Result<User, GetUserError>;
+    /// Every user, by nickname.
+    async fn list_users(&self) -> Result<Vec<User>, GetUserError>;
+    /// Oldest first. Accounts of GitHub and of other providers can share an email.
+    async fn list_users_with_email(&self, email: &str) -> Result<Vec<User>, GetUserError>;
+    async fn get_user_with_github_id(&self, github_id: i64) -> Result<User, GetUserError>;
+    async fn get_user_with_oidc_subject(
+        &self,
+        provider: &str,
+        subject: &str,
+    ) -> Result<User, GetUserError>;
+    /// GitHub users that logged in before their GitHub ids were stored.
+    async fn list_users_without_github_id(&self) -> Result<Vec<User>, GetUserError>;
+    async fn get_user_with_id(&self, id: &Ulid) -> Result<User, GetUserError>;
     async fn store_user(&self, user: User) -> Result<User, StoreUserError>;
+    /// Users that did not unsubscribe from the emails.
+    async fn list_users_with_mail_notifications(&self) -> Result<Vec<User>, GetUserError>;
+    async fn has_mail_notifications(&self, user_id: &Ulid) -> Result<bool, GetUserError>;
+    async fn set_mail_notifications(
+        &self,
+        user_id: &Ulid,
+        enabled: bool,
+    ) -> Result<(), StoreUserError>;
 }
 
 #[derive(Debug, Error)]
@@ -86,11 +140,119 @@ pub enum StoreUserError {
     Unknown(#[from] anyhow::Error),
 }
 
+pub trait RoleGateway {
+    async fn get_user_role(&self, user_id: &Ulid) -> Result<Option<Role>, RoleError>;
+    /// Sets or revokes (`None`) the role and records the change.
+    async fn set_user_role(
+        &self,
+        user_id: &Ulid,
+        role: Option<Role>,
+        changed_by: Option<&Ulid>,
+    ) -> Result<(), RoleError>;
+    async fn list_user_roles(&self) -> Result<Vec<(Ulid, Role)>, RoleError>;
+    /// Whether a role of the user was ever revoked.
+    async fn has_role_revocation(&self, user_id: &Ulid) -> Result<bool, RoleError>;
+    /// Newest first.
+    async fn list_role_changes(&self) -> Result<Vec<RoleChange>, RoleError>;
+}
+
+#[derive(Debug, Error)]
+pub enum RoleError {
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
+/// Events are only ever added, never changed or deleted.
+pub trait AuditGateway {
+    async fn store_audit_event(&self, event: &AuditEvent) -> Result<(), AuditError>;
+    /// Newest first, at most `limit` events.
+    async fn list_audit_events(
+        &self,
+        filter: &AuditFilter,
+        limit: u32,
+    ) -> Result<Vec<AuditEvent>, AuditError>;
+}
+
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
+pub trait ApiTokenGateway {
+    async fn store_api_token(
+        &self,
+        api_token: &ApiToken,
+        secret_hash: &str,
+    ) -> Result<(), StoreApiTokenError>;
+    /// Every token of the user, revoked ones included, newest first.
+    async fn list_api_tokens(&self, user_id: &Ulid) -> Result<Vec<ApiToken>, GetApiTokenError>;
+    /// Only finds tokens that were not revoked, and marks them as used.
+    async fn use_api_token(&self, secret_hash: &str) -> Result<ApiToken, GetApiTokenError>;
+    /// Returns whether the user had a token with the id that was not revoked yet.
+    async fn revoke_api_token(&self, user_id: &Ulid, id: &Ulid)
+        -> Result<bool, StoreApiTokenError>;
+}
+
+#[derive(Debug, Error)]
+pub enum StoreApiTokenError {
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetApiTokenError {
+    #[error("API token not found")]
+    NotFound,
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
+pub trait SessionGateway {
+    async fn store_session(
+        &self,
+        session: &Session,
+        token_hash: &str,
+    ) -> Result<(), StoreSessionError>;
+    /// Only finds sessions that were not revoked, and marks them as seen.
+    async fn use_session(&self, token_hash: &str) -> Result<Session, GetSessionError>;
+    /// Sessions that were not revoked and did not expire, most recently seen first.
+    async fn list_sessions(&self, user_id: &Ulid) -> Result<Vec<Session>, GetSessionError>;
+    /// Returns whether the user had a session with the id that was not revoked yet.
+    async fn revoke_session(&self, user_id: &Ulid, id: &Ulid) -> Result<bool, StoreSessionError>;
+}
+
+#[derive(Debug, Error)]
+pub enum StoreSessionError {
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum GetSessionError {
+    #[error("Session not found")]
+    NotFound,
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
+pub trait MailGateway {
+    async fn send_mail(&self, to: &str, notification: &Notification) -> Result<(), SendMailError>;
+}
+
+#[derive(Debug, Error)]
+pub enum SendMailError {
+    #[error("Invalid email address `{0}`")]
+    InvalidAddress(String),
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
 pub trait GithubGateway {
     async fn user_info(
         &self,
         access_token: &AccessToken,
-    ) -> Result<(String, String), UserInfoGithubError>;
+    ) -> Result<GithubUser, UserInfoGithubError>;
     async fn refresh_token(
         &self,
         refresh_token: &AccessToken,
@@ -103,6 +265,8 @@ pub trait GithubGateway {
 
 #[derive(Debug, Error)]
 pub enum UserInfoGithubError {
+    #[error("The user has no verified email on GitHub")]
+    UnverifiedEmail,
     #[error("Unknown error: `{0}`")]
     Unknown(#[from] anyhow::Error),
 }
@@ -119,19 +283,75 @@ pub enum ExchangeCodeError {
     Unknown(#[from] anyhow::Error),
 }
 
+/// One OpenID Connect provider, like GitLab, Google or Keycloak.
+pub trait OidcGateway {
+    /// Where the browser logs in. The state comes back to the callback and the nonce in the ID
+    /// token.
+    async fn authorize_url(&self, state: &str, nonce: &str) -> Result<Url, OidcError>;
+    /// Exchanges the code of the callback and validates the ID token it returns.
+    async fn exchange_code(&self, code: &str, nonce: &str) -> Result<OidcUser, OidcError>;
+}
+
+#[derive(Debug, Error)]
+pub enum OidcError {
+    #[error("Invalid ID token: {0}")]
+    InvalidIdToken(String),
+    #[error("The provider did not share the email of the user")]
+    MissingEmail,
+    #[error("The provider did not verify the email `{0}`")]
+    UnverifiedEmail(String),
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
 pub trait PaperGateway {
+    /// `limit` is the number of papers per user and `max_papers` the number of papers in the
+    /// meet up, `None` meaning no limit.
     async fn store_paper_with_meet_up(
         &self,
         paper: &Paper,
         meet_up_id: &Ulid,
         limit: u8,
+        max_papers: Option<u32>,
     ) -> Result<(), StorePaperError>;
+    /// Updates the title, description, speaker and pool opt-in of an existing paper.
+    async fn update_paper(&self, paper: &Paper) -> Result<(), StorePaperError>;
+    /// Removes the paper and its submission to meet ups.
+    async fn delete_paper(&self, id: &Ulid) -> Result<(), DeletePaperError>;
+    /// Removes only the link between a carried over paper and the meet up, the paper stays in the
+    /// meet ups it came from. A paper left in no meet up is deleted.
+    async fn remove_paper_from_meet_up(
+        &self,
+        id: &Ulid,
+        meet_up_id: &Ulid,
+    ) -> Result<(), DeletePaperError>;
     async fn get_paper(&self, id: &Ulid) -> Result<Paper, GetPaperError>;
+    /// Only the papers the user submitted to the meet up, not the carried over ones.
     async fn get_papers_from_user_and_meet_up(
         &self,
         user_id: &Ulid,
         meet_up_id: &Ulid,
     ) -> Result<Vec<Paper>, GetPaperError>;
+    async fn get_carried_over_papers_from_user_and_meet_up(
+        &self,
+        user_id: &Ulid,
+        meet_up_id: &Ulid,
+    ) -> Result<Vec<Paper>, GetPaperError>;
+    /// Links papers from a previous meet up to this one. Carried over papers do not count
+    /// against the limit of papers per user.
+    async fn carry_over_papers(
+        &self,
+        meet_up_id: &Ulid,
+        paper_ids: &[Ulid],
+    ) -> Result<(), StorePaperError>;
+    /// Stores `paper` as a new paper carried over to the meet up in place of `carried_over_id`,
+    /// which stays as it was in the meet ups it came from.
+    async fn replace_carried_over_paper(
+        &self,
+        meet_up_id: &Ulid,
+        carried_over_id: &Ulid,
+        paper: &Paper,
+    ) -> Result<(), StorePaperError>;
     async fn get_papers_from_meet_up(&self, meet_up_id: &Ulid)
         -> Result<Vec<Paper>, GetPaperError>;
 }
@@ -140,6 +360,14 @@ pub trait PaperGateway {
 pub enum StorePaperError {
     #[error("More than limit papaers per user per meetups. Limit is `{0}`")]
     MoreThanLimitPapersPerUserPerMeetUp(u8),
+    #[error("More than the maximum number of papers for the meet up. Maximum is `{0}`")]
+    MoreThanMaxPapersPerMeetUp(u32),
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum DeletePaperError {
     #[error("Unknown error: `{0}`")]
     Unknown(#[from] anyhow::Error),
 }
@@ -169,17 +397,48 @@ pub enum VoteError {
 }
 
 pub trait MeetUpGoersGateway {
+    /// Registers the user as attending while there are seats left, or at the end of the
+    /// waitlist otherwise. `None` capacity means unlimited seats.
     async fn register_user_to_meet_up(
         &self,
         user_id: &Ulid,
         meet_up_id: &Ulid,
-    ) -> Result<(), RegisterUserError>;
+        capacity: Option<u32>,
+    ) -> Result<RegistrationStatus, RegisterUserError>;
+    /// Returns the status the user had, `None` when they were not registered.
+    async fn unregister_user_from_meet_up(
+        &self,
+        user_id: &Ulid,
+        meet_up_id: &Ulid,
+    ) -> Result<Option<RegistrationStatus>, RegisterUserError>;
     async fn is_user_registered_to_meet_up(
         &self,
         user_id: &Ulid,
         meet_up_id: &Ulid,
     ) -> Result<bool, RegisterUserError>;
+    async fn get_registration_status(
+        &self,
+        user_id: &Ulid,
+        meet_up_id: &Ulid,
+    ) -> Result<Option<RegistrationStatus>, RegisterUserError>;
+    /// Moves the first users of the waitlist to attending while there are seats left and
+    /// returns their ids.
+    async fn promote_waitlisted_users(
+        &self,
+        meet_up_id: &Ulid,
+        capacity: Option<u32>,
+    ) -> Result<Vec<Ulid>, RegisterUserError>;
 
+    /// Every registered user, attending ones first and then the waitlist in order.
+    async fn list_attendees(&self, meet_up_id: &Ulid) -> Result<Vec<Attendee>, GetAttendeesError>;
+    /// Sets or clears (`None`) when the user arrived. Returns whether the user is registered.
+    async fn check_in_user(
+        &self,
+        user_id: &Ulid,
+        meet_up_id: &Ulid,
+        checked_in_at: Option<DateTime<Utc>>,
+    ) -> Result<bool, RegisterUserError>;
+    /// Only counts attending users, not the waitlisted ones.
     async fn get_number_attendees_from_meet_up(
         &self,
         meet_up_id: &Ulid,
@@ -1,7 +1,9 @@
 pub use entities::*;
 pub use gateways::*;
 pub use use_cases::*;
+pub use voting_method::*;
 
 mod entities;
 mod gateways;
 mod use_cases;
+mod voting_method;
@@ -0,0 +1,122 @@
+use serde_json::json;
+use shared::utc_now;
+use thiserror::Error;
+use ulid::Ulid;
+
+use crate::{
+    use_cases::audit::record, ApiToken, ApiTokenGateway, ApiTokenScope, AuditAction, AuditGateway,
+    User, UserGateway,
+};
+
+/// Returns the token and its secret, which can not be recovered later.
+pub async fn create_api_token(
+    api_token_gateway: &impl ApiTokenGateway,
+    audit_gateway: &impl AuditGateway,
+    user: &User,
+    name: &str,
+    scopes: &[ApiTokenScope],
+    is_admin: bool,
+) -> Result<(ApiToken, String), CreateApiTokenError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(CreateApiTokenError::EmptyName);
+    }
+    if scopes.is_empty() {
+        return Err(CreateApiTokenError::NoScopes);
+    }
+    if scopes.contains(&ApiTokenScope::Admin) && !is_admin {
+        return Err(CreateApiTokenError::AdminScopeNotAllowed);
+    }
+    let api_token = ApiToken {
+        id: Ulid::new(),
+        user_id: user.id,
+        name: name.to_string(),
+        scopes: ApiTokenScope::ALL
+            .into_iter()
+            .filter(|scope| scopes.contains(scope))
+            .collect(),
+        created_at: utc_now(),
+        last_used_at: None,
+        revoked_at: None,
+    };
+    let secret = ApiToken::generate_secret();
+    api_token_gateway
+        .store_api_token(&api_token, &ApiToken::hash_secret(&secret))
+        .await
+        .map_err(|err| CreateApiTokenError::Unknown(err.into()))?;
+    record(
+        audit_gateway,
+        Some(&user.id),
+        AuditAction::CreateApiToken,
+        None,
+        Some(&api_token.id),
+        json!({ "name": api_token.name, "scopes": api_token.scopes }),
+    )
+    .await?;
+    Ok((api_token, secret))
+}
+
+#[derive(Debug, Error)]
+pub enum CreateApiTokenError {
+    #[error("Token name is required")]
+    EmptyName,
+    #[error("Token needs at least one scope")]
+    NoScopes,
+    #[error("Only admins can create admin tokens")]
+    AdminScopeNotAllowed,
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
+pub async fn list_api_tokens(
+    api_token_gateway: &impl ApiTokenGateway,
+    user: &User,
+) -> anyhow::Result<Vec<ApiToken>> {
+    Ok(api_token_gateway.list_api_tokens(&user.id).await?)
+}
+
+pub async fn revoke_api_token(
+    api_token_gateway: &impl ApiTokenGateway,
+    audit_gateway: &impl AuditGateway,
+    user: &User,
+    id: &Ulid,
+) -> Result<(), RevokeApiTokenError> {
+    if !api_token_gateway
+        .revoke_api_token(&user.id, id)
+        .await
+        .map_err(|err| RevokeApiTokenError::Unknown(err.into()))?
+    {
+        return Err(RevokeApiTokenError::NotFound(*id));
+    }
+    record(
+        audit_gateway,
+        Some(&user.id),
+        AuditAction::RevokeApiToken,
+        None,
+        Some(id),
+        json!({}),
+    )
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum RevokeApiTokenError {
+    #[error("API token `{0}` not found")]
+    NotFound(Ulid),
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
+/// Finds the user of a `Bearer` secret. Revoked tokens are rejected.
+pub async fn login_with_api_token(
+    api_token_gateway: &impl ApiTokenGateway,
+    user_gateway: &impl UserGateway,
+    secret: &str,
+) -> anyhow::Result<(User, ApiToken)> {
+    let api_token = api_token_gateway
+        .use_api_token(&ApiToken::hash_secret(secret))
+        .await?;
+    let user = user_gateway.get_user_with_id(&api_token.user_id).await?;
+    Ok((user, api_token))
+}
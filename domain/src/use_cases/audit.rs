@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+use ulid::Ulid;
+
+use crate::{AuditAction, AuditEvent, AuditFilter, AuditGateway, GetUserError, User, UserGateway};
+
+/// The admin page shows only the latest events that match the filter.
+pub const AUDIT_LOG_LIMIT: u32 = 500;
+
+pub(crate) async fn record(
+    audit_gateway: &impl AuditGateway,
+    actor_id: Option<&Ulid>,
+    action: AuditAction,
+    meet_up_id: Option<&Ulid>,
+    target_id: Option<&Ulid>,
+    payload: Value,
+) -> anyhow::Result<()> {
+    Ok(audit_gateway
+        .store_audit_event(&AuditEvent::new(
+            actor_id.copied(),
+            action,
+            meet_up_id.copied(),
+            target_id.copied(),
+            payload,
+        ))
+        .await?)
+}
+
+/// The latest events matching the filter, newest first, with their actor when it still exists.
+pub async fn list_audit_events(
+    audit_gateway: &impl AuditGateway,
+    user_gateway: &impl UserGateway,
+    filter: &AuditFilter,
+) -> anyhow::Result<Vec<(AuditEvent, Option<User>)>> {
+    let mut actors: HashMap<Ulid, Option<User>> = HashMap::new();
+    let mut events = vec![];
+    for event in audit_gateway
+        .list_audit_events(filter, AUDIT_LOG_LIMIT)
+        .await?
+    {
+        let actor = match event.actor_id {
+            Some(actor_id) => match actors.get(&actor_id) {
+                Some(actor) => actor.clone(),
+                None => {
+                    let actor = match user_gateway.get_user_with_id(&actor_id).await {
+                        Ok(user) => Some(user),
+                        Err(GetUserError::NotFound) => None,
+                        Err(err) => return Err(err.into()),
+                    };
+                    actors.insert(actor_id, actor.clone());
+                    actor
+                }
+            },
+            None => None,
+        };
+        events.push((event, actor));
+    }
+    Ok(events)
+}
@@ -1,21 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use shared::utc_now;
 use thiserror::Error;
 use ulid::Ulid;
 
 use crate::{
-    GetPaperError, MeetUp, MeetUpGateway, MeetUpState, Paper, PaperGateway, StorePaperError, User,
+    use_cases::audit::record, AuditAction, AuditGateway, GetMeetUpError, GetPaperError, MeetUp,
+    MeetUpGateway, MeetUpState, Paper, PaperGateway, StorePaperError, User,
 };
 
-const MAX_PAPERS_PER_USER_PER_MEET_UP: u8 = 2;
-
+/// Returns the meet up, the papers submitted by the user, the user papers carried over from the
+/// previous meet up and whether the user reached the limit of submitted papers.
 pub async fn show_call_for_papers(
     paper_gateway: &impl PaperGateway,
     meet_up_gateway: &impl MeetUpGateway,
+    meet_up_id: &Ulid,
     user: &User,
-) -> anyhow::Result<(MeetUp, Vec<Paper>, bool)> {
-    let meet_up = meet_up_gateway
-        .get_future_meet_up()
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("No future meetups found"))?;
+) -> anyhow::Result<(MeetUp, Vec<Paper>, Vec<Paper>, bool)> {
+    let meet_up = meet_up_gateway.get_meet_up(meet_up_id).await?;
     if meet_up.state != MeetUpState::CallForPapers {
         return Err(anyhow::anyhow!(
             "Invalid meet up state: {:?}",
@@ -25,36 +27,203 @@ pub async fn show_call_for_papers(
     let papers = paper_gateway
         .get_papers_from_user_and_meet_up(&user.id, &meet_up.id)
         .await?;
-    let is_limit_of_papers_sent = papers.len() as u8 >= MAX_PAPERS_PER_USER_PER_MEET_UP;
-    Ok((meet_up, papers, is_limit_of_papers_sent))
+    let carried_over_papers = paper_gateway
+        .get_carried_over_papers_from_user_and_meet_up(&user.id, &meet_up.id)
+        .await?;
+    let is_limit_of_papers_sent = papers.len() as u8 >= meet_up.settings.papers_per_user;
+    Ok((
+        meet_up,
+        papers,
+        carried_over_papers,
+        is_limit_of_papers_sent,
+    ))
 }
 
 pub async fn submit_paper(
     paper_gateway: &impl PaperGateway,
     meet_up_gateway: &impl MeetUpGateway,
+    audit_gateway: &impl AuditGateway,
+    meet_up_id: &Ulid,
     paper: Paper,
 ) -> Result<(), SubmitPaperError> {
-    let future_meet_up = meet_up_gateway
-        .get_future_meet_up()
+    let meet_up = meet_up_gateway
+        .get_meet_up(meet_up_id)
         .await
-        .map_err(|err| SubmitPaperError::Unknown(err.into()))?
-        .ok_or(SubmitPaperError::NoFutureMeetUpFound)?;
+        .map_err(|err| match err {
+            GetMeetUpError::NotFound(id) => SubmitPaperError::MeetUpNotFound(id),
+            _ => SubmitPaperError::Unknown(err.into()),
+        })?;
     // We can have a concurrency problem here, but we are not handling it for now.
-    if future_meet_up.state != MeetUpState::CallForPapers {
+    if meet_up.state != MeetUpState::CallForPapers {
         return Err(SubmitPaperError::InvalidMeetUpState(Box::new(
-            future_meet_up.state,
+            meet_up.state,
         )));
     }
+    if let Some(deadline) = meet_up
+        .settings
+        .call_for_papers_deadline
+        .filter(|&deadline| utc_now() > deadline)
+    {
+        return Err(SubmitPaperError::CallForPapersClosed(deadline));
+    }
 
     paper_gateway
-        .store_paper_with_meet_up(&paper, &future_meet_up.id, MAX_PAPERS_PER_USER_PER_MEET_UP)
+        .store_paper_with_meet_up(
+            &paper,
+            &meet_up.id,
+            meet_up.settings.papers_per_user,
+            meet_up.settings.max_papers,
+        )
         .await
         .map_err(|err| match err {
             StorePaperError::MoreThanLimitPapersPerUserPerMeetUp(limit) => {
                 SubmitPaperError::MoreThanLimitPapersPerUserPerMeetUp(limit)
             }
+            StorePaperError::MoreThanMaxPapersPerMeetUp(max) => {
+                SubmitPaperError::MoreThanMaxPapersPerMeetUp(max)
+            }
             _ => SubmitPaperError::Unknown(err.into()),
-        })
+        })?;
+    record(
+        audit_gateway,
+        Some(&paper.user_id),
+        AuditAction::SubmitPaper,
+        Some(&meet_up.id),
+        Some(&paper.id),
+        paper_payload(&paper),
+    )
+    .await?;
+    Ok(())
+}
+
+/// Changes title, description, speaker and pool opt-in of a paper the user submitted to the meet
+/// up, the email and author of the given paper are ignored. A carried over paper is copied, so the
+/// meet ups it came from keep it as it was, and the copy with its new id is returned.
+pub async fn edit_paper(
+    paper_gateway: &impl PaperGateway,
+    meet_up_gateway: &impl MeetUpGateway,
+    audit_gateway: &impl AuditGateway,
+    meet_up_id: &Ulid,
+    user: &User,
+    paper: Paper,
+) -> Result<Paper, ChangePaperError> {
+    let (current, carried_over) =
+        authored_paper(paper_gateway, meet_up_gateway, meet_up_id, user, &paper.id).await?;
+    let carried_over_id = current.id;
+    let paper = Paper {
+        id: match carried_over {
+            true => Ulid::new(),
+            false => current.id,
+        },
+        title: paper.title,
+        description: paper.description,
+        speaker: paper.speaker,
+        keep_in_pool: paper.keep_in_pool,
+        ..current
+    };
+    if carried_over {
+        paper_gateway
+            .replace_carried_over_paper(meet_up_id, &carried_over_id, &paper)
+            .await
+    } else {
+        paper_gateway.update_paper(&paper).await
+    }
+    .map_err(|err| ChangePaperError::Unknown(err.into()))?;
+    record(
+        audit_gateway,
+        Some(&user.id),
+        AuditAction::EditPaper,
+        Some(meet_up_id),
+        Some(&paper.id),
+        paper_payload(&paper),
+    )
+    .await?;
+    Ok(paper)
+}
+
+/// Removes a paper the user submitted to the meet up, freeing a submission slot. A paper carried
+/// over from a previous meet up is only taken out of this one.
+pub async fn withdraw_paper(
+    paper_gateway: &impl PaperGateway,
+    meet_up_gateway: &impl MeetUpGateway,
+    audit_gateway: &impl AuditGateway,
+    meet_up_id: &Ulid,
+    user: &User,
+    paper_id: &Ulid,
+) -> Result<(), ChangePaperError> {
+    let (paper, carried_over) =
+        authored_paper(paper_gateway, meet_up_gateway, meet_up_id, user, paper_id).await?;
+    if carried_over {
+        paper_gateway
+            .remove_paper_from_meet_up(&paper.id, meet_up_id)
+            .await
+    } else {
+        paper_gateway.delete_paper(&paper.id).await
+    }
+    .map_err(|err| ChangePaperError::Unknown(err.into()))?;
+    record(
+        audit_gateway,
+        Some(&user.id),
+        AuditAction::WithdrawPaper,
+        Some(meet_up_id),
+        Some(&paper.id),
+        json!({ "title": paper.title }),
+    )
+    .await?;
+    Ok(())
+}
+
+fn paper_payload(paper: &Paper) -> serde_json::Value {
+    json!({
+        "title": paper.title,
+        "speaker": paper.speaker,
+        "keep_in_pool": paper.keep_in_pool,
+    })
+}
+
+/// Papers can only be changed by their author while the meet up is still taking submissions, up to
+/// the call for papers deadline. Also returns whether the paper was carried over from a previous
+/// meet up.
+async fn authored_paper(
+    paper_gateway: &impl PaperGateway,
+    meet_up_gateway: &impl MeetUpGateway,
+    meet_up_id: &Ulid,
+    user: &User,
+    paper_id: &Ulid,
+) -> Result<(Paper, bool), ChangePaperError> {
+    let meet_up = meet_up_gateway
+        .get_meet_up(meet_up_id)
+        .await
+        .map_err(|err| match err {
+            GetMeetUpError::NotFound(id) => ChangePaperError::MeetUpNotFound(id),
+            _ => ChangePaperError::Unknown(err.into()),
+        })?;
+    if meet_up.state != MeetUpState::CallForPapers {
+        return Err(ChangePaperError::InvalidMeetUpState(Box::new(
+            meet_up.state,
+        )));
+    }
+    if let Some(deadline) = meet_up
+        .settings
+        .call_for_papers_deadline
+        .filter(|&deadline| utc_now() > deadline)
+    {
+        return Err(ChangePaperError::CallForPapersClosed(deadline));
+    }
+    let submitted = paper_gateway
+        .get_papers_from_user_and_meet_up(&user.id, &meet_up.id)
+        .await
+        .map_err(|err| ChangePaperError::Unknown(err.into()))?;
+    let carried_over = paper_gateway
+        .get_carried_over_papers_from_user_and_meet_up(&user.id, &meet_up.id)
+        .await
+        .map_err(|err| ChangePaperError::Unknown(err.into()))?;
+    submitted
+        .into_iter()
+        .map(|paper| (paper, false))
+        .chain(carried_over.into_iter().map(|paper| (paper, true)))
+        .find(|(paper, _)| paper.id == *paper_id)
+        .ok_or(ChangePaperError::PaperNotFound(*paper_id))
 }
 
 pub async fn get_paper(
@@ -68,10 +237,28 @@ pub async fn get_paper(
 pub enum SubmitPaperError {
     #[error("Invalid meet up state: {0}")]
     InvalidMeetUpState(Box<MeetUpState>),
-    #[error("No future meetups found")]
-    NoFutureMeetUpFound,
+    #[error("Meet up with id `{0}` not found")]
+    MeetUpNotFound(Ulid),
     #[error("More than limit papers per user per meetups. Limit is `{0}`")]
     MoreThanLimitPapersPerUserPerMeetUp(u8),
+    #[error("More than the maximum number of papers for the meet up. Maximum is `{0}`")]
+    MoreThanMaxPapersPerMeetUp(u32),
+    #[error("Call for papers closed at `{0}`")]
+    CallForPapersClosed(DateTime<Utc>),
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
+#[derive(Debug, Error)]
+pub enum ChangePaperError {
+    #[error("Invalid meet up state: {0}")]
+    InvalidMeetUpState(Box<MeetUpState>),
+    #[error("Meet up with id `{0}` not found")]
+    MeetUpNotFound(Ulid),
+    #[error("Paper `{0}` not found among the user papers for this meet up")]
+    PaperNotFound(Ulid),
+    #[error("Call for papers closed at `{0}`")]
+    CallForPapersClosed(DateTime<Utc>),
     #[error("Unknown error: `{0}`")]
     Unknown(#[from] anyhow::Error),
 }
@@ -0,0 +1,63 @@
+use crate::{FeedEntry, FeedEntryKind, MeetUpGateway, MeetUpState, MeetUpStateKind};
+
+/// Every state change of the calendar meet ups, newest first. Each talk of a lineup is an entry
+/// of its own, dated by when it joined the lineup.
+pub async fn list_feed_entries(gateway: &impl MeetUpGateway) -> anyhow::Result<Vec<FeedEntry>> {
+    let mut entries = vec![];
+    for meet_up in crate::list_calendar_meet_ups(gateway).await? {
+        let state_changes = gateway.list_meet_up_state_changes(&meet_up.id).await?;
+        for change in &state_changes {
+            let kind = match (change.state, &meet_up.state) {
+                (MeetUpStateKind::CallForPapers, _) => FeedEntryKind::CallForPapersOpened,
+                (MeetUpStateKind::Voting, _) => FeedEntryKind::VotingOpened,
+                (MeetUpStateKind::Done, MeetUpState::Done { link, .. }) => {
+                    FeedEntryKind::Done(link.clone())
+                }
+                (MeetUpStateKind::Cancelled, MeetUpState::Cancelled) => FeedEntryKind::Cancelled,
+                // Lineups are published per talk below.
+                _ => continue,
+            };
+            entries.push(FeedEntry {
+                meet_up: meet_up.clone(),
+                kind,
+                date: change.date,
+            });
+        }
+        if meet_up.talks().is_empty() {
+            continue;
+        }
+        // Meet ups scheduled before the state changes were recorded only know when they were done.
+        let Some(scheduled_at) = state_changes
+            .iter()
+            .rev()
+            .find(|change| change.state == MeetUpStateKind::Scheduled)
+            .or_else(|| {
+                state_changes
+                    .iter()
+                    .find(|change| change.state == MeetUpStateKind::Done)
+            })
+            .map(|change| change.date)
+        else {
+            continue;
+        };
+        let overrides = gateway.list_schedule_overrides(&meet_up.id).await?;
+        for talk in meet_up.talks() {
+            let date = overrides
+                .iter()
+                .rev()
+                .find(|schedule_override| schedule_override.paper_id == Some(talk.paper.id))
+                .map(|schedule_override| schedule_override.date)
+                .filter(|date| *date > scheduled_at)
+                .unwrap_or(scheduled_at);
+            entries.push(FeedEntry {
+                meet_up: meet_up.clone(),
+                kind: FeedEntryKind::TalkScheduled(talk.clone()),
+                date,
+            });
+        }
+    }
+    // Reversed first so the latest change comes first among those on the same date.
+    entries.reverse();
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.date));
+    Ok(entries)
+}
@@ -1,46 +1,135 @@
 use anyhow::bail;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use rand::distributions::{Alphanumeric, DistString};
+use serde_json::json;
+use sha2::Sha256;
+use shared::utc_now;
+use thiserror::Error;
 use ulid::Ulid;
 
-use crate::{AccessToken, GetUserError, GithubGateway, LoginMethod, User, UserGateway};
+use crate::{
+    use_cases::audit::record, AccessToken, AuditAction, AuditGateway, GetUserError, GithubGateway,
+    LoginMethod, OidcGateway, Session, SessionGateway, User, UserGateway,
+};
 
-pub async fn login_with_access_token(
-    user_gateway: &impl UserGateway,
-    github_gateway: &impl GithubGateway,
-    access_token: &str,
-) -> anyhow::Result<User> {
-    let user = user_gateway.get_user_with_token(access_token).await?;
-    if !user.access_token.is_expired() {
-        return Ok(user);
+/// Longer `User-Agent`s are cut, they are only shown to tell the sessions apart.
+const MAX_DEVICE_LENGTH: usize = 200;
+
+/// How long the user has to finish the login on GitHub.
+const LOGIN_STATE_DURATION_MINUTES: i64 = 10;
+
+const MAX_RETURN_TO_LENGTH: usize = 200;
+
+/// OAuth `state` for the GitHub login, tied to the nonce kept in a pre-login cookie so a login
+/// started by another site can not be finished in the browser of the user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginState {
+    /// `nonce.expiration.return_to.signature`, with `return_to` and the signature hex encoded.
+    pub state: String,
+    pub nonce: String,
+    pub expire_at: DateTime<Utc>,
+}
+
+/// Paths outside of the site, like `//other.site`, fall back to the home page.
+pub fn issue_login_state(secret_key: &[u8], return_to: Option<&str>) -> LoginState {
+    let nonce = Alphanumeric.sample_string(&mut rand::thread_rng(), 32);
+    let expire_at = utc_now() + chrono::Duration::minutes(LOGIN_STATE_DURATION_MINUTES);
+    let payload = format!(
+        "{nonce}.{}.{}",
+        expire_at.timestamp(),
+        hex::encode(local_path(return_to))
+    );
+    let signature = hex::encode(signature_mac(secret_key, &payload).finalize().into_bytes());
+    LoginState {
+        state: format!("{payload}.{signature}"),
+        nonce,
+        expire_at,
     }
-    match user.login_method {
-        LoginMethod::Github {
-            mut access_token,
-            mut refresh_token,
-        } => {
-            if access_token.is_expired() {
-                if refresh_token.is_expired() {
-                    return Err(anyhow::anyhow!("Refresh token is expired"));
-                }
-                let (new_access_token, new_refresh_token) =
-                    github_gateway.refresh_token(&refresh_token).await?;
-                access_token = new_access_token;
-                refresh_token = new_refresh_token;
-            }
-            let (nickname, email) = github_gateway.user_info(&access_token).await?;
-            let user = user_gateway
-                .store_user(User {
-                    id: user.id,
-                    access_token: AccessToken::generate_new(),
-                    login_method: LoginMethod::Github {
-                        access_token,
-                        refresh_token,
-                    },
-                    nickname,
-                    email,
-                })
-                .await?;
-            Ok(user)
+}
+
+/// Returns the path to go back to after the login.
+pub fn verify_login_state(
+    secret_key: &[u8],
+    state: &str,
+    nonce: &str,
+) -> Result<String, LoginStateError> {
+    let (payload, signature) = state.rsplit_once('.').ok_or(LoginStateError::Invalid)?;
+    let signature = hex::decode(signature).map_err(|_| LoginStateError::Invalid)?;
+    signature_mac(secret_key, payload)
+        .verify_slice(&signature)
+        .map_err(|_| LoginStateError::Invalid)?;
+    let [state_nonce, expiration, return_to] = payload
+        .splitn(3, '.')
+        .collect::<Vec<_>>()
+        .try_into()
+        .map_err(|_| LoginStateError::Invalid)?;
+    if state_nonce != nonce {
+        return Err(LoginStateError::NonceMismatch);
+    }
+    let expiration = expiration
+        .parse::<i64>()
+        .map_err(|_| LoginStateError::Invalid)?;
+    if utc_now().timestamp() > expiration {
+        return Err(LoginStateError::Expired);
+    }
+    let return_to = hex::decode(return_to)
+        .ok()
+        .and_then(|return_to| String::from_utf8(return_to).ok())
+        .ok_or(LoginStateError::Invalid)?;
+    Ok(local_path(Some(&return_to)))
+}
+
+#[derive(Debug, Error)]
+pub enum LoginStateError {
+    #[error("Login state is invalid")]
+    Invalid,
+    #[error("Login state is expired")]
+    Expired,
+    #[error("Login state was not started in this browser")]
+    NonceMismatch,
+}
+
+/// Token for the `X-CSRF-Token` header of a session. It is derived from the token in the session
+/// cookie, so it changes with every login and nothing else has to be stored.
+pub fn issue_csrf_token(secret_key: &[u8], session_token: &str) -> String {
+    hex::encode(
+        signature_mac(secret_key, &csrf_payload(session_token))
+            .finalize()
+            .into_bytes(),
+    )
+}
+
+pub fn verify_csrf_token(secret_key: &[u8], session_token: &str, csrf_token: &str) -> bool {
+    hex::decode(csrf_token).is_ok_and(|csrf_token| {
+        signature_mac(secret_key, &csrf_payload(session_token))
+            .verify_slice(&csrf_token)
+            .is_ok()
+    })
+}
+
+fn csrf_payload(session_token: &str) -> String {
+    format!("csrf.{session_token}")
+}
+
+fn signature_mac(secret_key: &[u8], payload: &str) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret_key).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+fn local_path(return_to: Option<&str>) -> String {
+    match return_to {
+        Some(path)
+            if path.starts_with('/')
+                && !path.starts_with("//")
+                && path.len() <= MAX_RETURN_TO_LENGTH
+                && !path.contains(|char: char| char == '\\' || char.is_control()) =>
+        {
+            path.to_string()
         }
+        _ => "/".to_string(),
     }
 }
 
@@ -50,38 +139,219 @@ pub async fn login_with_github_code(
     code: String,
 ) -> anyhow::Result<User> {
     let (access_token, refresh_token) = github_gateway.exchange_code(&code).await?;
-    let (nickname, email) = github_gateway.user_info(&access_token).await?;
+    let github_user = github_gateway.user_info(&access_token).await?;
     // This code can have problems with concurrency. But this should be very unlikely
-    let user = match user_gateway.get_user_with_email(&email).await {
-        Ok(user) => {
-            user_gateway
-                .store_user(User {
-                    id: user.id,
-                    access_token: AccessToken::generate_new(),
-                    login_method: LoginMethod::Github {
-                        access_token,
-                        refresh_token,
-                    },
-                    nickname,
-                    email,
-                })
-                .await?
-        }
-        Err(GetUserError::NotFound) => {
-            user_gateway
-                .store_user(User {
-                    id: Ulid::new(),
-                    access_token: AccessToken::generate_new(),
-                    login_method: LoginMethod::Github {
-                        access_token,
-                        refresh_token,
-                    },
-                    nickname,
-                    email,
-                })
-                .await?
-        }
+    let id = match user_gateway.get_user_with_github_id(github_user.id).await {
+        Ok(user) => user.id,
+        // Logged in before the GitHub ids were stored, the email is all there is to match. Emails
+        // were unique back then, so there is at most one.
+        Err(GetUserError::NotFound) => user_gateway
+            .list_users_with_email(&github_user.email)
+            .await?
+            .into_iter()
+            .find(|user| {
+                matches!(
+                    user.login_method,
+                    LoginMethod::Github {
+                        github_id: None,
+                        ..
+                    }
+                )
+            })
+            .map_or_else(Ulid::new, |user| user.id),
         Err(err) => bail!("Error: {:?}", err),
     };
+    let user = user_gateway
+        .store_user(User {
+            id,
+            login_method: LoginMethod::Github {
+                github_id: Some(github_user.id),
+                access_token,
+                refresh_token,
+            },
+            nickname: github_user.login,
+            email: github_user.email,
+        })
+        .await?;
     Ok(user)
 }
+
+/// Users of OpenID Connect providers are only matched by their account, never by email, so a
+/// provider can not take over the accounts made on GitHub or on other providers.
+pub async fn login_with_oidc_code(
+    user_gateway: &impl UserGateway,
+    oidc_gateway: &impl OidcGateway,
+    code: &str,
+    nonce: &str,
+) -> anyhow::Result<User> {
+    let oidc_user = oidc_gateway.exchange_code(code, nonce).await?;
+    let id = match user_gateway
+        .get_user_with_oidc_subject(&oidc_user.provider, &oidc_user.subject)
+        .await
+    {
+        Ok(user) => user.id,
+        Err(GetUserError::NotFound) => Ulid::new(),
+        Err(err) => bail!("Error: {:?}", err),
+    };
+    let user = user_gateway
+        .store_user(User {
+            id,
+            nickname: oidc_user.nickname,
+            email: oidc_user.email,
+            login_method: LoginMethod::Oidc {
+                provider: oidc_user.provider,
+                subject: oidc_user.subject,
+            },
+        })
+        .await?;
+    Ok(user)
+}
+
+/// Looks up the GitHub ids of the users that logged in before they were stored, using their
+/// GitHub tokens. Returns the result for each user, the ones that fail are tried again on the next
+/// call or matched by email on their next login.
+pub async fn backfill_github_ids(
+    user_gateway: &impl UserGateway,
+    github_gateway: &impl GithubGateway,
+) -> anyhow::Result<Vec<(Ulid, anyhow::Result<i64>)>> {
+    let mut results = vec![];
+    for user in user_gateway.list_users_without_github_id().await? {
+        let id = user.id;
+        results.push((
+            id,
+            backfill_github_id(user_gateway, github_gateway, user).await,
+        ));
+    }
+    Ok(results)
+}
+
+async fn backfill_github_id(
+    user_gateway: &impl UserGateway,
+    github_gateway: &impl GithubGateway,
+    user: User,
+) -> anyhow::Result<i64> {
+    let LoginMethod::Github {
+        mut access_token,
+        mut refresh_token,
+        ..
+    } = user.login_method
+    else {
+        bail!("Not a GitHub user");
+    };
+    if access_token.is_expired() {
+        if refresh_token.is_expired() {
+            bail!("Refresh token is expired");
+        }
+        (access_token, refresh_token) = github_gateway.refresh_token(&refresh_token).await?;
+    }
+    let github_user = github_gateway.user_info(&access_token).await?;
+    match user_gateway.get_user_with_github_id(github_user.id).await {
+        Ok(other) => bail!(
+            "GitHub account {} already belongs to user {}",
+            github_user.id,
+            other.id
+        ),
+        Err(GetUserError::NotFound) => {}
+        Err(err) => bail!("Error: {:?}", err),
+    }
+    user_gateway
+        .store_user(User {
+            login_method: LoginMethod::Github {
+                github_id: Some(github_user.id),
+                access_token,
+                refresh_token,
+            },
+            ..user
+        })
+        .await?;
+    Ok(github_user.id)
+}
+
+/// Returns the session and its token, which only the cookie of the browser keeps.
+pub async fn start_session(
+    session_gateway: &impl SessionGateway,
+    user: &User,
+    device: &str,
+) -> anyhow::Result<(Session, String)> {
+    let token = AccessToken::generate_new();
+    let now = utc_now();
+    let session = Session {
+        id: Ulid::new(),
+        user_id: user.id,
+        device: device.trim().chars().take(MAX_DEVICE_LENGTH).collect(),
+        created_at: now,
+        last_seen_at: now,
+        expire_at: *token.expire_at(),
+    };
+    session_gateway
+        .store_session(&session, &Session::hash_token(token.token()))
+        .await?;
+    Ok((session, token.token().to_string()))
+}
+
+/// Finds the user of a session cookie. Revoked and expired sessions are rejected.
+pub async fn login_with_session(
+    session_gateway: &impl SessionGateway,
+    user_gateway: &impl UserGateway,
+    token: &str,
+) -> anyhow::Result<(User, Session)> {
+    let session = session_gateway
+        .use_session(&Session::hash_token(token))
+        .await?;
+    if session.is_expired() {
+        bail!("Session is expired");
+    }
+    let user = user_gateway.get_user_with_id(&session.user_id).await?;
+    Ok((user, session))
+}
+
+pub async fn list_sessions(
+    session_gateway: &impl SessionGateway,
+    user: &User,
+) -> anyhow::Result<Vec<Session>> {
+    Ok(session_gateway.list_sessions(&user.id).await?)
+}
+
+/// Logs a device of the user out.
+pub async fn revoke_session(
+    session_gateway: &impl SessionGateway,
+    audit_gateway: &impl AuditGateway,
+    user: &User,
+    id: &Ulid,
+) -> Result<(), RevokeSessionError> {
+    if !session_gateway
+        .revoke_session(&user.id, id)
+        .await
+        .map_err(|err| RevokeSessionError::Unknown(err.into()))?
+    {
+        return Err(RevokeSessionError::NotFound(*id));
+    }
+    record(
+        audit_gateway,
+        Some(&user.id),
+        AuditAction::RevokeSession,
+        None,
+        Some(id),
+        json!({}),
+    )
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum RevokeSessionError {
+    #[error("Session `{0}` not found")]
+    NotFound(Ulid),
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
+
+pub async fn end_session(
+    session_gateway: &impl SessionGateway,
+    session: &Session,
+) -> anyhow::Result<()> {
+    session_gateway
+        .revoke_session(&session.user_id, &session.id)
+        .await?;
+    Ok(())
+}
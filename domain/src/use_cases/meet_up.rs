@@ -1,44 +1,230 @@
+use std::collections::{HashMap, HashSet};
+
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
+use serde_json::json;
+use shared::utc_now;
 use thiserror::Error;
 use ulid::Ulid;
 use url::Url;
 
 use crate::{
-    GetFutureMeetUpError, GetMeetUpError, Location, MeetUp, MeetUpGateway, MeetUpMetadata,
-    MeetUpState, NewMeetUpError, VoteDecider, VoteGateway,
+    use_cases::audit::record, AuditAction, AuditGateway, GetMeetUpError, ListFutureMeetUpsError,
+    Location, MeetUp, MeetUpGateway, MeetUpGoersGateway, MeetUpMetadata, MeetUpRevision,
+    MeetUpSettings, MeetUpState, NewMeetUpError, Paper, PaperGateway, Talk, VoteDecider,
+    VoteGateway, VotingMethod,
 };
 
+/// Talks are placed back to back with this duration until an admin changes them.
+pub const DEFAULT_TALK_DURATION_MINUTES: u32 = 45;
+
+/// Creates the meet up in call for papers, already carrying over the papers that were not
+/// selected in the previous meet up when their authors opted in.
+#[allow(clippy::too_many_arguments)]
 pub async fn create_new_meet_up(
     gateway: &impl MeetUpGateway,
+    paper_gateway: &impl PaperGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: &Ulid,
     location: Location,
     date: DateTime<Utc>,
+    voting_method: VotingMethod,
+    number_of_talks: u8,
+    capacity: Option<u32>,
+    settings: MeetUpSettings,
 ) -> Result<MeetUp, NewMeetUpError> {
-    gateway.new_meet_up(Ulid::new(), location, date).await
+    let capacity = on_site_capacity(&location, capacity);
+    let previous_meet_up = previous_meet_up(gateway).await?;
+    let meet_up = gateway
+        .new_meet_up(
+            &MeetUp::new(
+                Ulid::new(),
+                MeetUpState::CallForPapers,
+                location,
+                date,
+                voting_method,
+                number_of_talks.max(1),
+                capacity,
+            )
+            .with_settings(MeetUpSettings {
+                papers_per_user: settings.papers_per_user.max(1),
+                ..settings
+            }),
+        )
+        .await?;
+    if let Some(previous_meet_up) = previous_meet_up {
+        let lineup = match &previous_meet_up.state {
+            MeetUpState::Scheduled(talks) | MeetUpState::Done { talks, .. } => talks.as_slice(),
+            _ => &[],
+        };
+        let open_papers =
+            papers_of_open_meet_ups(gateway, paper_gateway, &meet_up.id, &previous_meet_up.id)
+                .await?;
+        let paper_ids = paper_gateway
+            .get_papers_from_meet_up(&previous_meet_up.id)
+            .await
+            .map_err(|err| NewMeetUpError::Unknown(err.into()))?
+            .into_iter()
+            .filter(|paper| paper.keep_in_pool)
+            .filter(|paper| !lineup.iter().any(|talk| talk.paper.id == paper.id))
+            .filter(|paper| !open_papers.contains(&paper.id))
+            .map(|paper| paper.id)
+            .collect::<Vec<_>>();
+        paper_gateway
+            .carry_over_papers(&meet_up.id, &paper_ids)
+            .await
+            .map_err(|err| NewMeetUpError::Unknown(err.into()))?;
+    }
+    record(
+        audit_gateway,
+        Some(actor_id),
+        AuditAction::CreateMeetUp,
+        Some(&meet_up.id),
+        None,
+        json!({
+            "location": meet_up.location,
+            "date": meet_up.date,
+            "voting_method": meet_up.voting_method,
+            "number_of_talks": meet_up.number_of_talks,
+            "capacity": meet_up.capacity,
+        }),
+    )
+    .await?;
+    Ok(meet_up)
 }
 
-pub async fn move_future_meet_up_to_voting(gateway: &impl MeetUpGateway) -> anyhow::Result<MeetUp> {
-    let meet_up = gateway
-        .get_future_meet_up()
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("No future meetups found"))?;
+/// The latest meet up that got a lineup, scheduled or already done.
+async fn previous_meet_up(gateway: &impl MeetUpGateway) -> Result<Option<MeetUp>, NewMeetUpError> {
+    let scheduled = gateway
+        .list_future_meet_ups()
+        .await
+        .map_err(|err| NewMeetUpError::Unknown(err.into()))?
+        .into_iter()
+        .filter(|meet_up| matches!(meet_up.state, MeetUpState::Scheduled(_)))
+        .max_by_key(|meet_up| meet_up.date);
+    let done = gateway
+        .list_past_meet_ups()
+        .await
+        .map_err(|err| NewMeetUpError::Unknown(err.into()))?
+        .into_iter()
+        .max_by_key(|metadata| metadata.date);
+    Ok(match (scheduled, done) {
+        (Some(scheduled), Some(done)) if scheduled.date >= done.date => Some(scheduled),
+        (_, Some(done)) => Some(
+            gateway
+                .get_meet_up(&done.id)
+                .await
+                .map_err(|err| NewMeetUpError::Unknown(err.into()))?,
+        ),
+        (scheduled, None) => scheduled,
+    })
+}
+
+/// Papers already in other meet ups that can still select them, so a paper is carried over to only
+/// one of the open meet ups and can not be scheduled twice.
+async fn papers_of_open_meet_ups(
+    gateway: &impl MeetUpGateway,
+    paper_gateway: &impl PaperGateway,
+    meet_up_id: &Ulid,
+    previous_meet_up_id: &Ulid,
+) -> Result<HashSet<Ulid>, NewMeetUpError> {
+    let mut paper_ids = HashSet::new();
+    for meet_up in gateway
+        .list_future_meet_ups()
+        .await
+        .map_err(|err| NewMeetUpError::Unknown(err.into()))?
+    {
+        if meet_up.id == *meet_up_id
+            || meet_up.id == *previous_meet_up_id
+            || meet_up.state == MeetUpState::Cancelled
+        {
+            continue;
+        }
+        paper_ids.extend(
+            paper_gateway
+                .get_papers_from_meet_up(&meet_up.id)
+                .await
+                .map_err(|err| NewMeetUpError::Unknown(err.into()))?
+                .into_iter()
+                .map(|paper| paper.id),
+        );
+    }
+    Ok(paper_ids)
+}
+
+/// Online meet ups have no seat limit.
+fn on_site_capacity(location: &Location, capacity: Option<u32>) -> Option<u32> {
+    match location {
+        Location::OnSite(_) => capacity,
+        Location::Online { .. } => None,
+    }
+}
+
+pub async fn move_meet_up_to_voting(
+    gateway: &impl MeetUpGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: &Ulid,
+    id: &Ulid,
+) -> anyhow::Result<MeetUp> {
+    open_voting(gateway, audit_gateway, Some(actor_id), id).await
+}
+
+/// `actor_id` is `None` when the scheduler moves the meet up.
+async fn open_voting(
+    gateway: &impl MeetUpGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: Option<&Ulid>,
+    id: &Ulid,
+) -> anyhow::Result<MeetUp> {
+    let meet_up = gateway.get_meet_up(id).await?;
     if meet_up.state != MeetUpState::CallForPapers {
         return Err(anyhow::anyhow!(
             "Invalid meet up state: {:?}",
             meet_up.state
         ));
     }
-    Ok(gateway.update_meet_up_to_voting(&meet_up.id).await?)
+    let meet_up = gateway.update_meet_up_to_voting(&meet_up.id).await?;
+    record(
+        audit_gateway,
+        actor_id,
+        AuditAction::OpenVoting,
+        Some(&meet_up.id),
+        None,
+        json!({}),
+    )
+    .await?;
+    Ok(meet_up)
 }
 
-pub async fn move_future_meet_up_to_scheduled(
+pub async fn move_meet_up_to_scheduled(
     gateway: &impl MeetUpGateway,
+    paper_gateway: &impl PaperGateway,
     vote_gateway: &impl VoteGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: &Ulid,
+    id: &Ulid,
 ) -> anyhow::Result<MeetUp> {
-    let meet_up = gateway
-        .get_future_meet_up()
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("No future meetups found"))?;
+    schedule(
+        gateway,
+        paper_gateway,
+        vote_gateway,
+        audit_gateway,
+        Some(actor_id),
+        id,
+    )
+    .await
+}
+
+/// `actor_id` is `None` when the scheduler moves the meet up.
+async fn schedule(
+    gateway: &impl MeetUpGateway,
+    paper_gateway: &impl PaperGateway,
+    vote_gateway: &impl VoteGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: Option<&Ulid>,
+    id: &Ulid,
+) -> anyhow::Result<MeetUp> {
+    let meet_up = gateway.get_meet_up(id).await?;
     if meet_up.state != MeetUpState::Voting {
         return Err(anyhow::anyhow!(
             "Invalid meet up state: {:?}",
@@ -46,30 +232,282 @@ pub async fn move_future_meet_up_to_scheduled(
         ));
     }
     let votes = vote_gateway.get_votes_for_meet_up(&meet_up.id).await?;
-    let paper_id = VoteDecider::new(votes)
-        .decide()
-        .ok_or(anyhow!("No valid paper found"))?;
-    Ok(gateway
-        .update_meet_up_to_scheduled(&meet_up.id, &paper_id)
-        .await?)
+    let paper_ids =
+        VoteDecider::new(votes, meet_up.voting_method).decide_top(meet_up.number_of_talks.into());
+    if paper_ids.is_empty() {
+        return Err(anyhow!("No valid paper found"));
+    }
+    let mut papers = paper_gateway
+        .get_papers_from_meet_up(&meet_up.id)
+        .await?
+        .into_iter()
+        .map(|paper| (paper.id, paper))
+        .collect::<HashMap<Ulid, Paper>>();
+    let mut talks: Vec<Talk> = vec![];
+    for paper_id in paper_ids {
+        let paper = papers
+            .remove(&paper_id)
+            .ok_or(anyhow!("Vote for invalid paper '{paper_id}'"))?;
+        talks.push(next_talk(&meet_up, &talks, paper));
+    }
+    let meet_up = gateway
+        .update_meet_up_to_scheduled(&meet_up.id, &talks)
+        .await?;
+    record(
+        audit_gateway,
+        actor_id,
+        AuditAction::ScheduleMeetUp,
+        Some(&meet_up.id),
+        None,
+        json!({ "paper_ids": talks.iter().map(|talk| talk.paper.id).collect::<Vec<_>>() }),
+    )
+    .await?;
+    Ok(meet_up)
+}
+
+/// A transition run because a deadline of the meet up passed.
+#[derive(Debug)]
+pub struct AutomaticTransition {
+    pub meet_up_id: Ulid,
+    /// The meet up in its new state, or why it could not move.
+    pub result: anyhow::Result<MeetUp>,
+}
+
+/// Moves meet ups whose call for papers deadline passed to voting and those whose voting deadline
+/// passed to scheduled. Finishing a meet up needs its recording link, so it stays manual.
+/// Only the current state is considered, so running it again does nothing new.
+///
+/// Meet ups that fail to be scheduled, e.g. without papers or votes, are added to `stalled` and
+/// skipped while they are in it, so the failure is reported once and an organizer moves them.
+pub async fn advance_meet_ups(
+    gateway: &impl MeetUpGateway,
+    paper_gateway: &impl PaperGateway,
+    vote_gateway: &impl VoteGateway,
+    audit_gateway: &impl AuditGateway,
+    stalled: &mut HashSet<Ulid>,
+) -> Result<Vec<AutomaticTransition>, ListFutureMeetUpsError> {
+    let now = utc_now();
+    let mut transitions = vec![];
+    for mut meet_up in gateway.list_future_meet_ups().await? {
+        loop {
+            let result = match meet_up.state {
+                MeetUpState::CallForPapers if meet_up.settings.is_call_for_papers_closed(now) => {
+                    open_voting(gateway, audit_gateway, None, &meet_up.id).await
+                }
+                MeetUpState::Voting
+                    if meet_up.settings.is_voting_closed(now) && !stalled.contains(&meet_up.id) =>
+                {
+                    let result = schedule(
+                        gateway,
+                        paper_gateway,
+                        vote_gateway,
+                        audit_gateway,
+                        None,
+                        &meet_up.id,
+                    )
+                    .await;
+                    if result.is_err() {
+                        stalled.insert(meet_up.id);
+                    }
+                    result
+                }
+                _ => break,
+            };
+            let next = result.as_ref().ok().cloned();
+            transitions.push(AutomaticTransition {
+                meet_up_id: meet_up.id,
+                result,
+            });
+            match next {
+                Some(next) => meet_up = next,
+                None => break,
+            }
+        }
+    }
+    Ok(transitions)
+}
+
+/// Changes when a talk of the lineup starts and how long it takes.
+pub async fn update_meet_up_talk(
+    gateway: &impl MeetUpGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: &Ulid,
+    id: &Ulid,
+    paper_id: &Ulid,
+    start: DateTime<Utc>,
+    duration_minutes: u32,
+) -> anyhow::Result<MeetUp> {
+    let meet_up = gateway.get_meet_up(id).await?;
+    let MeetUpState::Scheduled(talks) = meet_up.state else {
+        return Err(anyhow::anyhow!(
+            "Invalid meet up state: {:?}",
+            meet_up.state
+        ));
+    };
+    let talk = talks
+        .into_iter()
+        .find(|talk| talk.paper.id == *paper_id)
+        .ok_or(anyhow!("Paper `{paper_id}` is not in the lineup"))?;
+    if duration_minutes == 0 {
+        return Err(anyhow!("Talk duration must be positive"));
+    }
+    let meet_up = gateway
+        .update_meet_up_talk(
+            &meet_up.id,
+            &Talk {
+                start,
+                duration_minutes,
+                ..talk
+            },
+        )
+        .await?;
+    record(
+        audit_gateway,
+        Some(actor_id),
+        AuditAction::UpdateTalk,
+        Some(&meet_up.id),
+        Some(paper_id),
+        json!({ "start": start, "duration_minutes": duration_minutes }),
+    )
+    .await?;
+    Ok(meet_up)
+}
+
+/// A talk for `paper` right after the last talk of `talks`.
+pub(crate) fn next_talk(meet_up: &MeetUp, talks: &[Talk], paper: Paper) -> Talk {
+    Talk {
+        paper,
+        start: talks.iter().map(Talk::end).max().unwrap_or(meet_up.date),
+        duration_minutes: DEFAULT_TALK_DURATION_MINUTES,
+    }
 }
 
-pub async fn move_future_meet_up_to_done(
+/// Drops the lineup so the meet up can be voted again.
+pub async fn move_meet_up_back_to_voting(
     gateway: &impl MeetUpGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: &Ulid,
+    id: &Ulid,
+) -> anyhow::Result<MeetUp> {
+    let meet_up = gateway.get_meet_up(id).await?;
+    if !matches!(meet_up.state, MeetUpState::Scheduled(_)) {
+        return Err(anyhow::anyhow!(
+            "Invalid meet up state: {:?}",
+            meet_up.state
+        ));
+    }
+    let meet_up = gateway.reopen_meet_up_voting(&meet_up.id).await?;
+    record(
+        audit_gateway,
+        Some(actor_id),
+        AuditAction::ReopenVoting,
+        Some(&meet_up.id),
+        None,
+        json!({}),
+    )
+    .await?;
+    Ok(meet_up)
+}
+
+pub async fn move_meet_up_to_done(
+    gateway: &impl MeetUpGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: &Ulid,
+    id: &Ulid,
     link: Url,
 ) -> anyhow::Result<()> {
-    let meet_up = gateway
-        .get_future_meet_up()
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("No future meetups found"))?;
+    let meet_up = gateway.get_meet_up(id).await?;
     if !matches!(meet_up.state, MeetUpState::Scheduled(_)) {
         return Err(anyhow::anyhow!(
             "Invalid meet up state: {:?}",
             meet_up.state
         ));
     }
-    gateway.finish_meet_up(&meet_up.id, link).await?;
-    Ok(())
+    gateway.finish_meet_up(&meet_up.id, link.clone()).await?;
+    record(
+        audit_gateway,
+        Some(actor_id),
+        AuditAction::FinishMeetUp,
+        Some(&meet_up.id),
+        None,
+        json!({ "link": link }),
+    )
+    .await
+}
+
+/// Raising the capacity promotes users from the waitlist, which are returned with the meet up.
+#[allow(clippy::too_many_arguments)]
+pub async fn update_meet_up(
+    gateway: &impl MeetUpGateway,
+    meet_up_goers_gateway: &impl MeetUpGoersGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: &Ulid,
+    id: &Ulid,
+    location: Location,
+    date: DateTime<Utc>,
+    capacity: Option<u32>,
+) -> anyhow::Result<(MeetUp, Vec<Ulid>)> {
+    let meet_up = gateway.get_meet_up(id).await?;
+    if matches!(
+        meet_up.state,
+        MeetUpState::Done { .. } | MeetUpState::Cancelled
+    ) {
+        return Err(anyhow::anyhow!(
+            "Invalid meet up state: {:?}",
+            meet_up.state
+        ));
+    }
+    let capacity = on_site_capacity(&location, capacity);
+    let meet_up = gateway
+        .update_meet_up(&meet_up.id, location, date, capacity)
+        .await?;
+    let promoted_user_ids = meet_up_goers_gateway
+        .promote_waitlisted_users(&meet_up.id, meet_up.capacity)
+        .await?;
+    record(
+        audit_gateway,
+        Some(actor_id),
+        AuditAction::UpdateMeetUp,
+        Some(&meet_up.id),
+        None,
+        json!({
+            "location": meet_up.location,
+            "date": meet_up.date,
+            "capacity": meet_up.capacity,
+            "promoted_user_ids": promoted_user_ids,
+        }),
+    )
+    .await?;
+    Ok((meet_up, promoted_user_ids))
+}
+
+pub async fn cancel_meet_up(
+    gateway: &impl MeetUpGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: &Ulid,
+    id: &Ulid,
+) -> anyhow::Result<MeetUp> {
+    let meet_up = gateway.get_meet_up(id).await?;
+    if matches!(
+        meet_up.state,
+        MeetUpState::Done { .. } | MeetUpState::Cancelled
+    ) {
+        return Err(anyhow::anyhow!(
+            "Invalid meet up state: {:?}",
+            meet_up.state
+        ));
+    }
+    let meet_up = gateway.cancel_meet_up(&meet_up.id).await?;
+    record(
+        audit_gateway,
+        Some(actor_id),
+        AuditAction::CancelMeetUp,
+        Some(&meet_up.id),
+        None,
+        json!({}),
+    )
+    .await?;
+    Ok(meet_up)
 }
 
 pub async fn get_meet_up(
@@ -86,10 +524,28 @@ pub async fn get_meet_up_metadata(
     Ok(gateway.get_meet_up_metadata(id).await?)
 }
 
-pub async fn get_future_meet_up(
+pub async fn get_meet_up_revision(
     gateway: &impl MeetUpGateway,
-) -> Result<Option<MeetUp>, GetFutureMeetUpError> {
-    gateway.get_future_meet_up().await
+    id: &Ulid,
+) -> Result<MeetUpRevision, GetPastMeetUpError> {
+    Ok(gateway.get_meet_up_revision(id).await?)
+}
+
+pub async fn list_future_meet_ups(
+    gateway: &impl MeetUpGateway,
+) -> Result<Vec<MeetUp>, ListFutureMeetUpsError> {
+    gateway.list_future_meet_ups().await
+}
+
+/// The upcoming meet ups, cancelled ones included so calendars can drop them, and the past ones,
+/// ordered by date.
+pub async fn list_calendar_meet_ups(gateway: &impl MeetUpGateway) -> anyhow::Result<Vec<MeetUp>> {
+    let mut meet_ups = gateway.list_future_meet_ups().await?;
+    for metadata in gateway.list_past_meet_ups().await? {
+        meet_ups.push(gateway.get_meet_up(&metadata.id).await?);
+    }
+    meet_ups.sort_by_key(|meet_up| (meet_up.date, meet_up.id));
+    Ok(meet_ups)
 }
 
 #[derive(Debug, Error)]
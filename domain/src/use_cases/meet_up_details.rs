@@ -0,0 +1,86 @@
+use ulid::Ulid;
+
+use crate::{
+    GetMeetUpError, GetPastMeetUpError, MeetUp, MeetUpGateway, MeetUpGoersGateway, MeetUpMetadata,
+    MeetUpRegistration, Paper, PaperGateway, User, Vote, VoteGateway,
+};
+
+/// A meet up with its public attendance numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeetUpDetails {
+    pub meet_up: MeetUp,
+    /// Only done meet ups have metadata.
+    pub metadata: Option<MeetUpMetadata>,
+    /// Only counts attending users, not the waitlisted ones.
+    pub attendees: usize,
+    /// `None` when the meet up has no seat limit.
+    pub remaining_seats: Option<usize>,
+}
+
+pub async fn show_meet_up_details(
+    meet_up_gateway: &impl MeetUpGateway,
+    meet_up_goers_gateway: &impl MeetUpGoersGateway,
+    id: &Ulid,
+) -> Result<MeetUpDetails, GetPastMeetUpError> {
+    let meet_up = meet_up_gateway.get_meet_up(id).await?;
+    let metadata = match meet_up_gateway.get_meet_up_metadata(meet_up.id).await {
+        Ok(metadata) => Some(metadata),
+        Err(GetMeetUpError::NotFound(_)) => None,
+        Err(err) => return Err(err.into()),
+    };
+    let attendees = meet_up_goers_gateway
+        .get_number_attendees_from_meet_up(&meet_up.id)
+        .await
+        .map_err(anyhow::Error::from)?;
+    let remaining_seats = meet_up
+        .capacity
+        .map(|capacity| (capacity as usize).saturating_sub(attendees));
+    Ok(MeetUpDetails {
+        meet_up,
+        metadata,
+        attendees,
+        remaining_seats,
+    })
+}
+
+/// What the user did in a meet up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserMeetUpActivity {
+    pub papers: Vec<Paper>,
+    /// The user ballot, best ranked paper first. Empty until the user opens the voting page.
+    pub votes: Vec<Vote>,
+    pub registration: MeetUpRegistration,
+}
+
+pub async fn show_user_meet_up_activity(
+    meet_up_gateway: &impl MeetUpGateway,
+    paper_gateway: &impl PaperGateway,
+    vote_gateway: &impl VoteGateway,
+    meet_up_goers_gateway: &impl MeetUpGoersGateway,
+    meet_up_id: &Ulid,
+    user: &User,
+) -> Result<UserMeetUpActivity, GetPastMeetUpError> {
+    let details = show_meet_up_details(meet_up_gateway, meet_up_goers_gateway, meet_up_id).await?;
+    let meet_up_id = &details.meet_up.id;
+    let papers = paper_gateway
+        .get_papers_from_user_and_meet_up(&user.id, meet_up_id)
+        .await
+        .map_err(anyhow::Error::from)?;
+    let mut votes = vote_gateway
+        .get_votes_for_user(meet_up_id, &user.id)
+        .await
+        .map_err(anyhow::Error::from)?;
+    votes.sort_by_key(|vote| vote.position);
+    let status = meet_up_goers_gateway
+        .get_registration_status(&user.id, meet_up_id)
+        .await
+        .map_err(anyhow::Error::from)?;
+    Ok(UserMeetUpActivity {
+        papers,
+        votes,
+        registration: MeetUpRegistration {
+            status,
+            remaining_seats: details.remaining_seats,
+        },
+    })
+}
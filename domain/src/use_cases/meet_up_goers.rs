@@ -1,23 +1,170 @@
 use ulid::Ulid;
 
-use crate::{MeetUpGateway, MeetUpGoersGateway, MeetUpState};
+use anyhow::anyhow;
+use serde_json::json;
+use shared::utc_now;
+
+use crate::{
+    use_cases::audit::record, Attendee, AuditAction, AuditGateway, MeetUp, MeetUpGateway,
+    MeetUpGoersGateway, MeetUpState, RegistrationStatus,
+};
 
 pub async fn register_event_goer(
     meet_up_gateway: &impl MeetUpGateway,
     meet_up_goers_gateway: &impl MeetUpGoersGateway,
+    audit_gateway: &impl AuditGateway,
+    meet_up_id: &Ulid,
     user_id: &Ulid,
-) -> anyhow::Result<()> {
-    let meet_up = meet_up_gateway
-        .get_future_meet_up()
+) -> anyhow::Result<RegistrationStatus> {
+    let meet_up = meet_up_gateway.get_meet_up(meet_up_id).await?;
+    if !matches!(meet_up.state, MeetUpState::Scheduled(..)) {
+        return Err(anyhow::anyhow!(
+            "Invalid meet up state: {:?}",
+            meet_up.state
+        ));
+    }
+    if let Some(status) = meet_up_goers_gateway
+        .get_registration_status(user_id, &meet_up.id)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("No future meetups found"))?;
+    {
+        return Ok(status);
+    }
+    let status = meet_up_goers_gateway
+        .register_user_to_meet_up(user_id, &meet_up.id, meet_up.capacity)
+        .await?;
+    record(
+        audit_gateway,
+        Some(user_id),
+        AuditAction::Register,
+        Some(&meet_up.id),
+        Some(user_id),
+        json!({ "status": status }),
+    )
+    .await?;
+    Ok(status)
+}
+
+/// Frees the seat of the user, which goes to the first user of the waitlist. Returns the users
+/// promoted from the waitlist.
+pub async fn unregister_event_goer(
+    meet_up_gateway: &impl MeetUpGateway,
+    meet_up_goers_gateway: &impl MeetUpGoersGateway,
+    audit_gateway: &impl AuditGateway,
+    meet_up_id: &Ulid,
+    user_id: &Ulid,
+) -> anyhow::Result<Vec<Ulid>> {
+    let meet_up = meet_up_gateway.get_meet_up(meet_up_id).await?;
     if !matches!(meet_up.state, MeetUpState::Scheduled(..)) {
         return Err(anyhow::anyhow!(
             "Invalid meet up state: {:?}",
             meet_up.state
         ));
     }
-    Ok(meet_up_goers_gateway
-        .register_user_to_meet_up(user_id, &meet_up.id)
-        .await?)
+    let status = meet_up_goers_gateway
+        .unregister_user_from_meet_up(user_id, &meet_up.id)
+        .await?;
+    let promoted_user_ids = match status {
+        Some(RegistrationStatus::Attending) => {
+            meet_up_goers_gateway
+                .promote_waitlisted_users(&meet_up.id, meet_up.capacity)
+                .await?
+        }
+        _ => vec![],
+    };
+    if status.is_some() {
+        record(
+            audit_gateway,
+            Some(user_id),
+            AuditAction::Unregister,
+            Some(&meet_up.id),
+            Some(user_id),
+            json!({ "status": status, "promoted_user_ids": promoted_user_ids }),
+        )
+        .await?;
+    }
+    Ok(promoted_user_ids)
+}
+
+#[derive(Debug, Clone)]
+pub struct ListAttendeesResponse {
+    pub meet_up: MeetUp,
+    pub attendees: Vec<Attendee>,
+}
+
+impl ListAttendeesResponse {
+    /// Attending users that were checked in, waitlisted ones are left out.
+    pub fn checked_in(&self) -> usize {
+        self.attending()
+            .filter(|attendee| attendee.checked_in_at.is_some())
+            .count()
+    }
+
+    /// Share of attending users that never checked in, `None` without attendees.
+    pub fn no_show_rate(&self) -> Option<f64> {
+        let attending = self.attending().count();
+        (attending > 0).then(|| (attending - self.checked_in()) as f64 / attending as f64)
+    }
+
+    fn attending(&self) -> impl Iterator<Item = &Attendee> {
+        self.attendees
+            .iter()
+            .filter(|attendee| !attendee.waitlisted)
+    }
+}
+
+pub async fn list_attendees(
+    meet_up_gateway: &impl MeetUpGateway,
+    meet_up_goers_gateway: &impl MeetUpGoersGateway,
+    meet_up_id: &Ulid,
+) -> anyhow::Result<ListAttendeesResponse> {
+    let meet_up = meet_up_gateway.get_meet_up(meet_up_id).await?;
+    if !matches!(
+        meet_up.state,
+        MeetUpState::Scheduled(..) | MeetUpState::Done { .. }
+    ) {
+        return Err(anyhow!("Invalid meet up state: {:?}", meet_up.state));
+    }
+    let attendees = meet_up_goers_gateway.list_attendees(&meet_up.id).await?;
+    Ok(ListAttendeesResponse { meet_up, attendees })
+}
+
+/// Marks the user as arrived at the meet up, or undoes it when `checked_in` is false.
+pub async fn check_in_event_goer(
+    meet_up_gateway: &impl MeetUpGateway,
+    meet_up_goers_gateway: &impl MeetUpGoersGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: &Ulid,
+    meet_up_id: &Ulid,
+    user_id: &Ulid,
+    checked_in: bool,
+) -> anyhow::Result<()> {
+    let meet_up = meet_up_gateway.get_meet_up(meet_up_id).await?;
+    if !matches!(
+        meet_up.state,
+        MeetUpState::Scheduled(..) | MeetUpState::Done { .. }
+    ) {
+        return Err(anyhow!("Invalid meet up state: {:?}", meet_up.state));
+    }
+    let registered = meet_up_goers_gateway
+        .check_in_user(user_id, &meet_up.id, checked_in.then(utc_now))
+        .await?;
+    if !registered {
+        return Err(anyhow!(
+            "User `{user_id}` is not registered to meet up `{}`",
+            meet_up.id
+        ));
+    }
+    let action = match checked_in {
+        true => AuditAction::CheckIn,
+        false => AuditAction::UndoCheckIn,
+    };
+    record(
+        audit_gateway,
+        Some(actor_id),
+        action,
+        Some(&meet_up.id),
+        Some(user_id),
+        json!({}),
+    )
+    .await
 }
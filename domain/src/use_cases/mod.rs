@@ -1,15 +1,29 @@
+pub use api_tokens::*;
+pub use audit::*;
 pub use call_for_papers::*;
+pub use feed::*;
 pub use login::*;
 pub use meet_up::*;
+pub use meet_up_details::*;
 pub use meet_up_goers::*;
+pub use notifications::*;
+pub use roles::*;
 pub use show_admin_page::*;
 pub use show_home_page::*;
 pub use voting::*;
+pub use voting_results::*;
 
+pub mod api_tokens;
+pub mod audit;
 pub mod call_for_papers;
+pub mod feed;
 pub mod login;
 pub mod meet_up;
+pub mod meet_up_details;
 pub mod meet_up_goers;
+pub mod notifications;
+pub mod roles;
 pub mod show_admin_page;
 pub mod show_home_page;
 pub mod voting;
+pub mod voting_results;
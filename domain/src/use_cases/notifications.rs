@@ -0,0 +1,200 @@
+use anyhow::anyhow;
+use shared::utc_now;
+use ulid::Ulid;
+
+use crate::{
+    MailGateway, MeetUp, MeetUpGateway, MeetUpGoersGateway, MeetUpState, Notification, Paper,
+    PaperGateway, User, UserGateway,
+};
+
+pub async fn has_mail_notifications(
+    user_gateway: &impl UserGateway,
+    user: &User,
+) -> anyhow::Result<bool> {
+    Ok(user_gateway.has_mail_notifications(&user.id).await?)
+}
+
+/// Unsubscribes the user from every email, or subscribes them back.
+pub async fn set_mail_notifications(
+    user_gateway: &impl UserGateway,
+    user: &User,
+    enabled: bool,
+) -> anyhow::Result<()> {
+    Ok(user_gateway
+        .set_mail_notifications(&user.id, enabled)
+        .await?)
+}
+
+/// Lets the author know the paper was received, at the email of their account. The email typed
+/// in the paper could be anyone's.
+pub async fn notify_paper_received(
+    mail_gateway: &impl MailGateway,
+    user_gateway: &impl UserGateway,
+    meet_up_gateway: &impl MeetUpGateway,
+    meet_up_id: &Ulid,
+    paper: &Paper,
+) -> anyhow::Result<()> {
+    if !user_gateway.has_mail_notifications(&paper.user_id).await? {
+        return Ok(());
+    }
+    let user = user_gateway.get_user_with_id(&paper.user_id).await?;
+    let meet_up = meet_up_gateway.get_meet_up(meet_up_id).await?;
+    mail_gateway
+        .send_mail(
+            &user.email,
+            &Notification::PaperReceived {
+                meet_up,
+                paper: paper.clone(),
+            },
+        )
+        .await?;
+    Ok(())
+}
+
+/// Lets every subscribed user know they can vote for the papers of the meet up.
+pub async fn notify_voting_opened(
+    mail_gateway: &impl MailGateway,
+    user_gateway: &impl UserGateway,
+    meet_up: &MeetUp,
+) -> anyhow::Result<usize> {
+    let mails = user_gateway
+        .list_users_with_mail_notifications()
+        .await?
+        .into_iter()
+        .map(|user| (user.email, Notification::VotingOpened(meet_up.clone())))
+        .collect();
+    send_mails(mail_gateway, mails).await
+}
+
+/// Lets the authors of the meet up papers know whether their papers made the lineup.
+pub async fn notify_lineup(
+    mail_gateway: &impl MailGateway,
+    user_gateway: &impl UserGateway,
+    paper_gateway: &impl PaperGateway,
+    meet_up: &MeetUp,
+) -> anyhow::Result<usize> {
+    let MeetUpState::Scheduled(talks) = &meet_up.state else {
+        return Err(anyhow!("Invalid meet up state: {:?}", meet_up.state));
+    };
+    let mut mails = vec![];
+    for paper in paper_gateway.get_papers_from_meet_up(&meet_up.id).await? {
+        if !user_gateway.has_mail_notifications(&paper.user_id).await? {
+            continue;
+        }
+        let email = paper.email.clone();
+        let meet_up = meet_up.clone();
+        let notification = match talks.iter().any(|talk| talk.paper.id == paper.id) {
+            true => Notification::PaperSelected { meet_up, paper },
+            false => Notification::PaperNotSelected { meet_up, paper },
+        };
+        mails.push((email, notification));
+    }
+    send_mails(mail_gateway, mails).await
+}
+
+/// Lets the users know they have a seat, either right after registering or when promoted from
+/// the waitlist.
+pub async fn notify_registration_confirmed(
+    mail_gateway: &impl MailGateway,
+    user_gateway: &impl UserGateway,
+    meet_up_gateway: &impl MeetUpGateway,
+    meet_up_id: &Ulid,
+    user_ids: &[Ulid],
+) -> anyhow::Result<usize> {
+    if user_ids.is_empty() {
+        return Ok(0);
+    }
+    let meet_up = meet_up_gateway.get_meet_up(meet_up_id).await?;
+    let mut mails = vec![];
+    for user_id in user_ids {
+        if !user_gateway.has_mail_notifications(user_id).await? {
+            continue;
+        }
+        let user = user_gateway.get_user_with_id(user_id).await?;
+        mails.push((
+            user.email,
+            Notification::RegistrationConfirmed(meet_up.clone()),
+        ));
+    }
+    send_mails(mail_gateway, mails).await
+}
+
+/// A reminder sent because its meet up happens in less than a day.
+#[derive(Debug)]
+pub struct SentReminder {
+    pub meet_up_id: Ulid,
+    /// How many attendees got the reminder, or why it could not be sent.
+    pub result: anyhow::Result<usize>,
+}
+
+/// Reminds the attending users of the scheduled meet ups happening in less than a day.
+/// The reminder of a meet up is claimed before it is sent, so it goes out at most once, even
+/// across restarts.
+pub async fn send_meet_up_reminders(
+    meet_up_gateway: &impl MeetUpGateway,
+    meet_up_goers_gateway: &impl MeetUpGoersGateway,
+    user_gateway: &impl UserGateway,
+    mail_gateway: &impl MailGateway,
+) -> anyhow::Result<Vec<SentReminder>> {
+    let now = utc_now();
+    let mut reminders = vec![];
+    for meet_up in meet_up_gateway.list_future_meet_ups().await? {
+        if !matches!(meet_up.state, MeetUpState::Scheduled(_))
+            || meet_up.date <= now
+            || meet_up.date - now > chrono::Duration::days(1)
+            || !meet_up_gateway.claim_meet_up_reminder(&meet_up.id).await?
+        {
+            continue;
+        }
+        let result =
+            remind_attendees(meet_up_goers_gateway, user_gateway, mail_gateway, &meet_up).await;
+        reminders.push(SentReminder {
+            meet_up_id: meet_up.id,
+            result,
+        });
+    }
+    Ok(reminders)
+}
+
+async fn remind_attendees(
+    meet_up_goers_gateway: &impl MeetUpGoersGateway,
+    user_gateway: &impl UserGateway,
+    mail_gateway: &impl MailGateway,
+    meet_up: &MeetUp,
+) -> anyhow::Result<usize> {
+    let mut mails = vec![];
+    for attendee in meet_up_goers_gateway.list_attendees(&meet_up.id).await? {
+        if attendee.waitlisted
+            || !user_gateway
+                .has_mail_notifications(&attendee.user_id)
+                .await?
+        {
+            continue;
+        }
+        mails.push((
+            attendee.email,
+            Notification::MeetUpReminder(meet_up.clone()),
+        ));
+    }
+    send_mails(mail_gateway, mails).await
+}
+
+/// Tries every mail even when some of them fail. Returns how many were sent.
+async fn send_mails(
+    mail_gateway: &impl MailGateway,
+    mails: Vec<(String, Notification)>,
+) -> anyhow::Result<usize> {
+    let total = mails.len();
+    let mut first_error = None;
+    let mut failed = 0;
+    for (to, notification) in mails {
+        if let Err(err) = mail_gateway.send_mail(&to, &notification).await {
+            failed += 1;
+            first_error.get_or_insert(err);
+        }
+    }
+    match first_error {
+        Some(err) => Err(anyhow!("Could not send {failed} of {total} emails: {err}")),
+        None => Ok(total),
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use serde_json::json;
+use thiserror::Error;
+use ulid::Ulid;
+
+use crate::{
+    use_cases::audit::record, AuditAction, AuditGateway, GetUserError, LoginMethod, Role,
+    RoleChange, RoleGateway, User, UserGateway,
+};
+
+pub async fn get_user_role(
+    role_gateway: &impl RoleGateway,
+    user: &User,
+) -> anyhow::Result<Option<Role>> {
+    Ok(role_gateway.get_user_role(&user.id).await?)
+}
+
+/// Makes the user an admin when their email is listed. Returns whether the role changed. Only
+/// GitHub users qualify, as other providers may let anyone register the listed emails, and only
+/// when no other GitHub user shares the email. Users whose role an admin revoked are left alone.
+pub async fn bootstrap_admin(
+    role_gateway: &impl RoleGateway,
+    user_gateway: &impl UserGateway,
+    audit_gateway: &impl AuditGateway,
+    user: &User,
+    admin_emails: &[String],
+) -> anyhow::Result<bool> {
+    if !admin_emails
+        .iter()
+        .any(|email| email.eq_ignore_ascii_case(&user.email))
+    {
+        return Ok(false);
+    }
+    match github_users_with_email(user_gateway, &user.email)
+        .await?
+        .as_slice()
+    {
+        [only] if only.id == user.id => promote_to_admin(role_gateway, audit_gateway, user).await,
+        _ => Ok(false),
+    }
+}
+
+/// Makes the already registered GitHub users of the listed emails admins.
+pub async fn bootstrap_admins(
+    role_gateway: &impl RoleGateway,
+    user_gateway: &impl UserGateway,
+    audit_gateway: &impl AuditGateway,
+    admin_emails: &[String],
+) -> anyhow::Result<Vec<AdminBootstrap>> {
+    let mut results = vec![];
+    for email in admin_emails {
+        match github_users_with_email(user_gateway, email)
+            .await?
+            .as_slice()
+        {
+            // They become admins when they first log in.
+            [] => {}
+            [user] => {
+                if promote_to_admin(role_gateway, audit_gateway, user).await? {
+                    results.push(AdminBootstrap::Granted(user.id));
+                }
+            }
+            users => results.push(AdminBootstrap::SharedEmail(
+                email.clone(),
+                users.iter().map(|user| user.id).collect(),
+            )),
+        }
+    }
+    Ok(results)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdminBootstrap {
+    Granted(Ulid),
+    /// The GitHub users sharing the email, none of them is made admin. An admin can pick the
+    /// right one on the roles page.
+    SharedEmail(String, Vec<Ulid>),
+}
+
+async fn github_users_with_email(
+    user_gateway: &impl UserGateway,
+    email: &str,
+) -> anyhow::Result<Vec<User>> {
+    Ok(user_gateway
+        .list_users_with_email(email)
+        .await?
+        .into_iter()
+        .filter(|user| matches!(user.login_method, LoginMethod::Github { .. }))
+        .collect())
+}
+
+async fn promote_to_admin(
+    role_gateway: &impl RoleGateway,
+    audit_gateway: &impl AuditGateway,
+    user: &User,
+) -> anyhow::Result<bool> {
+    // Runs on every login and start, so it must not undo a revocation.
+    if role_gateway.get_user_role(&user.id).await? == Some(Role::Admin)
+        || role_gateway.has_role_revocation(&user.id).await?
+    {
+        return Ok(false);
+    }
+    role_gateway
+        .set_user_role(&user.id, Some(Role::Admin), None)
+        .await?;
+    record(
+        audit_gateway,
+        None,
+        AuditAction::GrantRole,
+        None,
+        Some(&user.id),
+        json!({ "role": Role::Admin }),
+    )
+    .await?;
+    Ok(true)
+}
+
+/// The users with a role, and every role change, newest first, with the user whose role
+/// changed and who changed it.
+pub async fn list_roles(
+    role_gateway: &impl RoleGateway,
+    user_gateway: &impl UserGateway,
+) -> anyhow::Result<RolesOverview> {
+    let mut users = HashMap::new();
+    let mut with_roles = vec![];
+    for (user_id, role) in role_gateway.list_user_roles().await? {
+        with_roles.push((find_user(user_gateway, &mut users, &user_id).await?, role));
+    }
+    let mut changes = vec![];
+    for change in role_gateway.list_role_changes().await? {
+        let user = find_user(user_gateway, &mut users, &change.user_id).await?;
+        let changed_by = match &change.changed_by {
+            Some(changed_by) => Some(find_user(user_gateway, &mut users, changed_by).await?),
+            None => None,
+        };
+        changes.push((change, user, changed_by));
+    }
+    Ok(RolesOverview {
+        users: with_roles,
+        changes,
+    })
+}
+
+#[derive(Debug)]
+pub struct RolesOverview {
+    pub users: Vec<(User, Role)>,
+    /// The change, the user whose role changed and who changed it.
+    pub changes: Vec<(RoleChange, User, Option<User>)>,
+}
+
+async fn find_user(
+    user_gateway: &impl UserGateway,
+    users: &mut HashMap<Ulid, User>,
+    id: &Ulid,
+) -> anyhow::Result<User> {
+    if let Some(user) = users.get(id) {
+        return Ok(user.clone());
+    }
+    let user = user_gateway.get_user_with_id(id).await?;
+    users.insert(*id, user.clone());
+    Ok(user)
+}
+
+/// The users an admin can pick to grant a role to.
+pub async fn list_users(user_gateway: &impl UserGateway) -> anyhow::Result<Vec<User>> {
+    Ok(user_gateway.list_users().await?)
+}
+
+pub async fn grant_role(
+    role_gateway: &impl RoleGateway,
+    user_gateway: &impl UserGateway,
+    audit_gateway: &impl AuditGateway,
+    admin: &User,
+    user_id: &Ulid,
+    role: Role,
+) -> Result<User, ChangeRoleError> {
+    check_can_change_roles(role_gateway, admin).await?;
+    let user = user_gateway
+        .get_user_with_id(user_id)
+        .await
+        .map_err(|err| match err {
+            GetUserError::NotFound => ChangeRoleError::UserNotFound(*user_id),
+            err => ChangeRoleError::Unknown(err.into()),
+        })?;
+    if user.id == admin.id {
+        return Err(ChangeRoleError::OwnRole);
+    }
+    role_gateway
+        .set_user_role(&user.id, Some(role), Some(&admin.id))
+        .await
+        .map_err(|err| ChangeRoleError::Unknown(err.into()))?;
+    record(
+        audit_gateway,
+        Some(&admin.id),
+        AuditAction::GrantRole,
+        None,
+        Some(&user.id),
+        json!({ "role": role }),
+    )
+    .await?;
+    Ok(user)
+}
+
+pub async fn revoke_role(
+    role_gateway: &impl RoleGateway,
+    audit_gateway: &impl AuditGateway,
+    admin: &User,
+    user_id: &Ulid,
+) -> Result<(), ChangeRoleError> {
+    check_can_change_roles(role_gateway, admin).await?;
+    if *user_id == admin.id {
+        return Err(ChangeRoleError::OwnRole);
+    }
+    let Some(role) = role_gateway
+        .get_user_role(user_id)
+        .await
+        .map_err(|err| ChangeRoleError::Unknown(err.into()))?
+    else {
+        return Err(ChangeRoleError::NoRole(*user_id));
+    };
+    role_gateway
+        .set_user_role(user_id, None, Some(&admin.id))
+        .await
+        .map_err(|err| ChangeRoleError::Unknown(err.into()))?;
+    record(
+        audit_gateway,
+        Some(&admin.id),
+        AuditAction::RevokeRole,
+        None,
+        Some(user_id),
+        json!({ "role": role }),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn check_can_change_roles(
+    role_gateway: &impl RoleGateway,
+    admin: &User,
+) -> Result<(), ChangeRoleError> {
+    match role_gateway
+        .get_user_role(&admin.id)
+        .await
+        .map_err(|err| ChangeRoleError::Unknown(err.into()))?
+    {
+        Some(Role::Admin) => Ok(()),
+        _ => Err(ChangeRoleError::NotAllowed),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ChangeRoleError {
+    #[error("Only admins can change roles")]
+    NotAllowed,
+    // Keeps the last admin from locking everyone out.
+    #[error("Admins can not change their own role")]
+    OwnRole,
+    #[error("User `{0}` not found")]
+    UserNotFound(Ulid),
+    #[error("User `{0}` has no role")]
+    NoRole(Ulid),
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
@@ -4,11 +4,10 @@ pub async fn show_admin_page(
     meet_up_gateway: &impl MeetUpGateway,
     papers_gateway: &impl PaperGateway,
     meet_up_goers_gateway: &impl MeetUpGoersGateway,
-) -> anyhow::Result<ShowAdminPageResponse> {
-    let future_meet_up = meet_up_gateway.get_future_meet_up().await?;
-    Ok(match future_meet_up {
-        None => ShowAdminPageResponse::NoMeetUp,
-        Some(meet_up) => match &meet_up.state {
+) -> anyhow::Result<Vec<ShowAdminPageResponse>> {
+    let mut responses = Vec::new();
+    for meet_up in meet_up_gateway.list_future_meet_ups().await? {
+        responses.push(match &meet_up.state {
             MeetUpState::CallForPapers | MeetUpState::Voting => {
                 let n_papers = papers_gateway
                     .get_papers_from_meet_up(&meet_up.id)
@@ -23,13 +22,13 @@ pub async fn show_admin_page(
                 ShowAdminPageResponse::MeetUpWithAttendees(meet_up, n_attendees)
             }
             _ => ShowAdminPageResponse::MeetUp(meet_up),
-        },
-    })
+        });
+    }
+    Ok(responses)
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum ShowAdminPageResponse {
-    NoMeetUp,
     MeetUp(MeetUp),
     MeetUpWithPapers(MeetUp, usize),
     MeetUpWithAttendees(MeetUp, usize),
@@ -50,12 +49,11 @@ impl ShowAdminPageResponse {
         }
     }
 
-    pub fn into_meet_up(self) -> Option<MeetUp> {
+    pub fn into_meet_up(self) -> MeetUp {
         match self {
             ShowAdminPageResponse::MeetUp(meet_up)
             | ShowAdminPageResponse::MeetUpWithPapers(meet_up, _)
-            | ShowAdminPageResponse::MeetUpWithAttendees(meet_up, _) => Some(meet_up),
-            ShowAdminPageResponse::NoMeetUp => None,
+            | ShowAdminPageResponse::MeetUpWithAttendees(meet_up, _) => meet_up,
         }
     }
 }
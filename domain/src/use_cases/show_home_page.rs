@@ -1,24 +1,54 @@
 use ulid::Ulid;
 
-use crate::{MeetUp, MeetUpGateway, MeetUpGoersGateway, MeetUpMetadata};
+use crate::{MeetUp, MeetUpGateway, MeetUpGoersGateway, MeetUpMetadata, RegistrationStatus};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MeetUpRegistration {
+    /// `None` when there is no logged user or they are not registered.
+    pub status: Option<RegistrationStatus>,
+    /// `None` when the meet up has no seat limit.
+    pub remaining_seats: Option<usize>,
+}
 
 pub async fn show_home_page(
     meet_up_gateway: &impl MeetUpGateway,
     meet_up_goers_gateway: &impl MeetUpGoersGateway,
     user_id: Option<&Ulid>,
-) -> anyhow::Result<(Option<MeetUp>, Vec<MeetUpMetadata>, bool)> {
-    let future_meet_up = meet_up_gateway.get_future_meet_up().await?;
-    let is_registered_user = match user_id.and_then(|user_id| {
-        future_meet_up.as_ref().map(|meet_up| {
-            meet_up_goers_gateway.is_user_registered_to_meet_up(user_id, &meet_up.id)
-        })
-    }) {
-        Some(is_registered_user) => is_registered_user.await?,
-        None => false,
+) -> anyhow::Result<(Vec<(MeetUp, MeetUpRegistration)>, Vec<MeetUpMetadata>)> {
+    let mut future_meet_ups = Vec::new();
+    for meet_up in meet_up_gateway.list_future_meet_ups().await? {
+        let registration =
+            get_meet_up_registration(meet_up_goers_gateway, &meet_up, user_id).await?;
+        future_meet_ups.push((meet_up, registration));
+    }
+    Ok((future_meet_ups, meet_up_gateway.list_past_meet_ups().await?))
+}
+
+/// Registration of the user, `None` when logged out, and the seats left in the meet up.
+pub async fn get_meet_up_registration(
+    meet_up_goers_gateway: &impl MeetUpGoersGateway,
+    meet_up: &MeetUp,
+    user_id: Option<&Ulid>,
+) -> anyhow::Result<MeetUpRegistration> {
+    let status = match user_id {
+        Some(user_id) => {
+            meet_up_goers_gateway
+                .get_registration_status(user_id, &meet_up.id)
+                .await?
+        }
+        None => None,
+    };
+    let remaining_seats = match meet_up.capacity {
+        Some(capacity) => {
+            let n_attendees = meet_up_goers_gateway
+                .get_number_attendees_from_meet_up(&meet_up.id)
+                .await?;
+            Some((capacity as usize).saturating_sub(n_attendees))
+        }
+        None => None,
     };
-    Ok((
-        future_meet_up,
-        meet_up_gateway.list_past_meet_ups().await?,
-        is_registered_user,
-    ))
+    Ok(MeetUpRegistration {
+        status,
+        remaining_seats,
+    })
 }
@@ -1,31 +1,38 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::anyhow;
+use chrono::{DateTime, Utc};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
+use serde_json::json;
+use shared::utc_now;
+use thiserror::Error;
 use ulid::Ulid;
 
-use crate::{MeetUp, MeetUpGateway, MeetUpState, Paper, PaperGateway, Vote, VoteGateway};
+use crate::{
+    use_cases::audit::record, AuditAction, AuditGateway, GetMeetUpError, MeetUp, MeetUpGateway,
+    MeetUpState, Paper, PaperGateway, Vote, VoteGateway,
+};
 
 pub async fn show_voting(
     meet_up_gateway: &impl MeetUpGateway,
     papers_gateway: &impl PaperGateway,
     vote_gateway: &impl VoteGateway,
+    meet_up_id: &Ulid,
     user_id: &Ulid,
 ) -> anyhow::Result<(MeetUp, Vec<Paper>)> {
-    let future_meet_up = meet_up_gateway
-        .get_future_meet_up()
-        .await?
-        .ok_or(anyhow!("No future meetups found"))?;
-    if future_meet_up.state != MeetUpState::Voting {
-        return Err(anyhow!("Invalid meet up state: {:?}", future_meet_up.state));
+    let meet_up = meet_up_gateway.get_meet_up(meet_up_id).await?;
+    if meet_up.state != MeetUpState::Voting {
+        return Err(anyhow!("Invalid meet up state: {:?}", meet_up.state));
     }
     let votes = vote_gateway
-        .get_votes_for_user(&future_meet_up.id, user_id)
-        .await?;
-    let papers = papers_gateway
-        .get_papers_from_meet_up(&future_meet_up.id)
+        .get_votes_for_user(&meet_up.id, user_id)
         .await?;
+    let papers = papers_gateway.get_papers_from_meet_up(&meet_up.id).await?;
+    // After the deadline nobody can vote anymore, so there is no ballot to start.
+    if votes.is_empty() && meet_up.settings.is_voting_closed(utc_now()) {
+        return Ok((meet_up, papers));
+    }
     if votes.is_empty() {
         let mut papers = papers;
         papers.shuffle(&mut thread_rng());
@@ -37,13 +44,13 @@ pub async fn show_voting(
                     .map(|(pos, paper)| Vote {
                         user_id: *user_id,
                         paper_id: paper.id,
-                        meet_up_id: future_meet_up.id,
-                        vote: 1.0 / ((pos + 1) as f64),
+                        meet_up_id: meet_up.id,
+                        position: pos as u32,
                     })
                     .collect(),
             )
             .await?;
-        return Ok((future_meet_up, papers));
+        return Ok((meet_up, papers));
     }
     let mut papers = papers
         .into_iter()
@@ -57,29 +64,84 @@ pub async fn show_voting(
                 .ok_or(anyhow!("Vote for invalid paper '{}'", vote.paper_id))
         })
         .collect::<anyhow::Result<Vec<Paper>>>()?;
-    Ok((future_meet_up, papers))
+    Ok((meet_up, papers))
 }
 
 pub async fn store_votes(
     meet_up_gateway: &impl MeetUpGateway,
+    papers_gateway: &impl PaperGateway,
     vote_gateway: &impl VoteGateway,
+    audit_gateway: &impl AuditGateway,
+    meet_up_id: &Ulid,
     user_id: &Ulid,
     papers: Vec<Ulid>,
-) -> anyhow::Result<()> {
-    let future_meet_up = meet_up_gateway
-        .get_future_meet_up()
-        .await?
-        .ok_or(anyhow!("No meet up found"))?;
+) -> Result<(), StoreVotesError> {
+    let meet_up = meet_up_gateway
+        .get_meet_up(meet_up_id)
+        .await
+        .map_err(|err| match err {
+            GetMeetUpError::NotFound(id) => StoreVotesError::MeetUpNotFound(id),
+            _ => StoreVotesError::Unknown(err.into()),
+        })?;
+    if meet_up.state != MeetUpState::Voting {
+        return Err(StoreVotesError::InvalidMeetUpState(Box::new(meet_up.state)));
+    }
+    if let Some(deadline) = meet_up
+        .settings
+        .voting_deadline
+        .filter(|&deadline| utc_now() > deadline)
+    {
+        return Err(StoreVotesError::VotingClosed(deadline));
+    }
+    // The scheduling fails on votes for papers of other meet ups, so a ballot can only rank each
+    // paper of the meet up once.
+    let meet_up_papers = papers_gateway
+        .get_papers_from_meet_up(&meet_up.id)
+        .await
+        .map_err(|err| StoreVotesError::Unknown(err.into()))?;
+    let mut seen = HashSet::new();
+    if let Some(paper_id) = papers.iter().find(|paper_id| {
+        !seen.insert(**paper_id) || !meet_up_papers.iter().any(|paper| paper.id == **paper_id)
+    }) {
+        return Err(StoreVotesError::InvalidPaper(*paper_id));
+    }
+    let payload = json!({ "paper_ids": papers });
     let votes = papers
         .into_iter()
         .enumerate()
         .map(|(pos, paper_id)| Vote {
             user_id: *user_id,
             paper_id,
-            meet_up_id: future_meet_up.id,
-            vote: 1.0 / ((pos + 1) as f64),
+            meet_up_id: meet_up.id,
+            position: pos as u32,
         })
         .collect();
-    vote_gateway.store_votes(votes).await?;
+    vote_gateway
+        .store_votes(votes)
+        .await
+        .map_err(|err| StoreVotesError::Unknown(err.into()))?;
+    record(
+        audit_gateway,
+        Some(user_id),
+        AuditAction::Vote,
+        Some(&meet_up.id),
+        None,
+        payload,
+    )
+    .await?;
     Ok(())
 }
+
+#[derive(Debug, Error)]
+pub enum StoreVotesError {
+    #[error("Invalid meet up state: {0}")]
+    InvalidMeetUpState(Box<MeetUpState>),
+    #[error("Meet up with id `{0}` not found")]
+    MeetUpNotFound(Ulid),
+    #[error("Voting closed at `{0}`")]
+    VotingClosed(DateTime<Utc>),
+    #[error("Paper `{0}` is repeated or was not submitted to the meet up")]
+    InvalidPaper(Ulid),
+    #[error("Unknown error: `{0}`")]
+    Unknown(#[from] anyhow::Error),
+}
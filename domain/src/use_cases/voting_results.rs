@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use serde_json::json;
+use ulid::Ulid;
+
+use crate::{
+    use_cases::{audit::record, meet_up::next_talk},
+    AuditAction, AuditGateway, MeetUp, MeetUpGateway, MeetUpState, Paper, PaperGateway,
+    RankedPaper, ScheduleOverride, Talk, VoteDecider, VoteGateway,
+};
+
+#[derive(Debug, Clone)]
+pub struct ShowVotingResultsResponse {
+    pub meet_up: MeetUp,
+    pub ranking: Vec<(Paper, RankedPaper)>,
+    pub voters: usize,
+    pub overrides: Vec<ScheduleOverride>,
+}
+
+pub async fn show_voting_results(
+    meet_up_gateway: &impl MeetUpGateway,
+    paper_gateway: &impl PaperGateway,
+    vote_gateway: &impl VoteGateway,
+    meet_up_id: &Ulid,
+) -> anyhow::Result<ShowVotingResultsResponse> {
+    let meet_up = meet_up_gateway.get_meet_up(meet_up_id).await?;
+    if matches!(
+        meet_up.state,
+        MeetUpState::CallForPapers | MeetUpState::Cancelled
+    ) {
+        return Err(anyhow!("Invalid meet up state: {:?}", meet_up.state));
+    }
+    let (ranking, voters) = ranked_papers(paper_gateway, vote_gateway, &meet_up).await?;
+    let overrides = meet_up_gateway.list_schedule_overrides(&meet_up.id).await?;
+    Ok(ShowVotingResultsResponse {
+        meet_up,
+        ranking,
+        voters,
+        overrides,
+    })
+}
+
+/// Adds the given paper to the lineup regardless of the voting result.
+pub async fn schedule_meet_up_with_paper(
+    meet_up_gateway: &impl MeetUpGateway,
+    paper_gateway: &impl PaperGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: &Ulid,
+    meet_up_id: &Ulid,
+    paper_id: &Ulid,
+) -> anyhow::Result<MeetUp> {
+    let meet_up = meet_up_gateway.get_meet_up(meet_up_id).await?;
+    let talks = match &meet_up.state {
+        MeetUpState::Voting => vec![],
+        MeetUpState::Scheduled(talks) => talks.clone(),
+        state => return Err(anyhow!("Invalid meet up state: {state:?}")),
+    };
+    if talks.iter().any(|talk| talk.paper.id == *paper_id) {
+        return Err(anyhow!("Paper `{paper_id}` is already in the lineup"));
+    }
+    let paper = paper_gateway
+        .get_papers_from_meet_up(&meet_up.id)
+        .await?
+        .into_iter()
+        .find(|paper| paper.id == *paper_id)
+        .ok_or(anyhow!(
+            "Paper `{paper_id}` was not submitted to meet up `{}`",
+            meet_up.id
+        ))?;
+    let talk = next_talk(&meet_up, &talks, paper);
+    let meet_up = meet_up_gateway
+        .override_meet_up_schedule(&meet_up.id, None, &talk)
+        .await?;
+    record(
+        audit_gateway,
+        Some(actor_id),
+        AuditAction::SchedulePaper,
+        Some(&meet_up.id),
+        Some(paper_id),
+        json!({ "start": talk.start }),
+    )
+    .await?;
+    Ok(meet_up)
+}
+
+/// Replaces a talk of the lineup with the best paper ranked after it that is not in the lineup
+/// yet, e.g. when the speaker can no longer attend. The new talk keeps the same time slot.
+pub async fn reschedule_meet_up_with_next_paper(
+    meet_up_gateway: &impl MeetUpGateway,
+    paper_gateway: &impl PaperGateway,
+    vote_gateway: &impl VoteGateway,
+    audit_gateway: &impl AuditGateway,
+    actor_id: &Ulid,
+    meet_up_id: &Ulid,
+    paper_id: &Ulid,
+) -> anyhow::Result<MeetUp> {
+    let meet_up = meet_up_gateway.get_meet_up(meet_up_id).await?;
+    let MeetUpState::Scheduled(talks) = &meet_up.state else {
+        return Err(anyhow!("Invalid meet up state: {:?}", meet_up.state));
+    };
+    let talk = talks
+        .iter()
+        .find(|talk| talk.paper.id == *paper_id)
+        .ok_or(anyhow!("Paper `{paper_id}` is not in the lineup"))?;
+    let (ranking, _) = ranked_papers(paper_gateway, vote_gateway, &meet_up).await?;
+    let next_paper = ranking
+        .into_iter()
+        .skip_while(|(paper, _)| paper.id != talk.paper.id)
+        .map(|(paper, _)| paper)
+        .find(|paper| !talks.iter().any(|talk| talk.paper.id == paper.id))
+        .ok_or(anyhow!("No paper ranked after `{}`", talk.paper.title))?;
+    let next_paper_id = next_paper.id;
+    let meet_up = meet_up_gateway
+        .override_meet_up_schedule(
+            &meet_up.id,
+            Some(&talk.paper.id),
+            &Talk {
+                paper: next_paper,
+                ..talk.clone()
+            },
+        )
+        .await?;
+    record(
+        audit_gateway,
+        Some(actor_id),
+        AuditAction::ScheduleNextPaper,
+        Some(&meet_up.id),
+        Some(&next_paper_id),
+        json!({ "replaced_paper_id": paper_id }),
+    )
+    .await?;
+    Ok(meet_up)
+}
+
+/// Every paper submitted to the meet up, best voted first, and the number of voters.
+async fn ranked_papers(
+    paper_gateway: &impl PaperGateway,
+    vote_gateway: &impl VoteGateway,
+    meet_up: &MeetUp,
+) -> anyhow::Result<(Vec<(Paper, RankedPaper)>, usize)> {
+    let mut papers = paper_gateway
+        .get_papers_from_meet_up(&meet_up.id)
+        .await?
+        .into_iter()
+        .map(|paper| (paper.id, paper))
+        .collect::<HashMap<Ulid, Paper>>();
+    let votes = vote_gateway.get_votes_for_meet_up(&meet_up.id).await?;
+    let results = VoteDecider::new(votes, meet_up.voting_method)
+        .results_with_papers(&papers.keys().copied().collect::<Vec<_>>());
+    let ranking = results
+        .ranking
+        .into_iter()
+        .filter_map(|ranked| papers.remove(&ranked.paper_id).map(|paper| (paper, ranked)))
+        .collect();
+    Ok((ranking, results.voters))
+}
@@ -0,0 +1,245 @@
+use std::{collections::HashMap, fmt::Display, str::FromStr};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+/// How the ballots of a meet up are turned into a ranking. Chosen when the meet up is created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VotingMethod {
+    /// Positional voting where the paper in position `n` gets `1 / (n + 1)` points.
+    #[default]
+    Harmonic,
+    /// Positional voting where the paper in position `n` gets `candidates - 1 - n` points.
+    Borda,
+    /// Condorcet method using the strongest paths between every pair of papers
+    /// https://en.wikipedia.org/wiki/Schulze_method
+    Schulze,
+    /// Repeatedly drops the papers with the fewest first preferences
+    /// https://en.wikipedia.org/wiki/Instant-runoff_voting
+    InstantRunoff,
+}
+
+impl VotingMethod {
+    pub const ALL: [VotingMethod; 4] = [
+        VotingMethod::Harmonic,
+        VotingMethod::Borda,
+        VotingMethod::Schulze,
+        VotingMethod::InstantRunoff,
+    ];
+
+    /// Scores every candidate, higher is better. Each ballot lists paper ids from the most to
+    /// the least preferred one; candidates missing from a ballot are ranked last by that voter.
+    pub fn scores(&self, ballots: &[Vec<Ulid>], candidates: &[Ulid]) -> HashMap<Ulid, f64> {
+        match self {
+            VotingMethod::Harmonic => positional(ballots, candidates, |pos| 1.0 / (pos + 1) as f64),
+            VotingMethod::Borda => positional(ballots, candidates, |pos| {
+                candidates.len().saturating_sub(pos + 1) as f64
+            }),
+            VotingMethod::Schulze => schulze(ballots, candidates),
+            VotingMethod::InstantRunoff => instant_runoff(ballots, candidates),
+        }
+    }
+}
+
+impl Display for VotingMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VotingMethod::Harmonic => write!(f, "Harmonic"),
+            VotingMethod::Borda => write!(f, "Borda"),
+            VotingMethod::Schulze => write!(f, "Schulze"),
+            VotingMethod::InstantRunoff => write!(f, "InstantRunoff"),
+        }
+    }
+}
+
+impl FromStr for VotingMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        VotingMethod::ALL
+            .into_iter()
+            .find(|method| method.to_string() == s)
+            .ok_or(anyhow!("Invalid voting method {s}"))
+    }
+}
+
+fn positional(
+    ballots: &[Vec<Ulid>],
+    candidates: &[Ulid],
+    points: impl Fn(usize) -> f64,
+) -> HashMap<Ulid, f64> {
+    let mut scores: HashMap<Ulid, f64> = candidates.iter().map(|id| (*id, 0.0)).collect();
+    for ballot in ballots {
+        for (pos, paper_id) in ballot.iter().enumerate() {
+            *scores.entry(*paper_id).or_default() += points(pos);
+        }
+    }
+    scores
+}
+
+/// The score is how many other papers each paper beats through its strongest path.
+fn schulze(ballots: &[Vec<Ulid>], candidates: &[Ulid]) -> HashMap<Ulid, f64> {
+    let n = candidates.len();
+    let mut preferred = vec![vec![0usize; n]; n];
+    for ballot in ballots {
+        let ranks = candidates
+            .iter()
+            .map(|id| {
+                ballot
+                    .iter()
+                    .position(|paper_id| paper_id == id)
+                    .unwrap_or(usize::MAX)
+            })
+            .collect::<Vec<_>>();
+        for (i, row) in preferred.iter_mut().enumerate() {
+            for (j, count) in row.iter_mut().enumerate() {
+                if ranks[i] < ranks[j] {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    let mut strongest = vec![vec![0usize; n]; n];
+    for i in 0..n {
+        for j in 0..n {
+            if i != j && preferred[i][j] > preferred[j][i] {
+                strongest[i][j] = preferred[i][j];
+            }
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            for j in 0..n {
+                if i != j && i != k && j != k {
+                    strongest[i][j] = strongest[i][j].max(strongest[i][k].min(strongest[k][j]));
+                }
+            }
+        }
+    }
+
+    candidates
+        .iter()
+        .enumerate()
+        .map(|(i, id)| {
+            let wins = (0..n)
+                .filter(|&j| i != j && strongest[i][j] > strongest[j][i])
+                .count();
+            (*id, wins as f64)
+        })
+        .collect()
+}
+
+/// The score is the round each paper was eliminated in, so the winner has the highest one.
+/// Papers tied with the fewest first preferences are eliminated together.
+fn instant_runoff(ballots: &[Vec<Ulid>], candidates: &[Ulid]) -> HashMap<Ulid, f64> {
+    let mut remaining: Vec<Ulid> = candidates.to_vec();
+    let mut scores = HashMap::new();
+    let mut round = 0;
+    while !remaining.is_empty() {
+        round += 1;
+        let mut first_preferences: HashMap<Ulid, usize> =
+            remaining.iter().map(|id| (*id, 0)).collect();
+        for ballot in ballots {
+            if let Some(paper_id) = ballot
+                .iter()
+                .find(|paper_id| first_preferences.contains_key(paper_id))
+            {
+                *first_preferences.entry(*paper_id).or_default() += 1;
+            }
+        }
+        let fewest = first_preferences.values().copied().min().unwrap_or(0);
+        remaining.retain(|id| {
+            if first_preferences[id] == fewest {
+                scores.insert(*id, round as f64);
+                false
+            } else {
+                true
+            }
+        });
+    }
+    scores
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ballots(ballots: &[(usize, &[Ulid])]) -> Vec<Vec<Ulid>> {
+        ballots
+            .iter()
+            .flat_map(|(times, ballot)| std::iter::repeat_n(ballot.to_vec(), *times))
+            .collect()
+    }
+
+    fn winner(scores: HashMap<Ulid, f64>) -> Ulid {
+        scores
+            .into_iter()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(id, _)| id)
+            .expect("No candidates")
+    }
+
+    #[test]
+    fn borda_points() {
+        let (a, b, c) = (Ulid::new(), Ulid::new(), Ulid::new());
+        let ballots = ballots(&[(1, &[a, b, c]), (1, &[b, a])]);
+        let scores = VotingMethod::Borda.scores(&ballots, &[a, b, c]);
+        assert_eq!(scores[&a], 3.0);
+        assert_eq!(scores[&b], 3.0);
+        assert_eq!(scores[&c], 0.0);
+    }
+
+    /// `b` wins every head to head, but `a` has the most first preferences.
+    #[test]
+    fn methods_disagree_on_the_winner() {
+        let (a, b, c) = (Ulid::new(), Ulid::new(), Ulid::new());
+        let ballots = ballots(&[(3, &[a, b, c]), (2, &[b, c, a]), (2, &[c, b, a])]);
+        let candidates = [a, b, c];
+        assert_eq!(
+            winner(VotingMethod::Harmonic.scores(&ballots, &candidates)),
+            b
+        );
+        assert_eq!(winner(VotingMethod::Borda.scores(&ballots, &candidates)), b);
+        assert_eq!(
+            winner(VotingMethod::Schulze.scores(&ballots, &candidates)),
+            b
+        );
+        assert_eq!(
+            winner(VotingMethod::InstantRunoff.scores(&ballots, &candidates)),
+            a
+        );
+    }
+
+    #[test]
+    fn schulze_scores_count_wins() {
+        let (a, b, c) = (Ulid::new(), Ulid::new(), Ulid::new());
+        let ballots = ballots(&[(4, &[a, b, c]), (3, &[b, c, a]), (2, &[c, b, a])]);
+        let scores = VotingMethod::Schulze.scores(&ballots, &[a, b, c]);
+        assert_eq!(scores[&b], 2.0);
+        assert_eq!(scores[&c], 1.0);
+        assert_eq!(scores[&a], 0.0);
+    }
+
+    #[test]
+    fn instant_runoff_eliminates_ties_together() {
+        let (a, b, c) = (Ulid::new(), Ulid::new(), Ulid::new());
+        let ballots = ballots(&[(2, &[a]), (1, &[b]), (1, &[c])]);
+        let scores = VotingMethod::InstantRunoff.scores(&ballots, &[a, b, c]);
+        assert_eq!(scores[&a], 2.0);
+        assert_eq!(scores[&b], 1.0);
+        assert_eq!(scores[&c], 1.0);
+    }
+
+    #[test]
+    fn parse_voting_method() {
+        for method in VotingMethod::ALL {
+            assert_eq!(
+                method.to_string().parse::<VotingMethod>().ok(),
+                Some(method)
+            );
+        }
+        assert!("Plurality".parse::<VotingMethod>().is_err());
+    }
+}
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS meet_up_schedule_overrides (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    meet_up_id UUID NOT NULL,
+    previous_paper_id UUID,
+    paper_id UUID,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (meet_up_id) REFERENCES meet_ups(id),
+    FOREIGN KEY (previous_paper_id) REFERENCES papers(id),
+    FOREIGN KEY (paper_id) REFERENCES papers(id)
+);
+CREATE INDEX IF NOT EXISTS schedule_overrides_meet_up_id_index on meet_up_schedule_overrides (meet_up_id);
@@ -0,0 +1,6 @@
+ALTER TABLE meet_ups ADD COLUMN voting_method TEXT NOT NULL DEFAULT 'Harmonic';
+
+ALTER TABLE meet_up_papers_votes ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+-- Votes used to be stored as harmonic weights, `1 / (position + 1)`.
+UPDATE meet_up_papers_votes SET position = CAST(ROUND(1.0 / vote) AS INTEGER) - 1;
+ALTER TABLE meet_up_papers_votes DROP COLUMN vote;
@@ -0,0 +1,19 @@
+ALTER TABLE meet_ups ADD COLUMN number_of_talks INTEGER NOT NULL DEFAULT 1;
+
+CREATE TABLE IF NOT EXISTS meet_up_talks (
+    meet_up_id UUID NOT NULL,
+    paper_id UUID NOT NULL,
+    position INTEGER NOT NULL,
+    start TIMESTAMP WITH TIME ZONE NOT NULL,
+    duration_minutes INTEGER NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (meet_up_id) REFERENCES meet_ups(id),
+    FOREIGN KEY (paper_id) REFERENCES papers(id),
+    PRIMARY KEY (meet_up_id, paper_id)
+);
+
+INSERT INTO meet_up_talks (meet_up_id, paper_id, position, start, duration_minutes)
+SELECT id, paper_id, 0, datetime, 45 FROM meet_ups WHERE paper_id IS NOT NULL;
+-- SQLite can't drop a column with a foreign key, so `meet_ups.paper_id` is left unused.
+UPDATE meet_ups SET paper_id = NULL;
@@ -0,0 +1,4 @@
+ALTER TABLE meet_ups ADD COLUMN capacity INTEGER;
+
+ALTER TABLE meet_up_goers ADD COLUMN waitlisted BOOLEAN NOT NULL DEFAULT FALSE;
+CREATE INDEX IF NOT EXISTS meet_up_goers_meet_up_id_index ON meet_up_goers (meet_up_id, waitlisted, created_at);
@@ -0,0 +1 @@
+ALTER TABLE meet_up_goers ADD COLUMN checked_in_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,3 @@
+ALTER TABLE papers ADD COLUMN keep_in_pool BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE meet_up_papers ADD COLUMN carried_over BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+ALTER TABLE meet_ups ADD COLUMN papers_per_user INTEGER NOT NULL DEFAULT 2;
+ALTER TABLE meet_ups ADD COLUMN max_papers INTEGER;
+ALTER TABLE meet_ups ADD COLUMN call_for_papers_deadline TIMESTAMP WITH TIME ZONE;
+ALTER TABLE meet_ups ADD COLUMN voting_deadline TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,2 @@
+ALTER TABLE users ADD COLUMN mail_notifications BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE meet_ups ADD COLUMN reminder_sent_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS meet_up_state_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    meet_up_id UUID NOT NULL,
+    state INT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (meet_up_id) REFERENCES meet_ups(id)
+);
+CREATE INDEX IF NOT EXISTS state_changes_meet_up_id_index on meet_up_state_changes (meet_up_id);
+
+-- Only the creation and the current state of the existing meet ups are known.
+INSERT INTO meet_up_state_changes (meet_up_id, state, created_at)
+SELECT id, 0, created_at FROM meet_ups;
+INSERT INTO meet_up_state_changes (meet_up_id, state, created_at)
+SELECT id, state, updated_at FROM meet_ups WHERE state != 0;
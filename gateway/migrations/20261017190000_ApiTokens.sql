@@ -0,0 +1,16 @@
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id UUID PRIMARY KEY NOT NULL,
+    user_id UUID NOT NULL,
+    name TEXT NOT NULL,
+    -- Hex encoded SHA-256 of the secret, the secret itself is never stored.
+    secret_hash TEXT NOT NULL,
+    -- Comma separated scope names.
+    scopes TEXT NOT NULL,
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS api_tokens_secret_hash_unique_index ON api_tokens (secret_hash);
+CREATE INDEX IF NOT EXISTS api_tokens_user_id_index ON api_tokens (user_id);
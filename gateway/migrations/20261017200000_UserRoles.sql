@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS user_roles (
+    user_id UUID PRIMARY KEY NOT NULL,
+    role TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+-- Append only history of the grants and revocations.
+CREATE TABLE IF NOT EXISTS user_role_changes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id UUID NOT NULL,
+    -- NULL when the role was revoked.
+    role TEXT,
+    -- NULL when granted from the ADMIN_EMAILS list.
+    changed_by UUID,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id),
+    FOREIGN KEY (changed_by) REFERENCES users(id)
+);
+CREATE INDEX IF NOT EXISTS user_role_changes_user_id_index ON user_role_changes (user_id);
@@ -0,0 +1,23 @@
+-- No foreign keys: the log must outlive the papers, users and meet ups it mentions.
+CREATE TABLE IF NOT EXISTS audit_events (
+    id UUID PRIMARY KEY NOT NULL,
+    -- NULL when done by the server itself.
+    actor_id UUID,
+    action TEXT NOT NULL,
+    meet_up_id UUID,
+    target_id UUID,
+    payload TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS audit_events_meet_up_id_index ON audit_events (meet_up_id);
+CREATE INDEX IF NOT EXISTS audit_events_actor_id_index ON audit_events (actor_id);
+
+CREATE TRIGGER IF NOT EXISTS audit_events_no_update BEFORE UPDATE ON audit_events
+BEGIN
+    SELECT RAISE(ABORT, 'audit_events is append only');
+END;
+
+CREATE TRIGGER IF NOT EXISTS audit_events_no_delete BEFORE DELETE ON audit_events
+BEGIN
+    SELECT RAISE(ABORT, 'audit_events is append only');
+END;
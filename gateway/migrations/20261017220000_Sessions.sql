@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS sessions (
+    id UUID PRIMARY KEY NOT NULL,
+    user_id UUID NOT NULL,
+    -- Hex encoded SHA-256 of the cookie token, the token itself is never stored.
+    token_hash TEXT NOT NULL,
+    -- User-Agent of the browser that logged in.
+    device TEXT NOT NULL,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    last_seen_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    revoked_at TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS sessions_token_hash_unique_index ON sessions (token_hash);
+CREATE INDEX IF NOT EXISTS sessions_user_id_index ON sessions (user_id);
+
+-- The old single token of each user can not be hashed here, so everyone logs in again.
+DROP INDEX IF EXISTS users_access_token_index;
+ALTER TABLE users DROP COLUMN access_token;
+ALTER TABLE users DROP COLUMN expires_at;
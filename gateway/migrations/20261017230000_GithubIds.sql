@@ -0,0 +1,8 @@
+-- Existing rows are backfilled on startup with the stored GitHub tokens, as only the GitHub API
+-- knows the ids. Until then those users are still matched by email.
+ALTER TABLE github_logins ADD COLUMN github_id INTEGER;
+CREATE UNIQUE INDEX IF NOT EXISTS github_logins_github_id_unique_index ON github_logins (github_id);
+
+-- Different GitHub accounts can share an email.
+DROP INDEX IF EXISTS users_email_unique_index;
+CREATE INDEX IF NOT EXISTS users_email_index ON users (email);
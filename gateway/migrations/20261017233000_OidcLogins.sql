@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS oidc_logins (
+    id UUID PRIMARY KEY NOT NULL,
+    user_id UUID NOT NULL,
+    -- Id of the provider in the configuration.
+    provider TEXT NOT NULL,
+    -- The `sub` claim, stable for the account in the provider.
+    subject TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS oidc_logins_user_id_index ON oidc_logins (user_id);
+CREATE UNIQUE INDEX IF NOT EXISTS oidc_logins_provider_subject_unique_index ON oidc_logins (provider, subject);
@@ -0,0 +1,2 @@
+-- Grows with every change calendars should pick up, the SEQUENCE of the event.
+ALTER TABLE meet_ups ADD COLUMN sequence INT NOT NULL DEFAULT 0;
@@ -1,9 +1,9 @@
-use anyhow::anyhow;
 use reqwest::header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE, USER_AGENT};
 use serde::Deserialize;
 
 use domain::{
-    AccessToken, ExchangeCodeError, GithubGateway, RefreshTokenError, UserInfoGithubError,
+    AccessToken, ExchangeCodeError, GithubGateway, GithubUser, RefreshTokenError,
+    UserInfoGithubError,
 };
 use shared::utc_now;
 
@@ -27,6 +27,7 @@ impl GithubRestGateway {
 
 #[derive(Debug, Clone, Deserialize)]
 struct UserInfo {
+    id: i64,
     login: String,
     email: Option<String>,
 }
@@ -35,6 +36,7 @@ struct UserInfo {
 struct Email {
     email: String,
     primary: bool,
+    verified: bool,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -49,7 +51,7 @@ impl GithubGateway for GithubRestGateway {
     async fn user_info(
         &self,
         access_token: &AccessToken,
-    ) -> Result<(String, String), UserInfoGithubError> {
+    ) -> Result<GithubUser, UserInfoGithubError> {
         let response = self
             .client
             .get("https://api.github.com/user")
@@ -72,8 +74,13 @@ impl GithubGateway for GithubRestGateway {
                 response
             )
         })?;
-        if let Some(email) = &user_info.email {
-            return Ok((user_info.login, email.clone()));
+        // GitHub only lets users make a verified email public.
+        if let Some(email) = user_info.email {
+            return Ok(GithubUser {
+                id: user_info.id,
+                login: user_info.login,
+                email,
+            });
         }
 
         let emails_response = self
@@ -98,11 +105,17 @@ impl GithubGateway for GithubRestGateway {
                 emails_response
             )
         })?;
-        let email = emails.iter().find(|email| email.primary).or(emails.first());
-        Ok((
-            user_info.login,
-            email.ok_or(anyhow!("No email found"))?.email.clone(),
-        ))
+        // The email decides who becomes admin, so anyone could claim an unverified one.
+        let email = emails
+            .iter()
+            .find(|email| email.primary && email.verified)
+            .or(emails.iter().find(|email| email.verified))
+            .ok_or(UserInfoGithubError::UnverifiedEmail)?;
+        Ok(GithubUser {
+            id: user_info.id,
+            login: user_info.login,
+            email: email.email.clone(),
+        })
     }
 
     async fn refresh_token(
@@ -1,6 +1,8 @@
 pub use sqlite_database::SqliteDatabaseGateway;
 
 pub mod github;
+pub mod mail;
+pub mod oidc;
 mod sqlite_database;
 
 macro_rules! error_and_log {
@@ -0,0 +1,100 @@
+use sqlx::{sqlite::SqliteRow, Error, Row};
+use ulid::Ulid;
+
+use domain::{ApiToken, ApiTokenGateway, GetApiTokenError, StoreApiTokenError};
+use shared::utc_now;
+
+use crate::error_and_log;
+
+use super::{meet_up_gateway::ulid_from_row, SqliteDatabaseGateway};
+
+impl ApiTokenGateway for SqliteDatabaseGateway {
+    async fn store_api_token(
+        &self,
+        api_token: &ApiToken,
+        secret_hash: &str,
+    ) -> Result<(), StoreApiTokenError> {
+        sqlx::query("INSERT INTO api_tokens (id, user_id, name, secret_hash, scopes, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?)")
+            .bind(api_token.id.to_bytes().as_slice())
+            .bind(api_token.user_id.to_bytes().as_slice())
+            .bind(&api_token.name)
+            .bind(secret_hash)
+            .bind(
+                api_token
+                    .scopes
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            )
+            .bind(api_token.created_at)
+            .bind(api_token.created_at)
+            .execute(&self.sqlite_pool)
+            .await
+            .map_err(|err| StoreApiTokenError::Unknown(error_and_log!("SQLX Error: {err}")))?;
+        Ok(())
+    }
+
+    async fn list_api_tokens(&self, user_id: &Ulid) -> Result<Vec<ApiToken>, GetApiTokenError> {
+        sqlx::query(
+            "SELECT * FROM api_tokens WHERE user_id = ? ORDER BY created_at DESC, rowid DESC",
+        )
+        .bind(user_id.to_bytes().as_slice())
+        .try_map(api_token_from_row)
+        .fetch_all(&self.sqlite_pool)
+        .await
+        .map_err(|err| GetApiTokenError::Unknown(error_and_log!("SQLX Error: {err}")))
+    }
+
+    async fn use_api_token(&self, secret_hash: &str) -> Result<ApiToken, GetApiTokenError> {
+        sqlx::query("UPDATE api_tokens SET last_used_at = ? WHERE secret_hash = ? AND revoked_at IS NULL RETURNING *")
+            .bind(utc_now())
+            .bind(secret_hash)
+            .try_map(api_token_from_row)
+            .fetch_one(&self.sqlite_pool)
+            .await
+            .map_err(|err| match err {
+                Error::RowNotFound => GetApiTokenError::NotFound,
+                _ => GetApiTokenError::Unknown(error_and_log!("SQLX Error: {err}")),
+            })
+    }
+
+    async fn revoke_api_token(
+        &self,
+        user_id: &Ulid,
+        id: &Ulid,
+    ) -> Result<bool, StoreApiTokenError> {
+        let now = utc_now();
+        let rows_affected = sqlx::query("UPDATE api_tokens SET revoked_at = ?, updated_at = ? WHERE id = ? AND user_id = ? AND revoked_at IS NULL")
+            .bind(now)
+            .bind(now)
+            .bind(id.to_bytes().as_slice())
+            .bind(user_id.to_bytes().as_slice())
+            .execute(&self.sqlite_pool)
+            .await
+            .map_err(|err| StoreApiTokenError::Unknown(error_and_log!("SQLX Error: {err}")))?
+            .rows_affected();
+        Ok(rows_affected > 0)
+    }
+}
+
+fn api_token_from_row(row: SqliteRow) -> Result<ApiToken, Error> {
+    Ok(ApiToken {
+        id: ulid_from_row(&row, "id")?,
+        user_id: ulid_from_row(&row, "user_id")?,
+        name: row.try_get("name")?,
+        scopes: row
+            .try_get::<&str, _>("scopes")?
+            .split(',')
+            .filter(|scope| !scope.is_empty())
+            .map(|scope| {
+                scope
+                    .parse()
+                    .map_err(|err| Error::Decode(Box::from(format!("{err}"))))
+            })
+            .collect::<Result<_, _>>()?,
+        created_at: row.try_get("created_at")?,
+        last_used_at: row.try_get("last_used_at")?,
+        revoked_at: row.try_get("revoked_at")?,
+    })
+}
@@ -0,0 +1,69 @@
+use sqlx::{sqlite::SqliteRow, Error, Row};
+
+use domain::{AuditError, AuditEvent, AuditFilter, AuditGateway};
+
+use crate::error_and_log;
+
+use super::{
+    meet_up_gateway::{optional_ulid_from_row, ulid_from_row},
+    SqliteDatabaseGateway,
+};
+
+impl AuditGateway for SqliteDatabaseGateway {
+    async fn store_audit_event(&self, event: &AuditEvent) -> Result<(), AuditError> {
+        sqlx::query("INSERT INTO audit_events (id, actor_id, action, meet_up_id, target_id, payload, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)")
+            .bind(event.id.to_bytes().as_slice())
+            .bind(event.actor_id.map(|id| id.to_bytes().to_vec()))
+            .bind(event.action.to_string())
+            .bind(event.meet_up_id.map(|id| id.to_bytes().to_vec()))
+            .bind(event.target_id.map(|id| id.to_bytes().to_vec()))
+            .bind(event.payload.to_string())
+            .bind(event.date)
+            .execute(&self.sqlite_pool)
+            .await
+            .map_err(audit_error)?;
+        Ok(())
+    }
+
+    async fn list_audit_events(
+        &self,
+        filter: &AuditFilter,
+        limit: u32,
+    ) -> Result<Vec<AuditEvent>, AuditError> {
+        let meet_up_id = filter.meet_up_id.map(|id| id.to_bytes().to_vec());
+        let actor_id = filter.actor_id.map(|id| id.to_bytes().to_vec());
+        sqlx::query(
+            r#"SELECT * FROM audit_events
+            WHERE (?1 IS NULL OR meet_up_id = ?1) AND (?2 IS NULL OR actor_id = ?2)
+            ORDER BY created_at DESC, rowid DESC
+            LIMIT ?3"#,
+        )
+        .bind(meet_up_id)
+        .bind(actor_id)
+        .bind(limit)
+        .try_map(audit_event_from_row)
+        .fetch_all(&self.sqlite_pool)
+        .await
+        .map_err(audit_error)
+    }
+}
+
+fn audit_error(err: Error) -> AuditError {
+    AuditError::Unknown(error_and_log!("SQLX Error: {err}"))
+}
+
+fn audit_event_from_row(row: SqliteRow) -> Result<AuditEvent, Error> {
+    Ok(AuditEvent {
+        id: ulid_from_row(&row, "id")?,
+        actor_id: optional_ulid_from_row(&row, "actor_id")?,
+        action: row
+            .try_get::<&str, _>("action")?
+            .parse()
+            .map_err(|err: anyhow::Error| Error::Decode(err.into()))?,
+        meet_up_id: optional_ulid_from_row(&row, "meet_up_id")?,
+        target_id: optional_ulid_from_row(&row, "target_id")?,
+        payload: serde_json::from_str(row.try_get("payload")?)
+            .map_err(|err| Error::Decode(err.into()))?,
+        date: row.try_get("created_at")?,
+    })
+}
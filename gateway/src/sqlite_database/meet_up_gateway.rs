@@ -1,96 +1,168 @@
 use chrono::{DateTime, Utc};
-use sqlx::{sqlite::SqliteRow, Error, Row};
+use sqlx::{sqlite::SqliteRow, Error, Row, Sqlite, Transaction};
 use ulid::Ulid;
 use url::Url;
 
 use domain::{
-    GetFutureMeetUpError, GetMeetUpError, ListPastMeetUpsError, Location, MeetUp, MeetUpGateway,
-    MeetUpMetadata, MeetUpState, NewMeetUpError, Paper, UpdateMeetUpError,
+    GetMeetUpError, ListFutureMeetUpsError, ListPastMeetUpsError, Location, MeetUp, MeetUpGateway,
+    MeetUpMetadata, MeetUpRevision, MeetUpSettings, MeetUpState, MeetUpStateChange,
+    MeetUpStateKind, NewMeetUpError, ScheduleOverride, Talk, UpdateMeetUpError,
 };
 use shared::utc_now;
 
 use crate::error_and_log;
 
-use super::SqliteDatabaseGateway;
+use super::{paper_gateway::paper_from_row, SqliteDatabaseGateway};
 
 impl MeetUpGateway for SqliteDatabaseGateway {
-    async fn get_future_meet_up(&self) -> Result<Option<MeetUp>, GetFutureMeetUpError> {
-        let result = sqlx::query(
-            "SELECT mu.*, p.id as paper_id, p.title, p.description, p.speaker, p.user_id, p.email FROM meet_ups mu LEFT JOIN papers p ON p.id = mu.paper_id WHERE mu.state != 3;",
+    async fn list_future_meet_ups(&self) -> Result<Vec<MeetUp>, ListFutureMeetUpsError> {
+        let rows = sqlx::query(
+            "SELECT * FROM meet_ups WHERE state != 3 AND (state != 4 OR datetime >= ?) ORDER BY datetime;",
         )
-            .try_map(meet_up_from_sqlite_row)
-            .fetch_one(&self.sqlite_pool)
-            .await;
-        match result {
-            Ok(future_meet_up) => Ok(Some(future_meet_up)),
-            Err(Error::RowNotFound) => Ok(None),
-            Err(err) => Err(GetFutureMeetUpError::Unknown(error_and_log!(
-                "SQLX Error: {err}"
-            ))),
+        .bind(utc_now())
+        .fetch_all(&self.sqlite_pool)
+        .await
+        .map_err(|err| ListFutureMeetUpsError::Unknown(error_and_log!("SQLX Error: {err}")))?;
+        let mut meet_ups = Vec::with_capacity(rows.len());
+        for row in rows {
+            meet_ups.push(self.meet_up_from_sqlite_row(row).await.map_err(|err| {
+                ListFutureMeetUpsError::Unknown(error_and_log!("SQLX Error: {err}"))
+            })?);
         }
+        Ok(meet_ups)
     }
 
-    async fn new_meet_up(
-        &self,
-        id: Ulid,
-        location: Location,
-        date: DateTime<Utc>,
-    ) -> Result<MeetUp, NewMeetUpError> {
-        sqlx::query("INSERT INTO meet_ups (id, state, location, datetime) VALUES (?, ?, ?, ?)")
-            .bind(id.to_bytes().as_slice())
+    async fn new_meet_up(&self, meet_up: &MeetUp) -> Result<MeetUp, NewMeetUpError> {
+        let mut transaction = self
+            .sqlite_pool
+            .begin()
+            .await
+            .map_err(|err| NewMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))?;
+        sqlx::query("INSERT INTO meet_ups (id, state, location, datetime, voting_method, number_of_talks, capacity, papers_per_user, max_papers, call_for_papers_deadline, voting_deadline) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
+            .bind(meet_up.id.to_bytes().as_slice())
             .bind(0)
             .bind(
-                serde_json::to_string(&location)
+                serde_json::to_string(&meet_up.location)
                     .map_err(|err| NewMeetUpError::Unknown(error_and_log!("Serde Error: {err}")))?,
             )
-            .bind(date)
-            .execute(&self.sqlite_pool)
+            .bind(meet_up.date)
+            .bind(meet_up.voting_method.to_string())
+            .bind(meet_up.number_of_talks)
+            .bind(meet_up.capacity)
+            .bind(meet_up.settings.papers_per_user)
+            .bind(meet_up.settings.max_papers)
+            .bind(meet_up.settings.call_for_papers_deadline)
+            .bind(meet_up.settings.voting_deadline)
+            .execute(&mut *transaction)
             .await
             .map_err(|err| NewMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))?;
-        Ok(MeetUp::new(id, MeetUpState::CallForPapers, location, date))
+        insert_state_change(&mut transaction, &meet_up.id, 0)
+            .await
+            .map_err(|err| NewMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))?;
+        transaction
+            .commit()
+            .await
+            .map_err(|err| NewMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))?;
+        Ok(meet_up.clone())
     }
 
     async fn update_meet_up_to_voting(&self, id: &Ulid) -> Result<MeetUp, UpdateMeetUpError> {
+        let mut transaction = self.sqlite_pool.begin().await.map_err(update_error)?;
         let rows_affected =
-            sqlx::query("UPDATE meet_ups SET state = 1, updated_at = ? WHERE id = ? AND state = 0")
+            sqlx::query("UPDATE meet_ups SET state = 1, sequence = sequence + 1, updated_at = ? WHERE id = ? AND state = 0")
                 .bind(utc_now())
                 .bind(id.to_bytes().as_slice())
-                .execute(&self.sqlite_pool)
+                .execute(&mut *transaction)
                 .await
-                .map_err(|err| UpdateMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))?
+                .map_err(update_error)?
                 .rows_affected();
-        let meet_up = self
-            .get_future_meet_up()
-            .await
-            .map_err(|err| error_and_log!("{err}"))?
-            .ok_or(UpdateMeetUpError::NotFound(*id))?;
+        if rows_affected > 0 {
+            insert_state_change(&mut transaction, id, 1)
+                .await
+                .map_err(update_error)?;
+        }
+        transaction.commit().await.map_err(update_error)?;
+        self.get_updated_meet_up(id, rows_affected > 0).await
+    }
+
+    async fn update_meet_up_to_scheduled(
+        &self,
+        id: &Ulid,
+        talks: &[Talk],
+    ) -> Result<MeetUp, UpdateMeetUpError> {
+        let mut transaction = self.sqlite_pool.begin().await.map_err(update_error)?;
+        let rows_affected =
+            sqlx::query("UPDATE meet_ups SET state = 2, sequence = sequence + 1, updated_at = ? WHERE id = ? AND state = 1")
+                .bind(utc_now())
+                .bind(id.to_bytes().as_slice())
+                .execute(&mut *transaction)
+                .await
+                .map_err(update_error)?
+                .rows_affected();
+        if rows_affected > 0 {
+            insert_state_change(&mut transaction, id, 2)
+                .await
+                .map_err(update_error)?;
+            for talk in talks {
+                insert_talk(&mut transaction, id, talk)
+                    .await
+                    .map_err(update_error)?;
+            }
+        }
+        transaction.commit().await.map_err(update_error)?;
+        self.get_updated_meet_up(id, rows_affected > 0).await
+    }
+
+    async fn finish_meet_up(&self, id: &Ulid, link: Url) -> Result<(), UpdateMeetUpError> {
+        let mut transaction = self.sqlite_pool.begin().await.map_err(update_error)?;
+        let rows_affected = sqlx::query(
+            "UPDATE meet_ups SET state = 3, link = ?, sequence = sequence + 1, updated_at = ? WHERE id = ? AND state = 2",
+        )
+        .bind(link.as_str())
+        .bind(utc_now())
+        .bind(id.to_bytes().as_slice())
+        .execute(&mut *transaction)
+        .await
+        .map_err(update_error)?
+        .rows_affected();
         if rows_affected == 0 {
             return Err(UpdateMeetUpError::InvalidState);
         }
-
-        Ok(meet_up)
+        insert_state_change(&mut transaction, id, 3)
+            .await
+            .map_err(update_error)?;
+        transaction.commit().await.map_err(update_error)?;
+        Ok(())
     }
 
-    async fn update_meet_up_to_scheduled(
+    async fn update_meet_up(
         &self,
         id: &Ulid,
-        paper_id: &Ulid,
+        location: Location,
+        date: DateTime<Utc>,
+        capacity: Option<u32>,
     ) -> Result<MeetUp, UpdateMeetUpError> {
         let rows_affected = sqlx::query(
-            "UPDATE meet_ups SET state = 2, paper_id = ?, updated_at = ? WHERE id = ? AND state = 1",
+            // A new date needs a new reminder.
+            "UPDATE meet_ups SET location = ?, reminder_sent_at = CASE WHEN datetime = ? THEN reminder_sent_at END, datetime = ?, capacity = ?, sequence = sequence + 1, updated_at = ? WHERE id = ? AND state NOT IN (3, 4)",
         )
-            .bind(paper_id.to_bytes().as_slice())
+            .bind(
+                serde_json::to_string(&location)
+                    .map_err(|err| UpdateMeetUpError::Unknown(error_and_log!("Serde Error: {err}")))?,
+            )
+            .bind(date)
+            .bind(date)
+            .bind(capacity)
             .bind(utc_now())
             .bind(id.to_bytes().as_slice())
             .execute(&self.sqlite_pool)
             .await
             .map_err(|err| UpdateMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))?
             .rows_affected();
-        let meet_up = self
-            .get_future_meet_up()
-            .await
-            .map_err(|err| error_and_log!("{err}"))?
-            .ok_or(UpdateMeetUpError::NotFound(*id))?;
+        let meet_up = self.get_meet_up(id).await.map_err(|err| match err {
+            GetMeetUpError::NotFound(id) => UpdateMeetUpError::NotFound(id),
+            _ => UpdateMeetUpError::Unknown(error_and_log!("{err}")),
+        })?;
         if rows_affected == 0 {
             return Err(UpdateMeetUpError::InvalidState);
         }
@@ -98,49 +170,222 @@ impl MeetUpGateway for SqliteDatabaseGateway {
         Ok(meet_up)
     }
 
-    async fn finish_meet_up(&self, id: &Ulid, link: Url) -> Result<(), UpdateMeetUpError> {
+    async fn cancel_meet_up(&self, id: &Ulid) -> Result<MeetUp, UpdateMeetUpError> {
+        let mut transaction = self.sqlite_pool.begin().await.map_err(update_error)?;
         let rows_affected = sqlx::query(
-            "UPDATE meet_ups SET state = 3, link = ?, updated_at = ? WHERE id = ? AND state = 2",
+            "UPDATE meet_ups SET state = 4, sequence = sequence + 1, updated_at = ? WHERE id = ? AND state NOT IN (3, 4)",
         )
-        .bind(link.as_str())
         .bind(utc_now())
         .bind(id.to_bytes().as_slice())
-        .execute(&self.sqlite_pool)
+        .execute(&mut *transaction)
         .await
-        .map_err(|err| UpdateMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))?
+        .map_err(update_error)?
         .rows_affected();
-        if rows_affected == 0 {
-            return Err(UpdateMeetUpError::InvalidState);
+        if rows_affected > 0 {
+            insert_state_change(&mut transaction, id, 4)
+                .await
+                .map_err(update_error)?;
         }
-        Ok(())
+        transaction.commit().await.map_err(update_error)?;
+        self.get_updated_meet_up(id, rows_affected > 0).await
     }
 
-    async fn list_past_meet_ups(&self) -> Result<Vec<MeetUpMetadata>, ListPastMeetUpsError> {
-        Ok(
-            sqlx::query("SELECT mu.id, p.title, datetime FROM meet_ups mu JOIN papers p ON mu.paper_id = p.id AND mu.state = 3 ORDER BY datetime desc;")
-                .try_map(|row: SqliteRow| {
-                    Ok(MeetUpMetadata::new(
-                        Ulid::from_bytes(
-                            row.try_get::<&[u8], _>("id")?
-                                .try_into()
-                                .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
-                        ),
-                        row.get("title"),
-                        row.get("datetime"),
-                    ))
-                })
-                .fetch_all(&self.sqlite_pool)
+    async fn override_meet_up_schedule(
+        &self,
+        id: &Ulid,
+        previous_paper_id: Option<&Ulid>,
+        talk: &Talk,
+    ) -> Result<MeetUp, UpdateMeetUpError> {
+        let mut transaction = self.sqlite_pool.begin().await.map_err(update_error)?;
+        let was_voting = sqlx::query("SELECT 1 FROM meet_ups WHERE id = ? AND state = 1")
+            .bind(id.to_bytes().as_slice())
+            .fetch_optional(&mut *transaction)
+            .await
+            .map_err(update_error)?
+            .is_some();
+        let rows_affected = sqlx::query(
+            "UPDATE meet_ups SET state = 2, sequence = sequence + 1, updated_at = ? WHERE id = ? AND state IN (1, 2)",
+        )
+        .bind(utc_now())
+        .bind(id.to_bytes().as_slice())
+        .execute(&mut *transaction)
+        .await
+        .map_err(update_error)?
+        .rows_affected();
+        let changed = rows_affected > 0
+            && match previous_paper_id {
+                Some(previous_paper_id) => {
+                    sqlx::query(
+                        "UPDATE meet_up_talks SET paper_id = ?, start = ?, duration_minutes = ?, updated_at = ? WHERE meet_up_id = ? AND paper_id = ?",
+                    )
+                    .bind(talk.paper.id.to_bytes().as_slice())
+                    .bind(talk.start)
+                    .bind(talk.duration_minutes)
+                    .bind(utc_now())
+                    .bind(id.to_bytes().as_slice())
+                    .bind(previous_paper_id.to_bytes().as_slice())
+                    .execute(&mut *transaction)
+                    .await
+                    .map_err(update_error)?
+                    .rows_affected()
+                        > 0
+                }
+                None => {
+                    insert_talk(&mut transaction, id, talk)
+                        .await
+                        .map_err(update_error)?;
+                    true
+                }
+            };
+        if !changed {
+            transaction.rollback().await.map_err(update_error)?;
+            return self.get_updated_meet_up(id, false).await;
+        }
+        insert_schedule_override(
+            &mut transaction,
+            id,
+            previous_paper_id,
+            Some(&talk.paper.id),
+        )
+        .await
+        .map_err(update_error)?;
+        if was_voting {
+            insert_state_change(&mut transaction, id, 2)
                 .await
-                .map_err(|err| error_and_log!("SQLX Error: {err}"))?,
+                .map_err(update_error)?;
+        }
+        transaction.commit().await.map_err(update_error)?;
+        self.get_updated_meet_up(id, true).await
+    }
+
+    async fn update_meet_up_talk(
+        &self,
+        id: &Ulid,
+        talk: &Talk,
+    ) -> Result<MeetUp, UpdateMeetUpError> {
+        let mut transaction = self.sqlite_pool.begin().await.map_err(update_error)?;
+        let rows_affected = sqlx::query(
+            "UPDATE meet_up_talks SET start = ?, duration_minutes = ?, updated_at = ? WHERE meet_up_id = ? AND paper_id = ? AND EXISTS (SELECT 1 FROM meet_ups WHERE id = meet_up_id AND state = 2)",
         )
+        .bind(talk.start)
+        .bind(talk.duration_minutes)
+        .bind(utc_now())
+        .bind(id.to_bytes().as_slice())
+        .bind(talk.paper.id.to_bytes().as_slice())
+        .execute(&mut *transaction)
+        .await
+        .map_err(update_error)?
+        .rows_affected();
+        if rows_affected > 0 {
+            sqlx::query("UPDATE meet_ups SET sequence = sequence + 1, updated_at = ? WHERE id = ?")
+                .bind(utc_now())
+                .bind(id.to_bytes().as_slice())
+                .execute(&mut *transaction)
+                .await
+                .map_err(update_error)?;
+        }
+        transaction.commit().await.map_err(update_error)?;
+        self.get_updated_meet_up(id, rows_affected > 0).await
     }
 
-    async fn get_meet_up(&self, id: &Ulid) -> Result<MeetUp, GetMeetUpError> {
+    async fn reopen_meet_up_voting(&self, id: &Ulid) -> Result<MeetUp, UpdateMeetUpError> {
+        let mut transaction = self.sqlite_pool.begin().await.map_err(update_error)?;
+        let rows_affected =
+            sqlx::query("UPDATE meet_ups SET state = 1, sequence = sequence + 1, updated_at = ? WHERE id = ? AND state = 2")
+                .bind(utc_now())
+                .bind(id.to_bytes().as_slice())
+                .execute(&mut *transaction)
+                .await
+                .map_err(update_error)?
+                .rows_affected();
+        if rows_affected > 0 {
+            insert_state_change(&mut transaction, id, 1)
+                .await
+                .map_err(update_error)?;
+            let paper_ids = sqlx::query(
+                "SELECT paper_id FROM meet_up_talks WHERE meet_up_id = ? ORDER BY start, position",
+            )
+            .bind(id.to_bytes().as_slice())
+            .try_map(|row: SqliteRow| ulid_from_row(&row, "paper_id"))
+            .fetch_all(&mut *transaction)
+            .await
+            .map_err(update_error)?;
+            sqlx::query("DELETE FROM meet_up_talks WHERE meet_up_id = ?")
+                .bind(id.to_bytes().as_slice())
+                .execute(&mut *transaction)
+                .await
+                .map_err(update_error)?;
+            for paper_id in paper_ids {
+                insert_schedule_override(&mut transaction, id, Some(&paper_id), None)
+                    .await
+                    .map_err(update_error)?;
+            }
+        }
+        transaction.commit().await.map_err(update_error)?;
+        self.get_updated_meet_up(id, rows_affected > 0).await
+    }
+
+    async fn list_schedule_overrides(
+        &self,
+        id: &Ulid,
+    ) -> Result<Vec<ScheduleOverride>, GetMeetUpError> {
         sqlx::query(
-            "SELECT mu.id, mu.paper_id, mu.state, p.user_id, p.title, p.description, p.speaker, p.email, mu.datetime, mu.link, mu.location FROM meet_ups mu LEFT JOIN papers p ON mu.paper_id = p.id WHERE mu.id = ?",
+            "SELECT meet_up_id, previous_paper_id, paper_id, created_at FROM meet_up_schedule_overrides WHERE meet_up_id = ? ORDER BY id",
         )
             .bind(id.to_bytes().as_slice())
-            .try_map(meet_up_from_sqlite_row)
+            .try_map(|row: SqliteRow| {
+                Ok(ScheduleOverride {
+                    meet_up_id: Ulid::from_bytes(
+                        row.try_get::<&[u8], _>("meet_up_id")?
+                            .try_into()
+                            .map_err(|err| Error::Decode(Box::new(err)))?,
+                    ),
+                    previous_paper_id: optional_ulid_from_row(&row, "previous_paper_id")?,
+                    paper_id: optional_ulid_from_row(&row, "paper_id")?,
+                    date: row.get("created_at"),
+                })
+            })
+            .fetch_all(&self.sqlite_pool)
+            .await
+            .map_err(|err| GetMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))
+    }
+
+    async fn list_meet_up_state_changes(
+        &self,
+        id: &Ulid,
+    ) -> Result<Vec<MeetUpStateChange>, GetMeetUpError> {
+        sqlx::query(
+            "SELECT meet_up_id, state, created_at FROM meet_up_state_changes WHERE meet_up_id = ? ORDER BY id",
+        )
+        .bind(id.to_bytes().as_slice())
+        .try_map(|row: SqliteRow| {
+            Ok(MeetUpStateChange {
+                meet_up_id: ulid_from_row(&row, "meet_up_id")?,
+                state: match row.try_get::<i32, _>("state")? {
+                    0 => MeetUpStateKind::CallForPapers,
+                    1 => MeetUpStateKind::Voting,
+                    2 => MeetUpStateKind::Scheduled,
+                    3 => MeetUpStateKind::Done,
+                    4 => MeetUpStateKind::Cancelled,
+                    _ => return Err(Error::Decode("Unknown state".into())),
+                },
+                date: row.try_get("created_at")?,
+            })
+        })
+        .fetch_all(&self.sqlite_pool)
+        .await
+        .map_err(|err| GetMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))
+    }
+
+    async fn get_meet_up_revision(&self, id: &Ulid) -> Result<MeetUpRevision, GetMeetUpError> {
+        sqlx::query("SELECT sequence, updated_at FROM meet_ups WHERE id = ?")
+            .bind(id.to_bytes().as_slice())
+            .try_map(|row: SqliteRow| {
+                Ok(MeetUpRevision {
+                    sequence: row.try_get("sequence")?,
+                    last_modified: row.try_get("updated_at")?,
+                })
+            })
             .fetch_one(&self.sqlite_pool)
             .await
             .map_err(|err| match err {
@@ -149,81 +394,207 @@ impl MeetUpGateway for SqliteDatabaseGateway {
             })
     }
 
-    async fn get_meet_up_metadata(&self, id: Ulid) -> Result<MeetUpMetadata, GetMeetUpError> {
-        sqlx::query("SELECT mu.id, p.title, datetime FROM meet_ups mu JOIN papers p ON mu.paper_id = p.id AND state = 3 WHERE mu.id = ?")
+    async fn claim_meet_up_reminder(&self, id: &Ulid) -> Result<bool, UpdateMeetUpError> {
+        let rows_affected = sqlx::query(
+            "UPDATE meet_ups SET reminder_sent_at = ? WHERE id = ? AND reminder_sent_at IS NULL",
+        )
+        .bind(utc_now())
+        .bind(id.to_bytes().as_slice())
+        .execute(&self.sqlite_pool)
+        .await
+        .map_err(|err| UpdateMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))?
+        .rows_affected();
+        Ok(rows_affected > 0)
+    }
+
+    async fn list_past_meet_ups(&self) -> Result<Vec<MeetUpMetadata>, ListPastMeetUpsError> {
+        let rows = sqlx::query("SELECT mu.id, p.title, mu.datetime FROM meet_ups mu JOIN meet_up_talks t ON t.meet_up_id = mu.id JOIN papers p ON p.id = t.paper_id WHERE mu.state = 3 ORDER BY mu.datetime DESC, mu.id, t.start, t.position;")
+            .fetch_all(&self.sqlite_pool)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        Ok(meet_up_metadata_from_rows(rows).map_err(|err| error_and_log!("SQLX Error: {err}"))?)
+    }
+
+    async fn get_meet_up(&self, id: &Ulid) -> Result<MeetUp, GetMeetUpError> {
+        let row = sqlx::query("SELECT * FROM meet_ups WHERE id = ?")
             .bind(id.to_bytes().as_slice())
-            .try_map(|row: SqliteRow| {
-                Ok(MeetUpMetadata::new(
-                    Ulid::from_bytes(
-                        row.try_get::<&[u8], _>("id")?
-                            .try_into()
-                            .map_err(|err| Error::Decode(Box::new(err)))?,
-                    ),
-                    row.get("title"),
-                    row.get("datetime"),
-                ))
-            })
             .fetch_one(&self.sqlite_pool)
             .await
             .map_err(|err| match err {
-                Error::RowNotFound => GetMeetUpError::NotFound(id),
+                Error::RowNotFound => GetMeetUpError::NotFound(*id),
                 _ => GetMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")),
-            })
+            })?;
+        self.meet_up_from_sqlite_row(row)
+            .await
+            .map_err(|err| GetMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))
+    }
+
+    async fn get_meet_up_metadata(&self, id: Ulid) -> Result<MeetUpMetadata, GetMeetUpError> {
+        let rows = sqlx::query("SELECT mu.id, p.title, mu.datetime FROM meet_ups mu JOIN meet_up_talks t ON t.meet_up_id = mu.id JOIN papers p ON p.id = t.paper_id WHERE mu.state = 3 AND mu.id = ? ORDER BY t.start, t.position")
+            .bind(id.to_bytes().as_slice())
+            .fetch_all(&self.sqlite_pool)
+            .await
+            .map_err(|err| GetMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))?;
+        meet_up_metadata_from_rows(rows)
+            .map_err(|err| GetMeetUpError::Unknown(error_and_log!("SQLX Error: {err}")))?
+            .into_iter()
+            .next()
+            .ok_or(GetMeetUpError::NotFound(id))
     }
 }
-fn meet_up_from_sqlite_row(row: SqliteRow) -> Result<MeetUp, Error> {
-    let state = state_from_row(&row)?;
-    Ok(MeetUp::new(
-        Ulid::from_bytes(
-            row.try_get::<&[u8], _>("id")?
-                .try_into()
+
+impl SqliteDatabaseGateway {
+    async fn meet_up_from_sqlite_row(&self, row: SqliteRow) -> Result<MeetUp, Error> {
+        let id = ulid_from_row(&row, "id")?;
+        let talks = sqlx::query("SELECT p.*, t.start, t.duration_minutes FROM meet_up_talks t JOIN papers p ON p.id = t.paper_id WHERE t.meet_up_id = ? ORDER BY t.start, t.position")
+            .bind(id.to_bytes().as_slice())
+            .try_map(|row: SqliteRow| {
+                Ok(Talk {
+                    start: row.try_get("start")?,
+                    duration_minutes: row.try_get("duration_minutes")?,
+                    paper: paper_from_row(row)?,
+                })
+            })
+            .fetch_all(&self.sqlite_pool)
+            .await?;
+        let state = match row.get::<i32, _>("state") {
+            0 => MeetUpState::CallForPapers,
+            1 => MeetUpState::Voting,
+            2 => MeetUpState::Scheduled(talks),
+            3 => MeetUpState::Done {
+                talks,
+                link: Url::parse(row.get("link")).map_err(|err| Error::Decode(Box::new(err)))?,
+            },
+            4 => MeetUpState::Cancelled,
+            _ => return Err(Error::Decode("Unknown state".into())),
+        };
+        Ok(MeetUp::new(
+            id,
+            state,
+            serde_json::from_str(row.get::<'_, &str, _>("location"))
                 .map_err(|err| Error::Decode(Box::new(err)))?,
-        ),
-        state,
-        serde_json::from_str(row.get::<'_, &str, _>("location"))
+            row.get("datetime"),
+            row.get::<'_, &str, _>("voting_method")
+                .parse()
+                .map_err(|err: anyhow::Error| Error::Decode(err.into()))?,
+            row.try_get("number_of_talks")?,
+            row.try_get("capacity")?,
+        )
+        .with_settings(MeetUpSettings {
+            papers_per_user: row.try_get("papers_per_user")?,
+            max_papers: row.try_get("max_papers")?,
+            call_for_papers_deadline: row.try_get("call_for_papers_deadline")?,
+            voting_deadline: row.try_get("voting_deadline")?,
+        }))
+    }
+
+    async fn get_updated_meet_up(
+        &self,
+        id: &Ulid,
+        changed: bool,
+    ) -> Result<MeetUp, UpdateMeetUpError> {
+        let meet_up = self.get_meet_up(id).await.map_err(|err| match err {
+            GetMeetUpError::NotFound(id) => UpdateMeetUpError::NotFound(id),
+            _ => UpdateMeetUpError::Unknown(error_and_log!("{err}")),
+        })?;
+        if !changed {
+            return Err(UpdateMeetUpError::InvalidState);
+        }
+
+        Ok(meet_up)
+    }
+}
+
+fn update_error(err: Error) -> UpdateMeetUpError {
+    UpdateMeetUpError::Unknown(error_and_log!("SQLX Error: {err}"))
+}
+
+/// Adds `talk` at the end of the lineup.
+async fn insert_talk(
+    transaction: &mut Transaction<'_, Sqlite>,
+    meet_up_id: &Ulid,
+    talk: &Talk,
+) -> Result<(), Error> {
+    sqlx::query(
+        "INSERT INTO meet_up_talks (meet_up_id, paper_id, position, start, duration_minutes) VALUES (?, ?, (SELECT COALESCE(MAX(position) + 1, 0) FROM meet_up_talks WHERE meet_up_id = ?), ?, ?)",
+    )
+    .bind(meet_up_id.to_bytes().as_slice())
+    .bind(talk.paper.id.to_bytes().as_slice())
+    .bind(meet_up_id.to_bytes().as_slice())
+    .bind(talk.start)
+    .bind(talk.duration_minutes)
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+async fn insert_schedule_override(
+    transaction: &mut Transaction<'_, Sqlite>,
+    meet_up_id: &Ulid,
+    previous_paper_id: Option<&Ulid>,
+    paper_id: Option<&Ulid>,
+) -> Result<(), Error> {
+    sqlx::query(
+        "INSERT INTO meet_up_schedule_overrides (meet_up_id, previous_paper_id, paper_id, created_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(meet_up_id.to_bytes().as_slice())
+    .bind(previous_paper_id.map(|paper_id| paper_id.to_bytes().to_vec()))
+    .bind(paper_id.map(|paper_id| paper_id.to_bytes().to_vec()))
+    .bind(utc_now())
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Records that the meet up moved to `state`.
+async fn insert_state_change(
+    transaction: &mut Transaction<'_, Sqlite>,
+    meet_up_id: &Ulid,
+    state: i32,
+) -> Result<(), Error> {
+    sqlx::query(
+        "INSERT INTO meet_up_state_changes (meet_up_id, state, created_at) VALUES (?, ?, ?)",
+    )
+    .bind(meet_up_id.to_bytes().as_slice())
+    .bind(state)
+    .bind(utc_now())
+    .execute(&mut **transaction)
+    .await?;
+    Ok(())
+}
+
+/// Rows with the meet up `id`, `datetime` and one talk `title` each, grouped by meet up.
+fn meet_up_metadata_from_rows(rows: Vec<SqliteRow>) -> Result<Vec<MeetUpMetadata>, Error> {
+    let mut metadata: Vec<MeetUpMetadata> = vec![];
+    for row in rows {
+        let id = ulid_from_row(&row, "id")?;
+        let title: String = row.try_get("title")?;
+        match metadata.last_mut() {
+            Some(last) if last.id == id => {
+                last.title.push_str(" / ");
+                last.title.push_str(&title);
+            }
+            _ => metadata.push(MeetUpMetadata::new(id, title, row.get("datetime"))),
+        }
+    }
+    Ok(metadata)
+}
+
+pub(super) fn ulid_from_row(row: &SqliteRow, column: &str) -> Result<Ulid, Error> {
+    Ok(Ulid::from_bytes(
+        row.try_get::<&[u8], _>(column)?
+            .try_into()
             .map_err(|err| Error::Decode(Box::new(err)))?,
-        row.get("datetime"),
     ))
 }
-fn state_from_row(row: &SqliteRow) -> Result<MeetUpState, Error> {
-    Ok(match row.get::<i32, _>("state") {
-        0 => MeetUpState::CallForPapers,
-        1 => MeetUpState::Voting,
-        2 => MeetUpState::Scheduled(Paper {
-            id: Ulid::from_bytes(
-                row.try_get::<&[u8], _>("paper_id")?
-                    .try_into()
-                    .map_err(|err| Error::Decode(Box::new(err)))?,
-            ),
-            user_id: Ulid::from_bytes(
-                row.try_get::<&[u8], _>("user_id")?
-                    .try_into()
-                    .map_err(|err| Error::Decode(Box::new(err)))?,
-            ),
-            title: row.get("title"),
-            description: row.get("description"),
-            speaker: row.get("speaker"),
-            email: row.get("email"),
-        }),
-        3 => MeetUpState::Done {
-            paper: Paper {
-                id: Ulid::from_bytes(
-                    row.try_get::<&[u8], _>("paper_id")?
-                        .try_into()
-                        .map_err(|err| Error::Decode(Box::new(err)))?,
-                ),
-                user_id: Ulid::from_bytes(
-                    row.try_get::<&[u8], _>("user_id")?
-                        .try_into()
-                        .map_err(|err| Error::Decode(Box::new(err)))?,
-                ),
-                title: row.get("title"),
-                description: row.get("description"),
-                speaker: row.get("speaker"),
-                email: row.get("email"),
-            },
-            link: Url::parse(row.get("link")).map_err(|err| Error::Decode(Box::new(err)))?,
-        },
-        _ => return Err(Error::Decode("Unknown state".into())),
-    })
+
+pub(super) fn optional_ulid_from_row(row: &SqliteRow, column: &str) -> Result<Option<Ulid>, Error> {
+    row.try_get::<Option<&[u8]>, _>(column)?
+        .map(|bytes| {
+            bytes
+                .try_into()
+                .map(Ulid::from_bytes)
+                .map_err(|err| Error::Decode(Box::new(err)))
+        })
+        .transpose()
 }
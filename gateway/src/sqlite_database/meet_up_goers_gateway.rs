@@ -1,7 +1,11 @@
-use sqlx::Row;
+use chrono::{DateTime, Utc};
+use sqlx::{sqlite::SqliteRow, Error, Executor, Row, Sqlite};
 use ulid::Ulid;
 
-use domain::{GetAttendeesError, MeetUpGoersGateway, RegisterUserError};
+use domain::{
+    Attendee, GetAttendeesError, MeetUpGoersGateway, RegisterUserError, RegistrationStatus,
+};
+use shared::utc_now;
 
 use crate::{error_and_log, SqliteDatabaseGateway};
 
@@ -10,14 +14,62 @@ impl MeetUpGoersGateway for SqliteDatabaseGateway {
         &self,
         user_id: &Ulid,
         meet_up_id: &Ulid,
-    ) -> Result<(), RegisterUserError> {
-        sqlx::query("INSERT INTO meet_up_goers (user_id, meet_up_id) VALUES (?, ?)")
+        capacity: Option<u32>,
+    ) -> Result<RegistrationStatus, RegisterUserError> {
+        let mut transaction = self
+            .sqlite_pool
+            .begin()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        let n_attendees = count_attendees(&mut *transaction, meet_up_id)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        let waitlisted = capacity.is_some_and(|capacity| n_attendees >= capacity as usize);
+        sqlx::query(
+            "INSERT INTO meet_up_goers (user_id, meet_up_id, waitlisted, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(user_id.to_bytes().as_slice())
+        .bind(meet_up_id.to_bytes().as_slice())
+        .bind(waitlisted)
+        .bind(utc_now())
+        .execute(&mut *transaction)
+        .await
+        .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        let status = registration_status(&mut *transaction, user_id, meet_up_id)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?
+            .ok_or_else(|| error_and_log!("Registration not found after insert"))?;
+        transaction
+            .commit()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        Ok(status)
+    }
+
+    async fn unregister_user_from_meet_up(
+        &self,
+        user_id: &Ulid,
+        meet_up_id: &Ulid,
+    ) -> Result<Option<RegistrationStatus>, RegisterUserError> {
+        let mut transaction = self
+            .sqlite_pool
+            .begin()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        let status = registration_status(&mut *transaction, user_id, meet_up_id)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        sqlx::query("DELETE FROM meet_up_goers WHERE user_id = ? AND meet_up_id = ?")
             .bind(user_id.to_bytes().as_slice())
             .bind(meet_up_id.to_bytes().as_slice())
-            .execute(&self.sqlite_pool)
+            .execute(&mut *transaction)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        transaction
+            .commit()
             .await
             .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
-        Ok(())
+        Ok(status)
     }
 
     async fn is_user_registered_to_meet_up(
@@ -36,17 +88,164 @@ impl MeetUpGoersGateway for SqliteDatabaseGateway {
         )
     }
 
+    async fn get_registration_status(
+        &self,
+        user_id: &Ulid,
+        meet_up_id: &Ulid,
+    ) -> Result<Option<RegistrationStatus>, RegisterUserError> {
+        Ok(registration_status(&self.sqlite_pool, user_id, meet_up_id)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?)
+    }
+
+    async fn promote_waitlisted_users(
+        &self,
+        meet_up_id: &Ulid,
+        capacity: Option<u32>,
+    ) -> Result<Vec<Ulid>, RegisterUserError> {
+        let mut transaction = self
+            .sqlite_pool
+            .begin()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        let n_attendees = count_attendees(&mut *transaction, meet_up_id)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        // A negative limit means no limit in SQLite.
+        let free_seats = capacity
+            .map(|capacity| (capacity as i64 - n_attendees as i64).max(0))
+            .unwrap_or(-1);
+        let user_ids = sqlx::query(
+            "SELECT user_id FROM meet_up_goers WHERE meet_up_id = ? AND waitlisted ORDER BY created_at, rowid LIMIT ?",
+        )
+        .bind(meet_up_id.to_bytes().as_slice())
+        .bind(free_seats)
+        .try_map(|row: SqliteRow| {
+            Ok(Ulid::from_bytes(
+                row.try_get::<&[u8], _>("user_id")?
+                    .try_into()
+                    .map_err(|err| Error::Decode(Box::new(err)))?,
+            ))
+        })
+        .fetch_all(&mut *transaction)
+        .await
+        .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        for user_id in &user_ids {
+            sqlx::query(
+                "UPDATE meet_up_goers SET waitlisted = FALSE WHERE user_id = ? AND meet_up_id = ?",
+            )
+            .bind(user_id.to_bytes().as_slice())
+            .bind(meet_up_id.to_bytes().as_slice())
+            .execute(&mut *transaction)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        }
+        transaction
+            .commit()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        Ok(user_ids)
+    }
+
+    async fn list_attendees(&self, meet_up_id: &Ulid) -> Result<Vec<Attendee>, GetAttendeesError> {
+        Ok(sqlx::query(
+            r#"
+            SELECT g.user_id, u.nickname, u.email, g.waitlisted, g.created_at, g.checked_in_at
+            FROM meet_up_goers g
+            INNER JOIN users u ON u.id = g.user_id
+            WHERE g.meet_up_id = ?
+            ORDER BY g.waitlisted, g.created_at, g.rowid
+            "#,
+        )
+        .bind(meet_up_id.to_bytes().as_slice())
+        .try_map(|row: SqliteRow| {
+            Ok(Attendee {
+                user_id: Ulid::from_bytes(
+                    row.try_get::<&[u8], _>("user_id")?
+                        .try_into()
+                        .map_err(|err| Error::Decode(Box::new(err)))?,
+                ),
+                nickname: row.try_get("nickname")?,
+                email: row.try_get("email")?,
+                waitlisted: row.try_get("waitlisted")?,
+                registered_at: row.try_get("created_at")?,
+                checked_in_at: row.try_get("checked_in_at")?,
+            })
+        })
+        .fetch_all(&self.sqlite_pool)
+        .await
+        .map_err(|err| error_and_log!("SQLX Error: {err}"))?)
+    }
+
+    async fn check_in_user(
+        &self,
+        user_id: &Ulid,
+        meet_up_id: &Ulid,
+        checked_in_at: Option<DateTime<Utc>>,
+    ) -> Result<bool, RegisterUserError> {
+        Ok(sqlx::query(
+            "UPDATE meet_up_goers SET checked_in_at = ? WHERE user_id = ? AND meet_up_id = ?",
+        )
+        .bind(checked_in_at)
+        .bind(user_id.to_bytes().as_slice())
+        .bind(meet_up_id.to_bytes().as_slice())
+        .execute(&self.sqlite_pool)
+        .await
+        .map(|result| result.rows_affected() > 0)
+        .map_err(|err| error_and_log!("SQLX Error: {err}"))?)
+    }
+
     async fn get_number_attendees_from_meet_up(
         &self,
         meet_up_id: &Ulid,
     ) -> Result<usize, GetAttendeesError> {
-        Ok(
-            sqlx::query("SELECT COUNT(1) FROM meet_up_goers WHERE meet_up_id = ?")
-                .bind(meet_up_id.to_bytes().as_slice())
-                .fetch_one(&self.sqlite_pool)
-                .await
-                .map(|row| row.get::<i64, _>(0) as usize)
-                .map_err(|err| error_and_log!("SQLX Error: {err}"))?,
+        Ok(sqlx::query(
+            "SELECT COUNT(1) FROM meet_up_goers WHERE meet_up_id = ? AND NOT waitlisted",
         )
+        .bind(meet_up_id.to_bytes().as_slice())
+        .fetch_one(&self.sqlite_pool)
+        .await
+        .map(|row| row.get::<i64, _>(0) as usize)
+        .map_err(|err| error_and_log!("SQLX Error: {err}"))?)
     }
 }
+
+async fn count_attendees(
+    executor: impl Executor<'_, Database = Sqlite>,
+    meet_up_id: &Ulid,
+) -> Result<usize, Error> {
+    sqlx::query("SELECT COUNT(1) FROM meet_up_goers WHERE meet_up_id = ? AND NOT waitlisted")
+        .bind(meet_up_id.to_bytes().as_slice())
+        .fetch_one(executor)
+        .await
+        .map(|row| row.get::<i64, _>(0) as usize)
+}
+
+/// The waitlist position is the number of users waitlisted up to the given one.
+async fn registration_status(
+    executor: impl Executor<'_, Database = Sqlite>,
+    user_id: &Ulid,
+    meet_up_id: &Ulid,
+) -> Result<Option<RegistrationStatus>, Error> {
+    sqlx::query(
+        r#"
+        SELECT me.waitlisted, (
+            SELECT COUNT(1) FROM meet_up_goers other
+            WHERE other.meet_up_id = me.meet_up_id AND other.waitlisted
+                AND (other.created_at < me.created_at OR (other.created_at = me.created_at AND other.rowid <= me.rowid))
+        ) AS position
+        FROM meet_up_goers me
+        WHERE me.user_id = ? AND me.meet_up_id = ?
+        "#,
+    )
+    .bind(user_id.to_bytes().as_slice())
+    .bind(meet_up_id.to_bytes().as_slice())
+    .try_map(|row: SqliteRow| {
+        Ok(match row.try_get::<bool, _>("waitlisted")? {
+            true => RegistrationStatus::Waitlisted(row.try_get::<i64, _>("position")? as usize),
+            false => RegistrationStatus::Attending,
+        })
+    })
+    .fetch_optional(executor)
+    .await
+}
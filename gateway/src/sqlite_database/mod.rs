@@ -1,9 +1,13 @@
 use anyhow::Result;
 use sqlx::SqlitePool;
 
+mod api_token_gateway;
+mod audit_gateway;
 mod meet_up_gateway;
 mod meet_up_goers_gateway;
 mod paper_gateway;
+mod role_gateway;
+mod session_gateway;
 mod user_gateway;
 mod vote_gateway;
 
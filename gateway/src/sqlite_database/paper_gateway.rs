@@ -1,7 +1,7 @@
 use sqlx::{sqlite::SqliteRow, Error, Row};
 use ulid::Ulid;
 
-use domain::{GetPaperError, Paper, PaperGateway, StorePaperError};
+use domain::{DeletePaperError, GetPaperError, Paper, PaperGateway, StorePaperError};
 
 use crate::{error_and_log, SqliteDatabaseGateway};
 
@@ -11,19 +11,21 @@ impl PaperGateway for SqliteDatabaseGateway {
         paper: &Paper,
         meet_up_id: &Ulid,
         limit: u8,
+        max_papers: Option<u32>,
     ) -> Result<(), StorePaperError> {
         let mut transaction = self
             .sqlite_pool
             .begin()
             .await
             .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
-        sqlx::query("INSERT INTO papers (id, title, description, speaker, email, user_id) VALUES (?, ?, ?, ?, ?, ?);")
+        sqlx::query("INSERT INTO papers (id, title, description, speaker, email, user_id, keep_in_pool) VALUES (?, ?, ?, ?, ?, ?, ?);")
             .bind(paper.id.to_bytes().as_slice())
             .bind(&paper.title)
             .bind(&paper.description)
             .bind(&paper.speaker)
             .bind(&paper.email)
             .bind(paper.user_id.to_bytes().as_slice())
+            .bind(paper.keep_in_pool)
             .execute(&mut *transaction).await.map_err(|err| error_and_log!("SQLX Error: {err}"))?;
         sqlx::query("INSERT INTO meet_up_papers (meet_up_id, paper_id) VALUES (?, ?);")
             .bind(meet_up_id.to_bytes().as_slice())
@@ -31,7 +33,7 @@ impl PaperGateway for SqliteDatabaseGateway {
             .execute(&mut *transaction)
             .await
             .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
-        let n_papers_for_user = sqlx::query("SELECT count(1) count FROM papers p JOIN meet_up_papers mup ON mup.paper_id = p.id WHERE mup.meet_up_id = ? AND p.user_id = ?;")
+        let n_papers_for_user = sqlx::query("SELECT count(1) count FROM papers p JOIN meet_up_papers mup ON mup.paper_id = p.id WHERE mup.meet_up_id = ? AND p.user_id = ? AND NOT mup.carried_over;")
             .bind(meet_up_id.to_bytes().as_slice())
             .bind(paper.user_id.to_bytes().as_slice())
             .try_map(|row: SqliteRow| {
@@ -47,11 +49,91 @@ impl PaperGateway for SqliteDatabaseGateway {
                 .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
             return Err(StorePaperError::MoreThanLimitPapersPerUserPerMeetUp(limit));
         }
+        if let Some(max_papers) = max_papers {
+            let n_papers = sqlx::query("SELECT count(1) count FROM meet_up_papers WHERE meet_up_id = ? AND NOT carried_over;")
+                .bind(meet_up_id.to_bytes().as_slice())
+                .try_map(|row: SqliteRow| Ok(row.get::<u32, _>("count")))
+                .fetch_one(&mut *transaction)
+                .await
+                .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+            if n_papers > max_papers {
+                transaction
+                    .rollback()
+                    .await
+                    .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+                return Err(StorePaperError::MoreThanMaxPapersPerMeetUp(max_papers));
+            }
+        }
+        transaction
+            .commit()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+
+        Ok(())
+    }
+
+    async fn update_paper(&self, paper: &Paper) -> Result<(), StorePaperError> {
+        sqlx::query("UPDATE papers SET title = ?, description = ?, speaker = ?, keep_in_pool = ? WHERE id = ?")
+            .bind(&paper.title)
+            .bind(&paper.description)
+            .bind(&paper.speaker)
+            .bind(paper.keep_in_pool)
+            .bind(paper.id.to_bytes().as_slice())
+            .execute(&self.sqlite_pool)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        Ok(())
+    }
+
+    async fn delete_paper(&self, id: &Ulid) -> Result<(), DeletePaperError> {
+        let mut transaction = self
+            .sqlite_pool
+            .begin()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        sqlx::query("DELETE FROM meet_up_papers WHERE paper_id = ?")
+            .bind(id.to_bytes().as_slice())
+            .execute(&mut *transaction)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        sqlx::query("DELETE FROM papers WHERE id = ?")
+            .bind(id.to_bytes().as_slice())
+            .execute(&mut *transaction)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
         transaction
             .commit()
             .await
             .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        Ok(())
+    }
 
+    async fn remove_paper_from_meet_up(
+        &self,
+        id: &Ulid,
+        meet_up_id: &Ulid,
+    ) -> Result<(), DeletePaperError> {
+        let mut transaction = self
+            .sqlite_pool
+            .begin()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        sqlx::query("DELETE FROM meet_up_papers WHERE paper_id = ? AND meet_up_id = ?")
+            .bind(id.to_bytes().as_slice())
+            .bind(meet_up_id.to_bytes().as_slice())
+            .execute(&mut *transaction)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        sqlx::query("DELETE FROM papers WHERE id = ? AND NOT EXISTS (SELECT 1 FROM meet_up_papers WHERE paper_id = ?)")
+            .bind(id.to_bytes().as_slice())
+            .bind(id.to_bytes().as_slice())
+            .execute(&mut *transaction)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        transaction
+            .commit()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
         Ok(())
     }
 
@@ -73,7 +155,7 @@ impl PaperGateway for SqliteDatabaseGateway {
         user_id: &Ulid,
         meet_up_id: &Ulid,
     ) -> Result<Vec<Paper>, GetPaperError> {
-        let result = sqlx::query("SELECT * FROM papers p JOIN meet_up_papers mup ON p.id = mup.paper_id WHERE mup.meet_up_id = ? AND p.user_id = ?")
+        let result = sqlx::query("SELECT * FROM papers p JOIN meet_up_papers mup ON p.id = mup.paper_id WHERE mup.meet_up_id = ? AND p.user_id = ? AND NOT mup.carried_over")
             .bind(meet_up_id.to_bytes().as_slice())
             .bind(user_id.to_bytes().as_slice())
             .try_map(paper_from_row)
@@ -83,6 +165,80 @@ impl PaperGateway for SqliteDatabaseGateway {
         Ok(result)
     }
 
+    async fn get_carried_over_papers_from_user_and_meet_up(
+        &self,
+        user_id: &Ulid,
+        meet_up_id: &Ulid,
+    ) -> Result<Vec<Paper>, GetPaperError> {
+        let result = sqlx::query("SELECT * FROM papers p JOIN meet_up_papers mup ON p.id = mup.paper_id WHERE mup.meet_up_id = ? AND p.user_id = ? AND mup.carried_over")
+            .bind(meet_up_id.to_bytes().as_slice())
+            .bind(user_id.to_bytes().as_slice())
+            .try_map(paper_from_row)
+            .fetch_all(&self.sqlite_pool)
+            .await
+            .map_err(|err| GetPaperError::Unknown(error_and_log!("SQLX Error: {err}")))?;
+        Ok(result)
+    }
+
+    async fn carry_over_papers(
+        &self,
+        meet_up_id: &Ulid,
+        paper_ids: &[Ulid],
+    ) -> Result<(), StorePaperError> {
+        let mut transaction = self
+            .sqlite_pool
+            .begin()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        for paper_id in paper_ids {
+            sqlx::query("INSERT INTO meet_up_papers (meet_up_id, paper_id, carried_over) VALUES (?, ?, TRUE) ON CONFLICT (paper_id, meet_up_id) DO NOTHING;")
+                .bind(meet_up_id.to_bytes().as_slice())
+                .bind(paper_id.to_bytes().as_slice())
+                .execute(&mut *transaction)
+                .await
+                .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        }
+        transaction
+            .commit()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        Ok(())
+    }
+
+    async fn replace_carried_over_paper(
+        &self,
+        meet_up_id: &Ulid,
+        carried_over_id: &Ulid,
+        paper: &Paper,
+    ) -> Result<(), StorePaperError> {
+        let mut transaction = self
+            .sqlite_pool
+            .begin()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        sqlx::query("INSERT INTO papers (id, title, description, speaker, email, user_id, keep_in_pool) VALUES (?, ?, ?, ?, ?, ?, ?);")
+            .bind(paper.id.to_bytes().as_slice())
+            .bind(&paper.title)
+            .bind(&paper.description)
+            .bind(&paper.speaker)
+            .bind(&paper.email)
+            .bind(paper.user_id.to_bytes().as_slice())
+            .bind(paper.keep_in_pool)
+            .execute(&mut *transaction).await.map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        sqlx::query("UPDATE meet_up_papers SET paper_id = ? WHERE meet_up_id = ? AND paper_id = ? AND carried_over;")
+            .bind(paper.id.to_bytes().as_slice())
+            .bind(meet_up_id.to_bytes().as_slice())
+            .bind(carried_over_id.to_bytes().as_slice())
+            .execute(&mut *transaction)
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        transaction
+            .commit()
+            .await
+            .map_err(|err| error_and_log!("SQLX Error: {err}"))?;
+        Ok(())
+    }
+
     async fn get_papers_from_meet_up(
         &self,
         meet_up_id: &Ulid,
@@ -97,7 +253,7 @@ impl PaperGateway for SqliteDatabaseGateway {
     }
 }
 
-fn paper_from_row(row: SqliteRow) -> Result<Paper, Error> {
+pub(super) fn paper_from_row(row: SqliteRow) -> Result<Paper, Error> {
     Ok(Paper {
         id: Ulid::from_bytes(
             row.try_get::<&[u8], _>("id")?
@@ -108,6 +264,7 @@ fn paper_from_row(row: SqliteRow) -> Result<Paper, Error> {
         description: row.get("description"),
         speaker: row.get("speaker"),
         email: row.get("email"),
+        keep_in_pool: row.get("keep_in_pool"),
         user_id: Ulid::from_bytes(
             row.try_get::<&[u8], _>("user_id")?
                 .try_into()
@@ -0,0 +1,109 @@
+use sqlx::{sqlite::SqliteRow, Error, Row};
+use ulid::Ulid;
+
+use domain::{Role, RoleChange, RoleError, RoleGateway};
+use shared::utc_now;
+
+use crate::error_and_log;
+
+use super::{
+    meet_up_gateway::{optional_ulid_from_row, ulid_from_row},
+    SqliteDatabaseGateway,
+};
+
+impl RoleGateway for SqliteDatabaseGateway {
+    async fn get_user_role(&self, user_id: &Ulid) -> Result<Option<Role>, RoleError> {
+        sqlx::query("SELECT role FROM user_roles WHERE user_id = ?")
+            .bind(user_id.to_bytes().as_slice())
+            .try_map(|row: SqliteRow| role_from_row(&row))
+            .fetch_optional(&self.sqlite_pool)
+            .await
+            .map_err(|err| RoleError::Unknown(error_and_log!("SQLX Error: {err}")))
+    }
+
+    async fn set_user_role(
+        &self,
+        user_id: &Ulid,
+        role: Option<Role>,
+        changed_by: Option<&Ulid>,
+    ) -> Result<(), RoleError> {
+        let mut transaction = self.sqlite_pool.begin().await.map_err(role_error)?;
+        let now = utc_now();
+        match role {
+            Some(role) => {
+                sqlx::query("INSERT INTO user_roles (user_id, role, created_at, updated_at) VALUES (?, ?, ?, ?) ON CONFLICT (user_id) DO UPDATE SET role = EXCLUDED.role, updated_at = EXCLUDED.updated_at")
+                    .bind(user_id.to_bytes().as_slice())
+                    .bind(role.to_string())
+                    .bind(now)
+                    .bind(now)
+                    .execute(&mut *transaction)
+                    .await
+                    .map_err(role_error)?;
+            }
+            None => {
+                sqlx::query("DELETE FROM user_roles WHERE user_id = ?")
+                    .bind(user_id.to_bytes().as_slice())
+                    .execute(&mut *transaction)
+                    .await
+                    .map_err(role_error)?;
+            }
+        }
+        sqlx::query("INSERT INTO user_role_changes (user_id, role, changed_by, created_at) VALUES (?, ?, ?, ?)")
+            .bind(user_id.to_bytes().as_slice())
+            .bind(role.map(|role| role.to_string()))
+            .bind(changed_by.map(|changed_by| changed_by.to_bytes().to_vec()))
+            .bind(now)
+            .execute(&mut *transaction)
+            .await
+            .map_err(role_error)?;
+        transaction.commit().await.map_err(role_error)?;
+        Ok(())
+    }
+
+    async fn list_user_roles(&self) -> Result<Vec<(Ulid, Role)>, RoleError> {
+        sqlx::query("SELECT user_id, role FROM user_roles ORDER BY created_at, user_id")
+            .try_map(|row: SqliteRow| Ok((ulid_from_row(&row, "user_id")?, role_from_row(&row)?)))
+            .fetch_all(&self.sqlite_pool)
+            .await
+            .map_err(role_error)
+    }
+
+    async fn has_role_revocation(&self, user_id: &Ulid) -> Result<bool, RoleError> {
+        sqlx::query("SELECT EXISTS (SELECT 1 FROM user_role_changes WHERE user_id = ? AND role IS NULL) revoked")
+            .bind(user_id.to_bytes().as_slice())
+            .try_map(|row: SqliteRow| row.try_get::<bool, _>("revoked"))
+            .fetch_one(&self.sqlite_pool)
+            .await
+            .map_err(role_error)
+    }
+
+    async fn list_role_changes(&self) -> Result<Vec<RoleChange>, RoleError> {
+        sqlx::query(
+            "SELECT user_id, role, changed_by, created_at FROM user_role_changes ORDER BY id DESC",
+        )
+        .try_map(|row: SqliteRow| {
+            Ok(RoleChange {
+                user_id: ulid_from_row(&row, "user_id")?,
+                role: row
+                    .try_get::<Option<&str>, _>("role")?
+                    .map(|_| role_from_row(&row))
+                    .transpose()?,
+                changed_by: optional_ulid_from_row(&row, "changed_by")?,
+                date: row.try_get("created_at")?,
+            })
+        })
+        .fetch_all(&self.sqlite_pool)
+        .await
+        .map_err(role_error)
+    }
+}
+
+fn role_error(err: Error) -> RoleError {
+    RoleError::Unknown(error_and_log!("SQLX Error: {err}"))
+}
+
+fn role_from_row(row: &SqliteRow) -> Result<Role, Error> {
+    row.try_get::<&str, _>("role")?
+        .parse()
+        .map_err(|err: anyhow::Error| Error::Decode(err.into()))
+}
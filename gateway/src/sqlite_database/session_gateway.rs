@@ -0,0 +1,79 @@
+use sqlx::{sqlite::SqliteRow, Error, Row};
+use ulid::Ulid;
+
+use domain::{GetSessionError, Session, SessionGateway, StoreSessionError};
+use shared::utc_now;
+
+use crate::error_and_log;
+
+use super::{meet_up_gateway::ulid_from_row, SqliteDatabaseGateway};
+
+impl SessionGateway for SqliteDatabaseGateway {
+    async fn store_session(
+        &self,
+        session: &Session,
+        token_hash: &str,
+    ) -> Result<(), StoreSessionError> {
+        sqlx::query("INSERT INTO sessions (id, user_id, token_hash, device, expires_at, last_seen_at, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
+            .bind(session.id.to_bytes().as_slice())
+            .bind(session.user_id.to_bytes().as_slice())
+            .bind(token_hash)
+            .bind(&session.device)
+            .bind(session.expire_at)
+            .bind(session.last_seen_at)
+            .bind(session.created_at)
+            .bind(session.created_at)
+            .execute(&self.sqlite_pool)
+            .await
+            .map_err(|err| StoreSessionError::Unknown(error_and_log!("SQLX Error: {err}")))?;
+        Ok(())
+    }
+
+    async fn use_session(&self, token_hash: &str) -> Result<Session, GetSessionError> {
+        sqlx::query("UPDATE sessions SET last_seen_at = ? WHERE token_hash = ? AND revoked_at IS NULL RETURNING *")
+            .bind(utc_now())
+            .bind(token_hash)
+            .try_map(session_from_row)
+            .fetch_one(&self.sqlite_pool)
+            .await
+            .map_err(|err| match err {
+                Error::RowNotFound => GetSessionError::NotFound,
+                _ => GetSessionError::Unknown(error_and_log!("SQLX Error: {err}")),
+            })
+    }
+
+    async fn list_sessions(&self, user_id: &Ulid) -> Result<Vec<Session>, GetSessionError> {
+        sqlx::query("SELECT * FROM sessions WHERE user_id = ? AND revoked_at IS NULL AND expires_at > ? ORDER BY last_seen_at DESC, rowid DESC")
+            .bind(user_id.to_bytes().as_slice())
+            .bind(utc_now())
+            .try_map(session_from_row)
+            .fetch_all(&self.sqlite_pool)
+            .await
+            .map_err(|err| GetSessionError::Unknown(error_and_log!("SQLX Error: {err}")))
+    }
+
+    async fn revoke_session(&self, user_id: &Ulid, id: &Ulid) -> Result<bool, StoreSessionError> {
+        let now = utc_now();
+        let rows_affected = sqlx::query("UPDATE sessions SET revoked_at = ?, updated_at = ? WHERE id = ? AND user_id = ? AND revoked_at IS NULL")
+            .bind(now)
+            .bind(now)
+            .bind(id.to_bytes().as_slice())
+            .bind(user_id.to_bytes().as_slice())
+            .execute(&self.sqlite_pool)
+            .await
+            .map_err(|err| StoreSessionError::Unknown(error_and_log!("SQLX Error: {err}")))?
+            .rows_affected();
+        Ok(rows_affected > 0)
+    }
+}
+
+fn session_from_row(row: SqliteRow) -> Result<Session, Error> {
+    Ok(Session {
+        id: ulid_from_row(&row, "id")?,
+        user_id: ulid_from_row(&row, "user_id")?,
+        device: row.try_get("device")?,
+        created_at: row.try_get("created_at")?,
+        last_seen_at: row.try_get("last_seen_at")?,
+        expire_at: row.try_get("expires_at")?,
+    })
+}
@@ -8,39 +8,38 @@ use crate::error_and_log;
 
 use super::SqliteDatabaseGateway;
 
-impl UserGateway for SqliteDatabaseGateway {
-    async fn get_user_with_token(&self, access_token: &str) -> Result<User, GetUserError> {
-        sqlx::query(
+/// Selects the users with their login method, as read by `user_from_row`, followed by `$clauses`.
+macro_rules! select_users {
+    ($clauses:literal) => {
+        concat!(
             r#"
             SELECT u.id user_id,
                 u.nickname,
                 u.email,
-                u.access_token,
-                u.expires_at,
                 u.login_method,
+                gl.github_id,
                 gl.access_token github_access_token,
                 gl.expires_at github_expires_at,
                 gl.refresh_token,
-                gl.refresh_token_expires_at
+                gl.refresh_token_expires_at,
+                ol.provider oidc_provider,
+                ol.subject oidc_subject
             FROM users u
-            JOIN github_logins gl ON u.id = gl.user_id
-            WHERE u.access_token = ?"#,
+            LEFT JOIN github_logins gl ON u.id = gl.user_id
+            LEFT JOIN oidc_logins ol ON u.id = ol.user_id
+            "#,
+            $clauses
         )
-        .bind(access_token)
-        .try_map(user_from_row)
-        .fetch_one(&self.sqlite_pool)
-        .await
-        .map_err(|err| match err {
-            Error::RowNotFound => GetUserError::NotFound,
-            _ => GetUserError::Unknown(error_and_log!("SQLX Error: {err}")),
-        })
-    }
+    };
+}
 
+impl UserGateway for SqliteDatabaseGateway {
     async fn store_user(&self, user: User) -> Result<User, StoreUserError> {
         let id = user.id.to_bytes();
         let user_id = id.as_slice();
         let login_method = match user.login_method {
             LoginMethod::Github { .. } => 0,
+            LoginMethod::Oidc { .. } => 1,
         };
         let mut transaction = self
             .sqlite_pool
@@ -48,12 +47,10 @@ impl UserGateway for SqliteDatabaseGateway {
             .await
             .map_err(|err| StoreUserError::Unknown(error_and_log!("SQLX Error: {err}")))?;
         let now = utc_now();
-        sqlx::query("INSERT INTO users (id, nickname, email, access_token, expires_at, login_method, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ? ,?) ON CONFLICT (id) DO UPDATE SET nickname = EXCLUDED.nickname, email = EXCLUDED.email, access_token = EXCLUDED.access_token, expires_at = EXCLUDED.expires_at, login_method = EXCLUDED.login_method, updated_at = EXCLUDED.updated_at")
+        sqlx::query("INSERT INTO users (id, nickname, email, login_method, created_at, updated_at) VALUES (?, ?, ?, ?, ? ,?) ON CONFLICT (id) DO UPDATE SET nickname = EXCLUDED.nickname, email = EXCLUDED.email, login_method = EXCLUDED.login_method, updated_at = EXCLUDED.updated_at")
             .bind(user_id)
             .bind(&user.nickname)
             .bind(&user.email)
-            .bind(user.access_token.token())
-            .bind(user.access_token.expire_at())
             .bind(login_method)
             .bind(now)
             .bind(now)
@@ -62,12 +59,14 @@ impl UserGateway for SqliteDatabaseGateway {
             .map_err(|err| StoreUserError::Unknown(error_and_log!("SQLX Error: {err}")))?;
         match &user.login_method {
             LoginMethod::Github {
+                github_id,
                 access_token,
                 refresh_token,
             } => {
-                sqlx::query("INSERT INTO github_logins (id, user_id, access_token, expires_at, refresh_token, refresh_token_expires_at, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?) ON CONFLICT (user_id) DO UPDATE SET access_token = EXCLUDED.access_token, expires_at = EXCLUDED.expires_at, refresh_token = EXCLUDED.refresh_token, refresh_token_expires_at = EXCLUDED.refresh_token_expires_at, updated_at = EXCLUDED.updated_at")
+                sqlx::query("INSERT INTO github_logins (id, user_id, github_id, access_token, expires_at, refresh_token, refresh_token_expires_at, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) ON CONFLICT (user_id) DO UPDATE SET github_id = EXCLUDED.github_id, access_token = EXCLUDED.access_token, expires_at = EXCLUDED.expires_at, refresh_token = EXCLUDED.refresh_token, refresh_token_expires_at = EXCLUDED.refresh_token_expires_at, updated_at = EXCLUDED.updated_at")
                     .bind(Ulid::new().to_bytes().as_slice())
                     .bind(user_id)
+                    .bind(github_id)
                     .bind(access_token.token())
                     .bind(access_token.expire_at())
                     .bind(refresh_token.token())
@@ -78,6 +77,18 @@ impl UserGateway for SqliteDatabaseGateway {
                     .await
                     .map_err(|err| StoreUserError::Unknown(error_and_log!("SQLX Error: {err}")))?;
             }
+            LoginMethod::Oidc { provider, subject } => {
+                sqlx::query("INSERT INTO oidc_logins (id, user_id, provider, subject, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?) ON CONFLICT (user_id) DO UPDATE SET provider = EXCLUDED.provider, subject = EXCLUDED.subject, updated_at = EXCLUDED.updated_at")
+                    .bind(Ulid::new().to_bytes().as_slice())
+                    .bind(user_id)
+                    .bind(provider)
+                    .bind(subject)
+                    .bind(now)
+                    .bind(now)
+                    .execute(&mut *transaction)
+                    .await
+                    .map_err(|err| StoreUserError::Unknown(error_and_log!("SQLX Error: {err}")))?;
+            }
         };
         transaction
             .commit()
@@ -86,31 +97,111 @@ impl UserGateway for SqliteDatabaseGateway {
         Ok(user)
     }
 
-    async fn get_user_with_email(&self, email: &str) -> Result<User, GetUserError> {
-        sqlx::query(
-            r#"
-            SELECT u.id user_id,
-                u.nickname,
-                u.email,
-                u.access_token,
-                u.expires_at,
-                u.login_method,
-                gl.access_token github_access_token,
-                gl.expires_at github_expires_at,
-                gl.refresh_token,
-                gl.refresh_token_expires_at
-            FROM users u
-            JOIN github_logins gl ON u.id = gl.user_id
-            WHERE u.email = ?"#,
-        )
+    async fn get_user_with_id(&self, id: &Ulid) -> Result<User, GetUserError> {
+        sqlx::query(select_users!("WHERE u.id = ?"))
+            .bind(id.to_bytes().as_slice())
+            .try_map(user_from_row)
+            .fetch_one(&self.sqlite_pool)
+            .await
+            .map_err(|err| match err {
+                Error::RowNotFound => GetUserError::NotFound,
+                _ => GetUserError::Unknown(error_and_log!("SQLX Error: {err}")),
+            })
+    }
+
+    async fn list_users_with_mail_notifications(&self) -> Result<Vec<User>, GetUserError> {
+        sqlx::query(select_users!(
+            "WHERE u.mail_notifications ORDER BY u.created_at, u.id"
+        ))
+        .try_map(user_from_row)
+        .fetch_all(&self.sqlite_pool)
+        .await
+        .map_err(|err| GetUserError::Unknown(error_and_log!("SQLX Error: {err}")))
+    }
+
+    async fn has_mail_notifications(&self, user_id: &Ulid) -> Result<bool, GetUserError> {
+        sqlx::query("SELECT mail_notifications FROM users WHERE id = ?")
+            .bind(user_id.to_bytes().as_slice())
+            .try_map(|row: SqliteRow| row.try_get("mail_notifications"))
+            .fetch_one(&self.sqlite_pool)
+            .await
+            .map_err(|err| match err {
+                Error::RowNotFound => GetUserError::NotFound,
+                _ => GetUserError::Unknown(error_and_log!("SQLX Error: {err}")),
+            })
+    }
+
+    async fn set_mail_notifications(
+        &self,
+        user_id: &Ulid,
+        enabled: bool,
+    ) -> Result<(), StoreUserError> {
+        sqlx::query("UPDATE users SET mail_notifications = ?, updated_at = ? WHERE id = ?")
+            .bind(enabled)
+            .bind(utc_now())
+            .bind(user_id.to_bytes().as_slice())
+            .execute(&self.sqlite_pool)
+            .await
+            .map_err(|err| StoreUserError::Unknown(error_and_log!("SQLX Error: {err}")))?;
+        Ok(())
+    }
+
+    async fn list_users(&self) -> Result<Vec<User>, GetUserError> {
+        sqlx::query(select_users!("ORDER BY u.nickname COLLATE NOCASE, u.id"))
+            .try_map(user_from_row)
+            .fetch_all(&self.sqlite_pool)
+            .await
+            .map_err(|err| GetUserError::Unknown(error_and_log!("SQLX Error: {err}")))
+    }
+
+    async fn list_users_with_email(&self, email: &str) -> Result<Vec<User>, GetUserError> {
+        sqlx::query(select_users!(
+            "WHERE u.email = ? COLLATE NOCASE ORDER BY u.created_at, u.id"
+        ))
         .bind(email)
         .try_map(user_from_row)
-        .fetch_one(&self.sqlite_pool)
+        .fetch_all(&self.sqlite_pool)
         .await
-        .map_err(|err| match err {
-            Error::RowNotFound => GetUserError::NotFound,
-            _ => GetUserError::Unknown(error_and_log!("SQLX Error: {err}")),
-        })
+        .map_err(|err| GetUserError::Unknown(error_and_log!("SQLX Error: {err}")))
+    }
+
+    async fn get_user_with_oidc_subject(
+        &self,
+        provider: &str,
+        subject: &str,
+    ) -> Result<User, GetUserError> {
+        sqlx::query(select_users!("WHERE ol.provider = ? AND ol.subject = ?"))
+            .bind(provider)
+            .bind(subject)
+            .try_map(user_from_row)
+            .fetch_one(&self.sqlite_pool)
+            .await
+            .map_err(|err| match err {
+                Error::RowNotFound => GetUserError::NotFound,
+                _ => GetUserError::Unknown(error_and_log!("SQLX Error: {err}")),
+            })
+    }
+
+    async fn get_user_with_github_id(&self, github_id: i64) -> Result<User, GetUserError> {
+        sqlx::query(select_users!("WHERE gl.github_id = ?"))
+            .bind(github_id)
+            .try_map(user_from_row)
+            .fetch_one(&self.sqlite_pool)
+            .await
+            .map_err(|err| match err {
+                Error::RowNotFound => GetUserError::NotFound,
+                _ => GetUserError::Unknown(error_and_log!("SQLX Error: {err}")),
+            })
+    }
+
+    async fn list_users_without_github_id(&self) -> Result<Vec<User>, GetUserError> {
+        sqlx::query(select_users!(
+            "WHERE u.login_method = 0 AND gl.github_id IS NULL ORDER BY u.created_at, u.id"
+        ))
+        .try_map(user_from_row)
+        .fetch_all(&self.sqlite_pool)
+        .await
+        .map_err(|err| GetUserError::Unknown(error_and_log!("SQLX Error: {err}")))
     }
 }
 
@@ -123,9 +214,9 @@ fn user_from_row(row: SqliteRow) -> Result<User, Error> {
         ),
         row.get("nickname"),
         row.get("email"),
-        AccessToken::new(row.get("access_token"), row.get("expires_at")),
         match row.get("login_method") {
             0 => LoginMethod::Github {
+                github_id: row.get("github_id"),
                 access_token: AccessToken::new(
                     row.get("github_access_token"),
                     row.get("github_expires_at"),
@@ -135,6 +226,10 @@ fn user_from_row(row: SqliteRow) -> Result<User, Error> {
                     row.get("refresh_token_expires_at"),
                 ),
             },
+            1 => LoginMethod::Oidc {
+                provider: row.get("oidc_provider"),
+                subject: row.get("oidc_subject"),
+            },
             _ => return Err(Error::Decode("Unknown login method".into())),
         },
     ))
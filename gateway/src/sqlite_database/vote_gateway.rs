@@ -17,15 +17,15 @@ impl VoteGateway for SqliteDatabaseGateway {
             let now = utc_now();
             sqlx::query(
                 r#"
-                INSERT INTO meet_up_papers_votes (user_id, paper_id, meet_up_id, vote, created_at, updated_at)
+                INSERT INTO meet_up_papers_votes (user_id, paper_id, meet_up_id, position, created_at, updated_at)
                 VALUES (?, ?, ?, ?, ?, ?)
-                ON CONFLICT(user_id, paper_id, meet_up_id) DO UPDATE SET vote=excluded.vote, updated_at=excluded.updated_at;
+                ON CONFLICT(user_id, paper_id, meet_up_id) DO UPDATE SET position=excluded.position, updated_at=excluded.updated_at;
                 "#,
             )
                 .bind(vote.user_id.to_bytes().as_slice())
                 .bind(vote.paper_id.to_bytes().as_slice())
                 .bind(vote.meet_up_id.to_bytes().as_slice())
-                .bind(vote.vote)
+                .bind(vote.position)
                 .bind(now)
                 .bind(now)
                 .execute(&mut *transaction)
@@ -45,7 +45,7 @@ impl VoteGateway for SqliteDatabaseGateway {
         user_id: &Ulid,
     ) -> Result<Vec<Vote>, VoteError> {
         let votes =
-            sqlx::query("SELECT * FROM meet_up_papers_votes WHERE meet_up_id = ? AND user_id = ? ORDER BY vote DESC")
+            sqlx::query("SELECT * FROM meet_up_papers_votes WHERE meet_up_id = ? AND user_id = ? ORDER BY position")
                 .bind(meet_up_id.to_bytes().as_slice())
                 .bind(user_id.to_bytes().as_slice())
                 .try_map(vote_from_row)
@@ -82,6 +82,6 @@ fn vote_from_row(row: SqliteRow) -> Result<Vote, Error> {
                 .try_into()
                 .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
         ),
-        vote: row.try_get("vote")?,
+        position: row.try_get("position")?,
     })
 }
@@ -0,0 +1,172 @@
+use anyhow::Result;
+use ulid::Ulid;
+
+use domain::{
+    create_api_token, list_api_tokens, login_with_api_token, revoke_api_token, ApiToken,
+    ApiTokenScope, CreateApiTokenError, RevokeApiTokenError,
+};
+use tests::{build_gateway, create_random_user};
+
+#[::tokio::test]
+async fn create_api_token_and_login_with_it() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let (api_token, secret) = create_api_token(
+        &gateway,
+        &gateway,
+        &user,
+        " bot ",
+        &[ApiTokenScope::Vote, ApiTokenScope::ReadOnly],
+        false,
+    )
+    .await?;
+    assert_eq!("bot", api_token.name);
+    assert_eq!(
+        vec![ApiTokenScope::ReadOnly, ApiTokenScope::Vote],
+        api_token.scopes
+    );
+
+    let (logged_user, used_token) = login_with_api_token(&gateway, &gateway, &secret).await?;
+    assert_eq!(user, logged_user);
+    assert_eq!(api_token.id, used_token.id);
+    assert!(used_token.last_used_at.is_some());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn create_api_token_stores_only_the_hash() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let (_, secret) = create_api_token(
+        &gateway,
+        &gateway,
+        &user,
+        "bot",
+        &[ApiTokenScope::ReadOnly],
+        false,
+    )
+    .await?;
+    assert_ne!(secret, ApiToken::hash_secret(&secret));
+    login_with_api_token(&gateway, &gateway, &ApiToken::hash_secret(&secret))
+        .await
+        .expect_err("The hash is not a valid secret");
+    Ok(())
+}
+
+#[::tokio::test]
+async fn create_api_token_with_invalid_params() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let err = create_api_token(
+        &gateway,
+        &gateway,
+        &user,
+        " ",
+        &[ApiTokenScope::ReadOnly],
+        false,
+    )
+    .await
+    .expect_err("Should error out");
+    assert!(matches!(err, CreateApiTokenError::EmptyName));
+    let err = create_api_token(&gateway, &gateway, &user, "bot", &[], false)
+        .await
+        .expect_err("Should error out");
+    assert!(matches!(err, CreateApiTokenError::NoScopes));
+    let err = create_api_token(
+        &gateway,
+        &gateway,
+        &user,
+        "bot",
+        &[ApiTokenScope::Admin],
+        false,
+    )
+    .await
+    .expect_err("Should error out");
+    assert!(matches!(err, CreateApiTokenError::AdminScopeNotAllowed));
+    assert!(list_api_tokens(&gateway, &user).await?.is_empty());
+
+    let (api_token, _) = create_api_token(
+        &gateway,
+        &gateway,
+        &user,
+        "admin",
+        &[ApiTokenScope::Admin],
+        true,
+    )
+    .await?;
+    assert!(api_token.has_scope(ApiTokenScope::Admin));
+    Ok(())
+}
+
+#[::tokio::test]
+async fn revoked_api_token_can_not_login() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let (api_token, secret) = create_api_token(
+        &gateway,
+        &gateway,
+        &user,
+        "bot",
+        &[ApiTokenScope::ReadOnly],
+        false,
+    )
+    .await?;
+    let (other_token, other_secret) = create_api_token(
+        &gateway,
+        &gateway,
+        &user,
+        "other",
+        &[ApiTokenScope::Vote],
+        false,
+    )
+    .await?;
+    revoke_api_token(&gateway, &gateway, &user, &api_token.id).await?;
+
+    login_with_api_token(&gateway, &gateway, &secret)
+        .await
+        .expect_err("Revoked token should not login");
+    login_with_api_token(&gateway, &gateway, &other_secret).await?;
+    let tokens = list_api_tokens(&gateway, &user).await?;
+    assert_eq!(
+        vec![(other_token.id, false), (api_token.id, true)],
+        tokens
+            .iter()
+            .map(|token| (token.id, token.revoked_at.is_some()))
+            .collect::<Vec<_>>()
+    );
+
+    let err = revoke_api_token(&gateway, &gateway, &user, &api_token.id)
+        .await
+        .expect_err("Already revoked");
+    assert!(matches!(err, RevokeApiTokenError::NotFound(_)));
+    Ok(())
+}
+
+#[::tokio::test]
+async fn revoke_api_token_of_another_user() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let other_user = create_random_user(&gateway).await?;
+    let (api_token, secret) = create_api_token(
+        &gateway,
+        &gateway,
+        &user,
+        "bot",
+        &[ApiTokenScope::ReadOnly],
+        false,
+    )
+    .await?;
+    let err = revoke_api_token(&gateway, &gateway, &other_user, &api_token.id)
+        .await
+        .expect_err("Should error out");
+    assert_eq!(
+        format!("API token `{}` not found", api_token.id),
+        err.to_string()
+    );
+    let err = revoke_api_token(&gateway, &gateway, &user, &Ulid::new())
+        .await
+        .expect_err("Should error out");
+    assert!(matches!(err, RevokeApiTokenError::NotFound(_)));
+    login_with_api_token(&gateway, &gateway, &secret).await?;
+    Ok(())
+}
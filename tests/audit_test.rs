@@ -0,0 +1,213 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use serde_json::json;
+
+use domain::{
+    advance_meet_ups, create_new_meet_up, list_audit_events, move_meet_up_to_scheduled,
+    move_meet_up_to_voting, register_event_goer, store_votes, submit_paper, AuditAction,
+    AuditFilter, AuditGateway, Location, MeetUpSettings, MeetUpState, RegistrationStatus,
+    VotingMethod,
+};
+use shared::{test::set_now, utc_now};
+use tests::{
+    build_gateway, build_paper_with_user, create_meet_up_with_settings, create_random_user,
+};
+
+#[::tokio::test]
+async fn admin_actions_are_recorded_with_their_actor() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let admin = create_random_user(&gateway).await?;
+    let meet_up = create_new_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &admin.id,
+        Location::OnSite("Rua".into()),
+        "2030-01-01T19:00:00Z".parse()?,
+        VotingMethod::Borda,
+        1,
+        Some(30),
+        MeetUpSettings::default(),
+    )
+    .await?;
+    move_meet_up_to_voting(&gateway, &gateway, &admin.id, &meet_up.id).await?;
+
+    let events = list_audit_events(&gateway, &gateway, &AuditFilter::default()).await?;
+    let actions = events
+        .iter()
+        .map(|(event, _)| event.action)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![AuditAction::OpenVoting, AuditAction::CreateMeetUp],
+        actions
+    );
+    for (event, actor) in &events {
+        assert_eq!(Some(admin.id), event.actor_id);
+        assert_eq!(Some(&admin), actor.as_ref());
+        assert_eq!(Some(meet_up.id), event.meet_up_id);
+    }
+    let (created, _) = &events[1];
+    assert_eq!(json!("Borda"), created.payload["voting_method"]);
+    assert_eq!(json!(30), created.payload["capacity"]);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn user_mutations_are_recorded() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let admin = create_random_user(&gateway).await?;
+    let user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up_with_settings(&gateway, MeetUpSettings::default()).await?;
+    let paper = build_paper_with_user(user.id);
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    move_meet_up_to_voting(&gateway, &gateway, &admin.id, &meet_up.id).await?;
+    store_votes(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user.id,
+        vec![paper.id],
+    )
+    .await?;
+    move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &admin.id,
+        &meet_up.id,
+    )
+    .await?;
+    assert_eq!(
+        RegistrationStatus::Attending,
+        register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?
+    );
+
+    let events = gateway
+        .list_audit_events(
+            &AuditFilter {
+                actor_id: Some(user.id),
+                ..AuditFilter::default()
+            },
+            10,
+        )
+        .await?;
+    let actions = events.iter().map(|event| event.action).collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            AuditAction::Register,
+            AuditAction::Vote,
+            AuditAction::SubmitPaper
+        ],
+        actions
+    );
+    assert_eq!(Some(user.id), events[0].target_id);
+    assert_eq!(json!({ "paper_ids": [paper.id] }), events[1].payload);
+    assert_eq!(Some(paper.id), events[2].target_id);
+    assert_eq!(json!(paper.title), events[2].payload["title"]);
+
+    let scheduled = gateway
+        .list_audit_events(
+            &AuditFilter {
+                actor_id: Some(admin.id),
+                ..AuditFilter::default()
+            },
+            1,
+        )
+        .await?;
+    assert_eq!(AuditAction::ScheduleMeetUp, scheduled[0].action);
+    assert_eq!(json!({ "paper_ids": [paper.id] }), scheduled[0].payload);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn filter_audit_events_by_meet_up_and_actor() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let admin = create_random_user(&gateway).await?;
+    let other_admin = create_random_user(&gateway).await?;
+    let meet_up_1 = create_meet_up_with_settings(&gateway, MeetUpSettings::default()).await?;
+    let meet_up_2 = create_meet_up_with_settings(&gateway, MeetUpSettings::default()).await?;
+    move_meet_up_to_voting(&gateway, &gateway, &admin.id, &meet_up_1.id).await?;
+    move_meet_up_to_voting(&gateway, &gateway, &other_admin.id, &meet_up_2.id).await?;
+
+    let by_meet_up = list_audit_events(
+        &gateway,
+        &gateway,
+        &AuditFilter {
+            meet_up_id: Some(meet_up_2.id),
+            ..AuditFilter::default()
+        },
+    )
+    .await?;
+    assert_eq!(1, by_meet_up.len());
+    assert_eq!(Some(other_admin.id), by_meet_up[0].0.actor_id);
+
+    let by_actor_and_meet_up = list_audit_events(
+        &gateway,
+        &gateway,
+        &AuditFilter {
+            meet_up_id: Some(meet_up_2.id),
+            actor_id: Some(admin.id),
+        },
+    )
+    .await?;
+    assert!(by_actor_and_meet_up.is_empty());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn scheduler_transitions_have_no_actor() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let now = utc_now();
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            call_for_papers_deadline: Some(now + chrono::Duration::hours(1)),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    set_now(&(now + chrono::Duration::hours(2)));
+    let transitions =
+        advance_meet_ups(&gateway, &gateway, &gateway, &gateway, &mut HashSet::new()).await?;
+    assert!(matches!(
+        transitions[0].result.as_ref().map(|meet_up| &meet_up.state),
+        Ok(MeetUpState::Voting)
+    ));
+
+    let events = list_audit_events(&gateway, &gateway, &AuditFilter::default()).await?;
+    assert_eq!(1, events.len());
+    let (event, actor) = &events[0];
+    assert_eq!(AuditAction::OpenVoting, event.action);
+    assert_eq!(Some(meet_up.id), event.meet_up_id);
+    assert_eq!(None, event.actor_id);
+    assert_eq!(None, *actor);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn failed_actions_are_not_recorded() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let admin = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up_with_settings(&gateway, MeetUpSettings::default()).await?;
+    move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &admin.id,
+        &meet_up.id,
+    )
+    .await
+    .expect_err("Should not schedule a meet up in call for papers");
+
+    assert!(
+        list_audit_events(&gateway, &gateway, &AuditFilter::default())
+            .await?
+            .is_empty()
+    );
+    Ok(())
+}
@@ -1,18 +1,25 @@
 use assertables::{assert_contains, assert_contains_as_result};
 use ulid::Ulid;
 
-use domain::{get_paper, show_call_for_papers, submit_paper, Location, MeetUpState};
+use domain::{
+    edit_paper, get_paper, show_call_for_papers, submit_paper, withdraw_paper, ChangePaperError,
+    Location, MeetUpGateway, MeetUpSettings, MeetUpState, Paper, PaperGateway, SubmitPaperError,
+};
 use shared::utc_now;
-use tests::{build_gateway, build_paper_with_user, create_meet_up, create_random_user};
+use tests::{
+    build_gateway, build_paper_with_user, create_meet_up, create_meet_up_with_settings,
+    create_random_user,
+};
 
 #[::tokio::test]
 async fn show_call_for_papers_without_meet_up() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let err = show_call_for_papers(&gateway, &gateway, &user)
+    let id = Ulid::new();
+    let err = show_call_for_papers(&gateway, &gateway, &id, &user)
         .await
         .expect_err("Should error out");
-    assert_eq!("No future meetups found", err.to_string());
+    assert_eq!(format!("Meet up with id `{id}` not found"), err.to_string());
     Ok(())
 }
 
@@ -20,14 +27,14 @@ async fn show_call_for_papers_without_meet_up() -> anyhow::Result<()> {
 async fn show_call_for_papers_with_wrong_state() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let _ = create_meet_up(
+    let meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
         utc_now(),
         MeetUpState::Voting,
     )
     .await?;
-    let err = show_call_for_papers(&gateway, &gateway, &user)
+    let err = show_call_for_papers(&gateway, &gateway, &meet_up.id, &user)
         .await
         .expect_err("Should error out");
     assert_eq!("Invalid meet up state: Voting", err.to_string());
@@ -45,8 +52,8 @@ async fn show_call_for_papers_without_papers() -> anyhow::Result<()> {
         MeetUpState::CallForPapers,
     )
     .await?;
-    let (show_meet_up, papers, over_the_limit) =
-        show_call_for_papers(&gateway, &gateway, &user).await?;
+    let (show_meet_up, papers, _, over_the_limit) =
+        show_call_for_papers(&gateway, &gateway, &meet_up.id, &user).await?;
     assert_eq!(meet_up, show_meet_up);
     assert!(papers.is_empty());
     assert!(!over_the_limit);
@@ -57,7 +64,7 @@ async fn show_call_for_papers_without_papers() -> anyhow::Result<()> {
 async fn show_call_for_papers_with_papers_but_less_than_limit() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper = build_paper_with_user(user.id.clone());
+    let paper = build_paper_with_user(user.id);
     let meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
@@ -65,9 +72,9 @@ async fn show_call_for_papers_with_papers_but_less_than_limit() -> anyhow::Resul
         MeetUpState::CallForPapers,
     )
     .await?;
-    submit_paper(&gateway, &gateway, paper.clone()).await?;
-    let (show_meet_up, papers, over_the_limit) =
-        show_call_for_papers(&gateway, &gateway, &user).await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    let (show_meet_up, papers, _, over_the_limit) =
+        show_call_for_papers(&gateway, &gateway, &meet_up.id, &user).await?;
     assert_eq!(meet_up, show_meet_up);
     assert_eq!(vec![paper], papers);
     assert!(!over_the_limit);
@@ -78,8 +85,8 @@ async fn show_call_for_papers_with_papers_but_less_than_limit() -> anyhow::Resul
 async fn show_call_for_papers_with_papers_at_limit() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper_1 = build_paper_with_user(user.id.clone());
-    let paper_2 = build_paper_with_user(user.id.clone());
+    let paper_1 = build_paper_with_user(user.id);
+    let paper_2 = build_paper_with_user(user.id);
     let meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
@@ -87,10 +94,10 @@ async fn show_call_for_papers_with_papers_at_limit() -> anyhow::Result<()> {
         MeetUpState::CallForPapers,
     )
     .await?;
-    submit_paper(&gateway, &gateway, paper_1.clone()).await?;
-    submit_paper(&gateway, &gateway, paper_2.clone()).await?;
-    let (show_meet_up, papers, over_the_limit) =
-        show_call_for_papers(&gateway, &gateway, &user).await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper_1.clone()).await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper_2.clone()).await?;
+    let (show_meet_up, papers, _, over_the_limit) =
+        show_call_for_papers(&gateway, &gateway, &meet_up.id, &user).await?;
     assert_eq!(meet_up, show_meet_up);
     assert_eq!(2, papers.len());
     assert_contains!(papers, &paper_1);
@@ -104,8 +111,8 @@ async fn show_call_for_papers_should_only_show_papers_from_correct_user() -> any
     let gateway = build_gateway().await?;
     let user_1 = create_random_user(&gateway).await?;
     let user_2 = create_random_user(&gateway).await?;
-    let paper_1 = build_paper_with_user(user_1.id.clone());
-    let paper_2 = build_paper_with_user(user_2.id.clone());
+    let paper_1 = build_paper_with_user(user_1.id);
+    let paper_2 = build_paper_with_user(user_2.id);
     let meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
@@ -113,15 +120,15 @@ async fn show_call_for_papers_should_only_show_papers_from_correct_user() -> any
         MeetUpState::CallForPapers,
     )
     .await?;
-    submit_paper(&gateway, &gateway, paper_1.clone()).await?;
-    submit_paper(&gateway, &gateway, paper_2.clone()).await?;
-    let (show_meet_up, papers_1, over_the_limit) =
-        show_call_for_papers(&gateway, &gateway, &user_1).await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper_1.clone()).await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper_2.clone()).await?;
+    let (show_meet_up, papers_1, _, over_the_limit) =
+        show_call_for_papers(&gateway, &gateway, &meet_up.id, &user_1).await?;
     assert_eq!(meet_up, show_meet_up);
     assert_eq!(vec![paper_1], papers_1);
     assert!(!over_the_limit);
-    let (show_meet_up, papers_2, over_the_limit) =
-        show_call_for_papers(&gateway, &gateway, &user_2).await?;
+    let (show_meet_up, papers_2, _, over_the_limit) =
+        show_call_for_papers(&gateway, &gateway, &meet_up.id, &user_2).await?;
     assert_eq!(meet_up, show_meet_up);
     assert_eq!(vec![paper_2], papers_2);
     assert!(!over_the_limit);
@@ -132,11 +139,12 @@ async fn show_call_for_papers_should_only_show_papers_from_correct_user() -> any
 async fn submit_paper_without_meet_up() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper = build_paper_with_user(user.id.clone());
-    let err = submit_paper(&gateway, &gateway, paper)
+    let paper = build_paper_with_user(user.id);
+    let id = Ulid::new();
+    let err = submit_paper(&gateway, &gateway, &gateway, &id, paper)
         .await
         .expect_err("Should error out");
-    assert_eq!("No future meetups found", err.to_string());
+    assert_eq!(format!("Meet up with id `{id}` not found"), err.to_string());
     Ok(())
 }
 
@@ -144,15 +152,15 @@ async fn submit_paper_without_meet_up() -> anyhow::Result<()> {
 async fn submit_paper_with_invalid_state() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper = build_paper_with_user(user.id.clone());
-    let _ = create_meet_up(
+    let paper = build_paper_with_user(user.id);
+    let meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
         utc_now(),
         MeetUpState::Voting,
     )
     .await?;
-    let err = submit_paper(&gateway, &gateway, paper)
+    let err = submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper)
         .await
         .expect_err("Should error out");
     assert_eq!("Invalid meet up state: Voting", err.to_string());
@@ -163,19 +171,19 @@ async fn submit_paper_with_invalid_state() -> anyhow::Result<()> {
 async fn submit_paper_over_limit_per_user() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper_1 = build_paper_with_user(user.id.clone());
-    let paper_2 = build_paper_with_user(user.id.clone());
-    let paper_3 = build_paper_with_user(user.id.clone());
-    let _ = create_meet_up(
+    let paper_1 = build_paper_with_user(user.id);
+    let paper_2 = build_paper_with_user(user.id);
+    let paper_3 = build_paper_with_user(user.id);
+    let meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
         utc_now(),
         MeetUpState::CallForPapers,
     )
     .await?;
-    submit_paper(&gateway, &gateway, paper_1).await?;
-    submit_paper(&gateway, &gateway, paper_2).await?;
-    let err = submit_paper(&gateway, &gateway, paper_3)
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper_1).await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper_2).await?;
+    let err = submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper_3)
         .await
         .expect_err("Should error out");
     assert_eq!(
@@ -203,15 +211,388 @@ async fn get_invalid_paper() -> anyhow::Result<()> {
 async fn get_paper_should_return_expected_paper() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper = build_paper_with_user(user.id.clone());
-    let _ = create_meet_up(
+    let paper = build_paper_with_user(user.id);
+    let meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
         utc_now(),
         MeetUpState::CallForPapers,
     )
     .await?;
-    submit_paper(&gateway, &gateway, paper.clone()).await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
     assert_eq!(paper, get_paper(&gateway, &paper.id).await?);
     Ok(())
 }
+
+#[::tokio::test]
+async fn edit_paper_should_keep_email_and_author() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let paper = build_paper_with_user(user.id);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    let edited = edit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user,
+        Paper {
+            id: paper.id,
+            title: "new title".into(),
+            description: "new description".into(),
+            speaker: "new speaker".into(),
+            email: "other email".into(),
+            user_id: Ulid::new(),
+            keep_in_pool: true,
+        },
+    )
+    .await?;
+    let expected = Paper {
+        title: "new title".into(),
+        description: "new description".into(),
+        speaker: "new speaker".into(),
+        keep_in_pool: true,
+        ..paper.clone()
+    };
+    assert_eq!(expected, edited);
+    assert_eq!(expected, get_paper(&gateway, &paper.id).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn edit_paper_from_another_user() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let other_user = create_random_user(&gateway).await?;
+    let paper = build_paper_with_user(user.id);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    let err = edit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &other_user,
+        Paper {
+            title: "new title".into(),
+            ..paper.clone()
+        },
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!(
+        format!(
+            "Paper `{}` not found among the user papers for this meet up",
+            paper.id
+        ),
+        err.to_string()
+    );
+    assert_eq!(paper, get_paper(&gateway, &paper.id).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn edit_paper_after_call_for_papers() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let paper = build_paper_with_user(user.id);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    gateway.update_meet_up_to_voting(&meet_up.id).await?;
+    let err = edit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user,
+        paper.clone(),
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!("Invalid meet up state: Voting", err.to_string());
+    let err = withdraw_paper(&gateway, &gateway, &gateway, &meet_up.id, &user, &paper.id)
+        .await
+        .expect_err("Should error out");
+    assert_eq!("Invalid meet up state: Voting", err.to_string());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn withdraw_paper_should_free_a_slot() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    let papers = [
+        build_paper_with_user(user.id),
+        build_paper_with_user(user.id),
+    ];
+    for paper in &papers {
+        submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    }
+    let (_, _, _, over_the_limit) =
+        show_call_for_papers(&gateway, &gateway, &meet_up.id, &user).await?;
+    assert!(over_the_limit);
+
+    withdraw_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user,
+        &papers[0].id,
+    )
+    .await?;
+    let (_, show_papers, _, over_the_limit) =
+        show_call_for_papers(&gateway, &gateway, &meet_up.id, &user).await?;
+    assert_eq!(vec![papers[1].clone()], show_papers);
+    assert!(!over_the_limit);
+    assert!(get_paper(&gateway, &papers[0].id).await.is_err());
+
+    let paper = build_paper_with_user(user.id);
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper).await?;
+    Ok(())
+}
+
+#[::tokio::test]
+async fn withdraw_paper_from_another_user() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let other_user = create_random_user(&gateway).await?;
+    let paper = build_paper_with_user(user.id);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    withdraw_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &other_user,
+        &paper.id,
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!(paper, get_paper(&gateway, &paper.id).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn submit_paper_over_meet_up_limit_per_user() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            papers_per_user: 1,
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    submit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        build_paper_with_user(user.id),
+    )
+    .await?;
+    let (_, _, _, over_the_limit) =
+        show_call_for_papers(&gateway, &gateway, &meet_up.id, &user).await?;
+    assert!(over_the_limit);
+    let err = submit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        build_paper_with_user(user.id),
+    )
+    .await
+    .expect_err("Should error out");
+    assert!(matches!(
+        err,
+        SubmitPaperError::MoreThanLimitPapersPerUserPerMeetUp(1)
+    ));
+    Ok(())
+}
+
+#[::tokio::test]
+async fn submit_paper_over_max_papers() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            max_papers: Some(2),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    for _ in 0..2 {
+        let user = create_random_user(&gateway).await?;
+        submit_paper(
+            &gateway,
+            &gateway,
+            &gateway,
+            &meet_up.id,
+            build_paper_with_user(user.id),
+        )
+        .await?;
+    }
+    let user = create_random_user(&gateway).await?;
+    let err = submit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        build_paper_with_user(user.id),
+    )
+    .await
+    .expect_err("Should error out");
+    assert!(matches!(
+        err,
+        SubmitPaperError::MoreThanMaxPapersPerMeetUp(2)
+    ));
+    assert_eq!(2, gateway.get_papers_from_meet_up(&meet_up.id).await?.len());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn submit_paper_after_call_for_papers_deadline() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let deadline = utc_now() - chrono::Duration::minutes(1);
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            call_for_papers_deadline: Some(deadline),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    let err = submit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        build_paper_with_user(user.id),
+    )
+    .await
+    .expect_err("Should error out");
+    assert!(matches!(err, SubmitPaperError::CallForPapersClosed(at) if at == deadline));
+    Ok(())
+}
+
+#[::tokio::test]
+async fn submit_paper_before_call_for_papers_deadline() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            call_for_papers_deadline: Some(utc_now() + chrono::Duration::hours(1)),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    let paper = build_paper_with_user(user.id);
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    assert_eq!(
+        vec![paper],
+        gateway.get_papers_from_meet_up(&meet_up.id).await?
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn edit_paper_after_call_for_papers_deadline() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let deadline = utc_now() - chrono::Duration::minutes(1);
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            call_for_papers_deadline: Some(deadline),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    // Submitted before the deadline.
+    let paper = build_paper_with_user(user.id);
+    gateway
+        .store_paper_with_meet_up(&paper, &meet_up.id, 100, None)
+        .await?;
+    let err = edit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user,
+        Paper {
+            title: "New title".into(),
+            ..paper.clone()
+        },
+    )
+    .await
+    .expect_err("Should error out");
+    assert!(matches!(err, ChangePaperError::CallForPapersClosed(at) if at == deadline));
+    assert_eq!(paper, get_paper(&gateway, &paper.id).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn withdraw_paper_after_call_for_papers_deadline() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let deadline = utc_now() - chrono::Duration::minutes(1);
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            call_for_papers_deadline: Some(deadline),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    // Submitted before the deadline.
+    let paper = build_paper_with_user(user.id);
+    gateway
+        .store_paper_with_meet_up(&paper, &meet_up.id, 100, None)
+        .await?;
+    let err = withdraw_paper(&gateway, &gateway, &gateway, &meet_up.id, &user, &paper.id)
+        .await
+        .expect_err("Should error out");
+    assert!(matches!(err, ChangePaperError::CallForPapersClosed(at) if at == deadline));
+    assert_eq!(
+        vec![paper],
+        gateway.get_papers_from_meet_up(&meet_up.id).await?
+    );
+    Ok(())
+}
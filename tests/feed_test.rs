@@ -0,0 +1,148 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use ulid::Ulid;
+
+use domain::{
+    list_feed_entries, move_meet_up_back_to_voting, schedule_meet_up_with_paper, FeedEntryKind,
+    Location, MeetUpGateway, MeetUpState, MeetUpStateKind, PaperGateway,
+};
+use shared::{test::set_now, utc_now};
+use tests::{
+    build_gateway, build_paper_with_user, build_talks, create_meet_up, create_random_user,
+};
+
+fn advance_clock() -> DateTime<Utc> {
+    let now = utc_now() + chrono::Duration::minutes(1);
+    set_now(&now);
+    now
+}
+
+#[::tokio::test]
+async fn list_feed_entries_without_meet_ups() -> Result<()> {
+    let gateway = build_gateway().await?;
+    assert!(list_feed_entries(&gateway).await?.is_empty());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn state_changes_are_recorded_in_order() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date = utc_now() + chrono::Duration::days(10);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Scheduled(build_talks(build_paper_with_user(user.id), date)),
+    )
+    .await?;
+    move_meet_up_back_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+    gateway.cancel_meet_up(&meet_up.id).await?;
+    // Invalid transitions are not recorded.
+    assert!(gateway.cancel_meet_up(&meet_up.id).await.is_err());
+    let states = gateway
+        .list_meet_up_state_changes(&meet_up.id)
+        .await?
+        .into_iter()
+        .map(|change| change.state)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            MeetUpStateKind::CallForPapers,
+            MeetUpStateKind::Voting,
+            MeetUpStateKind::Scheduled,
+            MeetUpStateKind::Voting,
+            MeetUpStateKind::Cancelled,
+        ],
+        states
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn list_feed_entries_of_done_meet_up() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date = utc_now() - chrono::Duration::days(1);
+    let call_for_papers_at = advance_clock();
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    let voting_at = advance_clock();
+    gateway.update_meet_up_to_voting(&meet_up.id).await?;
+    let talks = build_talks(build_paper_with_user(user.id), date);
+    gateway
+        .store_paper_with_meet_up(&talks[0].paper, &meet_up.id, 100, None)
+        .await?;
+    let scheduled_at = advance_clock();
+    gateway
+        .update_meet_up_to_scheduled(&meet_up.id, &talks)
+        .await?;
+    let done_at = advance_clock();
+    let link = "https://example.com/recording".parse()?;
+    gateway.finish_meet_up(&meet_up.id, link).await?;
+
+    let entries = list_feed_entries(&gateway).await?;
+    assert_eq!(
+        vec![
+            (
+                FeedEntryKind::Done("https://example.com/recording".parse()?),
+                done_at
+            ),
+            (FeedEntryKind::TalkScheduled(talks[0].clone()), scheduled_at),
+            (FeedEntryKind::VotingOpened, voting_at),
+            (FeedEntryKind::CallForPapersOpened, call_for_papers_at),
+        ],
+        entries
+            .into_iter()
+            .map(|entry| (entry.kind, entry.date))
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn list_feed_entries_after_voting_is_reopened() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date = utc_now() + chrono::Duration::days(10);
+    let talks = build_talks(build_paper_with_user(user.id), date);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Scheduled(talks.clone()),
+    )
+    .await?;
+    let reopened_at = advance_clock();
+    move_meet_up_back_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+    let rescheduled_at = advance_clock();
+    let meet_up = schedule_meet_up_with_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &talks[0].paper.id,
+    )
+    .await?;
+
+    let entries = list_feed_entries(&gateway).await?;
+    assert_eq!(4, entries.len());
+    assert_eq!(
+        FeedEntryKind::TalkScheduled(meet_up.talks()[0].clone()),
+        entries[0].kind
+    );
+    assert_eq!(rescheduled_at, entries[0].date);
+    assert_eq!(FeedEntryKind::VotingOpened, entries[1].kind);
+    assert_eq!(reopened_at, entries[1].date);
+    assert_eq!(FeedEntryKind::VotingOpened, entries[2].kind);
+    assert!(entries[2].date < reopened_at);
+    assert_eq!(FeedEntryKind::CallForPapersOpened, entries[3].kind);
+    assert!(entries.iter().all(|entry| entry.meet_up == meet_up));
+    Ok(())
+}
@@ -1,13 +1,11 @@
-use anyhow::anyhow;
+use chrono::Duration;
 
 use domain::{
-    login_with_access_token, login_with_github_code, AccessToken, LoginMethod, RefreshTokenError,
-};
-use shared::utc_now;
-use tests::{
-    build_gateway, create_random_user, create_user_with_access_token_and_login_method,
-    GithubGatewayMock,
+    backfill_github_ids, issue_login_state, login_with_github_code, verify_login_state,
+    AccessToken, GithubUser, LoginMethod, LoginStateError, User, UserGateway,
 };
+use shared::{test::set_now, utc_now};
+use tests::{build_gateway, create_random_user, create_user_with_login_method, GithubGatewayMock};
 
 #[::tokio::test]
 async fn login_with_github_code_for_new_user() -> anyhow::Result<()> {
@@ -27,16 +25,23 @@ async fn login_with_github_code_for_new_user() -> anyhow::Result<()> {
         .await
         .push_user_info(move |access_token| {
             assert_eq!(&github_access_token, access_token);
-            Ok(("nickname".into(), "email@email.com".into()))
+            Ok(GithubUser {
+                id: 1,
+                login: "nickname".into(),
+                email: "email@email.com".into(),
+            })
         })
         .await;
     let user = login_with_github_code(&user_gateway, &github_gateway, github_code.into()).await?;
     assert_eq!("nickname", user.nickname);
     assert_eq!("email@email.com", user.email);
-    assert_eq!(
-        utc_now() + chrono::Duration::days(1),
-        *user.access_token.expire_at()
-    );
+    assert!(matches!(
+        user.login_method,
+        LoginMethod::Github {
+            github_id: Some(1),
+            ..
+        }
+    ));
     Ok(())
 }
 
@@ -48,6 +53,7 @@ async fn login_with_github_code_for_existent_user() -> anyhow::Result<()> {
     let user_gateway = build_gateway().await?;
     let user = create_random_user(&user_gateway).await?;
     let email = user.email.clone();
+    let github_id = github_id_of(&user);
     let github_access_token_clone = github_access_token.clone();
     let github_gateway = GithubGatewayMock::default()
         .push_exchange_code(move |code| {
@@ -60,142 +66,309 @@ async fn login_with_github_code_for_existent_user() -> anyhow::Result<()> {
         .await
         .push_user_info(move |access_token| {
             assert_eq!(&github_access_token, access_token);
-            Ok(("nickname".into(), email.clone()))
+            Ok(GithubUser {
+                id: github_id,
+                login: "nickname".into(),
+                email: email.clone(),
+            })
         })
         .await;
     let logged_user =
         login_with_github_code(&user_gateway, &github_gateway, github_code.into()).await?;
     assert_eq!("nickname", logged_user.nickname);
     assert_eq!(user.email, logged_user.email);
+    assert_eq!(user.id, logged_user.id);
     Ok(())
 }
 
 #[::tokio::test]
-async fn login_with_access_token_for_not_expired_user() -> anyhow::Result<()> {
+async fn login_with_github_code_after_changing_the_email_keeps_the_user() -> anyhow::Result<()> {
     let user_gateway = build_gateway().await?;
-    let github_gateway = GithubGatewayMock::default();
-    let user_1 = create_random_user(&user_gateway).await?;
-    let user_2 = create_random_user(&user_gateway).await?;
-    let logged_user_1 =
-        login_with_access_token(&user_gateway, &github_gateway, user_1.access_token.token())
-            .await?;
-    let logged_user_2 =
-        login_with_access_token(&user_gateway, &github_gateway, user_2.access_token.token())
-            .await?;
-    assert_eq!(user_1, logged_user_1);
-    assert_eq!(user_2, logged_user_2);
+    let user = create_random_user(&user_gateway).await?;
+    let github_gateway = github_login_mock(GithubUser {
+        id: github_id_of(&user),
+        login: user.nickname.clone(),
+        email: "new@email.com".into(),
+    })
+    .await;
+
+    let logged_user = login_with_github_code(&user_gateway, &github_gateway, "code".into()).await?;
+    assert_eq!(user.id, logged_user.id);
+    assert_eq!("new@email.com", logged_user.email);
+    assert_eq!(
+        user.id,
+        user_gateway.list_users_with_email("new@email.com").await?[0].id
+    );
     Ok(())
 }
 
 #[::tokio::test]
-async fn login_with_expired_access_token_but_valid_github_access_token() -> anyhow::Result<()> {
+async fn login_with_github_code_for_another_account_with_the_same_email() -> anyhow::Result<()> {
     let user_gateway = build_gateway().await?;
-    let github_access_token = AccessToken::generate_new();
-    let github_access_token_clone = github_access_token.clone();
-    let github_gateway = GithubGatewayMock::default()
-        .push_user_info(move |access_token| {
-            assert_eq!(access_token, &github_access_token_clone);
-            Ok(("nickname".into(), "email@email.com".into()))
-        })
-        .await;
-    let user = create_user_with_access_token_and_login_method(
-        &user_gateway,
-        AccessToken::new("token_1".into(), utc_now() - chrono::Duration::seconds(1)),
-        LoginMethod::Github {
-            access_token: github_access_token.clone(),
-            refresh_token: AccessToken::generate_new(),
-        },
-    )
-    .await?;
+    let user = create_random_user(&user_gateway).await?;
+    let github_gateway = github_login_mock(GithubUser {
+        id: github_id_of(&user) + 1,
+        login: "other".into(),
+        email: user.email.clone(),
+    })
+    .await;
 
-    let logged_user =
-        login_with_access_token(&user_gateway, &github_gateway, user.access_token.token()).await?;
-    assert_eq!("email@email.com", logged_user.email);
-    assert_eq!("nickname", logged_user.nickname);
+    let logged_user = login_with_github_code(&user_gateway, &github_gateway, "code".into()).await?;
+    assert_ne!(user.id, logged_user.id);
+    assert_eq!(user.email, logged_user.email);
+    assert_eq!(user, user_gateway.get_user_with_id(&user.id).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn login_with_github_code_stores_the_github_id_of_users_without_it() -> anyhow::Result<()> {
+    let user_gateway = build_gateway().await?;
+    let user = create_user_with_login_method(&user_gateway, legacy_login_method()).await?;
+    let github_gateway = github_login_mock(GithubUser {
+        id: 42,
+        login: user.nickname.clone(),
+        email: user.email.to_uppercase(),
+    })
+    .await;
+
+    let logged_user = login_with_github_code(&user_gateway, &github_gateway, "code".into()).await?;
     assert_eq!(user.id, logged_user.id);
-    assert_ne!(user.access_token, logged_user.access_token);
+    assert_eq!(user.id, user_gateway.get_user_with_github_id(42).await?.id);
     Ok(())
 }
 
 #[::tokio::test]
-async fn login_with_expired_access_token_expired_github_access_token_but_valid_refresh_token(
+async fn login_with_github_code_finds_legacy_users_sharing_the_email_with_others(
 ) -> anyhow::Result<()> {
     let user_gateway = build_gateway().await?;
-    let github_access_token = AccessToken::generate_new();
-    let github_refresh_token = AccessToken::generate_new();
-    let github_access_token_clone = github_access_token.clone();
-    let github_access_token_clone_2 = github_access_token.clone();
-    let github_refresh_token_clone = github_refresh_token.clone();
-    let github_gateway = GithubGatewayMock::default()
-        .push_refresh_token(move |refresh_token| {
-            assert_eq!(refresh_token, &github_refresh_token_clone);
-            Ok((
-                github_access_token_clone.clone(),
-                AccessToken::generate_new(),
-            ))
-        })
-        .await
-        .push_user_info(move |access_token| {
-            assert_eq!(access_token, &github_access_token_clone_2);
-            Ok(("nickname".into(), "email@email.com".into()))
-        })
-        .await;
-    let user = create_user_with_access_token_and_login_method(
+    let oidc_user = create_user_with_login_method(
         &user_gateway,
-        AccessToken::new("token_1".into(), utc_now() - chrono::Duration::seconds(1)),
-        LoginMethod::Github {
-            access_token: AccessToken::new(
-                "token_2".into(),
-                utc_now() - chrono::Duration::seconds(1),
-            )
-            .clone(),
-            refresh_token: github_refresh_token.clone(),
+        LoginMethod::Oidc {
+            provider: "keycloak".into(),
+            subject: "subject".into(),
         },
     )
     .await?;
+    let user = create_user_with_login_method(&user_gateway, legacy_login_method()).await?;
+    let user = user_gateway
+        .store_user(User {
+            email: oidc_user.email.clone(),
+            ..user
+        })
+        .await?;
+    let github_gateway = github_login_mock(GithubUser {
+        id: 42,
+        login: user.nickname.clone(),
+        email: user.email.clone(),
+    })
+    .await;
 
-    let logged_user =
-        login_with_access_token(&user_gateway, &github_gateway, user.access_token.token()).await?;
-    assert_eq!("email@email.com", logged_user.email);
-    assert_eq!("nickname", logged_user.nickname);
+    let logged_user = login_with_github_code(&user_gateway, &github_gateway, "code".into()).await?;
     assert_eq!(user.id, logged_user.id);
-    let LoginMethod::Github {
-        access_token,
-        refresh_token: _,
-    } = logged_user.login_method;
-    assert_eq!(github_access_token, access_token);
-    assert_ne!(user.access_token, logged_user.access_token);
+    assert_eq!(
+        oidc_user,
+        user_gateway.get_user_with_id(&oidc_user.id).await?
+    );
     Ok(())
 }
 
 #[::tokio::test]
-async fn login_with_all_expired_tokens() -> anyhow::Result<()> {
+async fn backfill_github_ids_of_users_without_them() -> anyhow::Result<()> {
     let user_gateway = build_gateway().await?;
-    let github_refresh_token = AccessToken::generate_new();
-    let github_refresh_token_clone = github_refresh_token.clone();
+    create_random_user(&user_gateway).await?;
+    let user = create_user_with_login_method(&user_gateway, legacy_login_method()).await?;
+    let expired_user = create_user_with_login_method(
+        &user_gateway,
+        LoginMethod::Github {
+            github_id: None,
+            access_token: AccessToken::new("expired".into(), utc_now() - Duration::seconds(1)),
+            refresh_token: AccessToken::generate_new(),
+        },
+    )
+    .await?;
+    let refreshed_access_token = AccessToken::generate_new();
+    let refreshed_token = refreshed_access_token.token().to_string();
+    // The users created in the same instant come in any order.
+    let user_info = move |access_token: &AccessToken| {
+        Ok(if access_token.token() == refreshed_token {
+            GithubUser {
+                id: 2,
+                login: "expired".into(),
+                email: "expired@email.com".into(),
+            }
+        } else {
+            GithubUser {
+                id: 1,
+                login: "user".into(),
+                email: "user@email.com".into(),
+            }
+        })
+    };
     let github_gateway = GithubGatewayMock::default()
-        .push_refresh_token(move |refresh_token| {
-            assert_eq!(refresh_token, &github_refresh_token_clone);
-            Err(RefreshTokenError::Unknown(anyhow!("Invalid token")))
+        .push_user_info(user_info.clone())
+        .await
+        .push_user_info(user_info.clone())
+        .await
+        .push_refresh_token(move |_| {
+            Ok((refreshed_access_token.clone(), AccessToken::generate_new()))
         })
         .await;
-    let user = create_user_with_access_token_and_login_method(
+
+    let mut results = backfill_github_ids(&user_gateway, &github_gateway)
+        .await?
+        .into_iter()
+        .map(|(id, result)| Ok((id, result?)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    results.sort_by_key(|(_, github_id)| *github_id);
+    assert_eq!(vec![(user.id, 1), (expired_user.id, 2)], results);
+    let backfilled_user = user_gateway.get_user_with_github_id(1).await?;
+    assert_eq!(user.id, backfilled_user.id);
+    assert_eq!(user.email, backfilled_user.email);
+    assert_eq!(
+        expired_user.id,
+        user_gateway.get_user_with_github_id(2).await?.id
+    );
+    assert!(user_gateway
+        .list_users_without_github_id()
+        .await?
+        .is_empty());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn backfill_github_ids_skips_expired_and_taken_accounts() -> anyhow::Result<()> {
+    let user_gateway = build_gateway().await?;
+    let user = create_random_user(&user_gateway).await?;
+    let taken = create_user_with_login_method(&user_gateway, legacy_login_method()).await?;
+    let expired = create_user_with_login_method(
         &user_gateway,
-        AccessToken::new("token_1".into(), utc_now() - chrono::Duration::seconds(1)),
         LoginMethod::Github {
-            access_token: AccessToken::new(
-                "token_2".into(),
-                utc_now() - chrono::Duration::seconds(1),
-            )
-            .clone(),
-            refresh_token: github_refresh_token.clone(),
+            github_id: None,
+            access_token: AccessToken::new("expired".into(), utc_now() - Duration::seconds(1)),
+            refresh_token: AccessToken::new("expired".into(), utc_now() - Duration::seconds(1)),
         },
     )
     .await?;
+    let github_id = github_id_of(&user);
+    let github_gateway = GithubGatewayMock::default()
+        .push_user_info(move |_| {
+            Ok(GithubUser {
+                id: github_id,
+                login: "taken".into(),
+                email: "taken@email.com".into(),
+            })
+        })
+        .await;
 
-    let err = login_with_access_token(&user_gateway, &github_gateway, user.access_token.token())
-        .await
-        .expect_err("Should return error");
-    assert_eq!("Unknown error: `Invalid token`", err.to_string());
+    let results = backfill_github_ids(&user_gateway, &github_gateway).await?;
+    assert_eq!(2, results.len());
+    let result_of = |id| {
+        &results
+            .iter()
+            .find(|(user_id, _)| *user_id == id)
+            .unwrap()
+            .1
+    };
+    assert!(result_of(taken.id).is_err());
+    assert_eq!(
+        "Refresh token is expired",
+        result_of(expired.id).as_ref().unwrap_err().to_string()
+    );
+    assert_eq!(2, user_gateway.list_users_without_github_id().await?.len());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn login_state_returns_to_the_page_that_started_the_login() -> anyhow::Result<()> {
+    let login_state = issue_login_state(b"secret", Some("/voting/01HZ"));
+    assert_eq!(
+        "/voting/01HZ",
+        verify_login_state(b"secret", &login_state.state, &login_state.nonce)?
+    );
+    assert_eq!(
+        utc_now() + chrono::Duration::minutes(10),
+        login_state.expire_at
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn login_state_only_returns_to_local_paths() -> anyhow::Result<()> {
+    for return_to in [
+        None,
+        Some("https://evil.com"),
+        Some("//evil.com"),
+        Some("/\\evil.com"),
+    ] {
+        let login_state = issue_login_state(b"secret", return_to);
+        assert_eq!(
+            "/",
+            verify_login_state(b"secret", &login_state.state, &login_state.nonce)?
+        );
+    }
+    Ok(())
+}
+
+#[::tokio::test]
+async fn login_state_rejects_other_browsers_keys_and_tampering() -> anyhow::Result<()> {
+    let login_state = issue_login_state(b"secret", Some("/user"));
+    let other_login_state = issue_login_state(b"secret", Some("/user"));
+
+    let err = verify_login_state(b"secret", &login_state.state, &other_login_state.nonce)
+        .expect_err("Should reject the nonce of another browser");
+    assert!(matches!(err, LoginStateError::NonceMismatch));
+    let err = verify_login_state(b"other secret", &login_state.state, &login_state.nonce)
+        .expect_err("Should reject states signed with another key");
+    assert!(matches!(err, LoginStateError::Invalid));
+    let (payload, signature) = login_state.state.rsplit_once('.').unwrap();
+    let (payload, _) = payload.rsplit_once('.').unwrap();
+    let tampered = format!("{payload}.{}.{signature}", hex_encode("//evil.com"));
+    let err = verify_login_state(b"secret", &tampered, &login_state.nonce)
+        .expect_err("Should reject a changed return path");
+    assert!(matches!(err, LoginStateError::Invalid));
+    let err = verify_login_state(b"secret", "", "").expect_err("Should reject a missing state");
+    assert!(matches!(err, LoginStateError::Invalid));
+    Ok(())
+}
+
+#[::tokio::test]
+async fn login_state_expires() -> anyhow::Result<()> {
+    let login_state = issue_login_state(b"secret", Some("/user"));
+    set_now(&(login_state.expire_at + chrono::Duration::seconds(1)));
+    let err = verify_login_state(b"secret", &login_state.state, &login_state.nonce)
+        .expect_err("Should reject expired states");
+    assert!(matches!(err, LoginStateError::Expired));
     Ok(())
 }
+
+fn hex_encode(value: &str) -> String {
+    value.bytes().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn github_id_of(user: &User) -> i64 {
+    let LoginMethod::Github {
+        github_id: Some(github_id),
+        ..
+    } = user.login_method
+    else {
+        panic!("Random users have a GitHub id");
+    };
+    github_id
+}
+
+/// Logged in before the GitHub ids were stored.
+fn legacy_login_method() -> LoginMethod {
+    LoginMethod::Github {
+        github_id: None,
+        access_token: AccessToken::generate_new(),
+        refresh_token: AccessToken::generate_new(),
+    }
+}
+
+async fn github_login_mock(github_user: GithubUser) -> GithubGatewayMock {
+    GithubGatewayMock::default()
+        .push_exchange_code(|_| Ok((AccessToken::generate_new(), AccessToken::generate_new())))
+        .await
+        .push_user_info(move |_| Ok(github_user.clone()))
+        .await
+}
@@ -0,0 +1,144 @@
+use anyhow::Result;
+use ulid::Ulid;
+
+use domain::{
+    register_event_goer, show_meet_up_details, show_user_meet_up_activity, show_voting,
+    submit_paper, update_meet_up, Location, MeetUpGateway, MeetUpRegistration, MeetUpState,
+};
+use shared::utc_now;
+use tests::{
+    build_gateway, build_paper_with_user, build_talks, create_meet_up, create_random_user,
+};
+
+#[::tokio::test]
+async fn show_meet_up_details_not_found() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let id = Ulid::new();
+    let err = show_meet_up_details(&gateway, &gateway, &id)
+        .await
+        .expect_err("Should error out");
+    assert_eq!(format!("Meet up with `{id} not found"), err.to_string());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn show_meet_up_details_counts_attendees() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date = utc_now() + chrono::Duration::days(1);
+    let location = Location::OnSite("location".into());
+    let meet_up = create_meet_up(
+        &gateway,
+        location.clone(),
+        date,
+        MeetUpState::Scheduled(build_talks(build_paper_with_user(user.id), date)),
+    )
+    .await?;
+    let (meet_up, _) = update_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        location,
+        date,
+        Some(10),
+    )
+    .await?;
+    register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+
+    let details = show_meet_up_details(&gateway, &gateway, &meet_up.id).await?;
+    assert_eq!(meet_up, details.meet_up);
+    assert_eq!(None, details.metadata);
+    assert_eq!(1, details.attendees);
+    assert_eq!(Some(9), details.remaining_seats);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn show_meet_up_details_of_done_meet_up_has_metadata() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date = utc_now() - chrono::Duration::days(1);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Done {
+            talks: build_talks(build_paper_with_user(user.id), date),
+            link: "https://example.com".parse()?,
+        },
+    )
+    .await?;
+
+    let details = show_meet_up_details(&gateway, &gateway, &meet_up.id).await?;
+    assert_eq!(
+        Some(gateway.get_meet_up_metadata(meet_up.id).await?),
+        details.metadata
+    );
+    assert_eq!(0, details.attendees);
+    assert_eq!(None, details.remaining_seats);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn show_user_meet_up_activity_without_activity() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now() + chrono::Duration::days(1),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+
+    let activity =
+        show_user_meet_up_activity(&gateway, &gateway, &gateway, &gateway, &meet_up.id, &user)
+            .await?;
+    assert!(activity.papers.is_empty());
+    assert!(activity.votes.is_empty());
+    assert_eq!(MeetUpRegistration::default(), activity.registration);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn show_user_meet_up_activity_with_papers_and_votes() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let other_user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now() + chrono::Duration::days(1),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    let paper = build_paper_with_user(user.id);
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    submit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        build_paper_with_user(other_user.id),
+    )
+    .await?;
+    gateway.update_meet_up_to_voting(&meet_up.id).await?;
+    let (_, ballot) = show_voting(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+
+    let activity =
+        show_user_meet_up_activity(&gateway, &gateway, &gateway, &gateway, &meet_up.id, &user)
+            .await?;
+    assert_eq!(vec![paper], activity.papers);
+    assert_eq!(
+        ballot.iter().map(|paper| paper.id).collect::<Vec<_>>(),
+        activity
+            .votes
+            .iter()
+            .map(|vote| vote.paper_id)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(None, activity.registration.status);
+    Ok(())
+}
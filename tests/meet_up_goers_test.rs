@@ -1,15 +1,52 @@
-use domain::{register_event_goer, Location, MeetUpGoersGateway, MeetUpState};
+use domain::{
+    check_in_event_goer, get_meet_up_registration, list_attendees, register_event_goer,
+    show_home_page, unregister_event_goer, update_meet_up, Location, MeetUp, MeetUpGoersGateway,
+    MeetUpRegistration, MeetUpState, RegistrationStatus,
+};
+use gateway::SqliteDatabaseGateway;
 use shared::utc_now;
-use tests::{build_gateway, build_paper_with_user, create_meet_up, create_random_user};
+use tests::{
+    build_gateway, build_paper_with_user, build_talks, create_meet_up, create_random_user,
+};
+use ulid::Ulid;
+
+async fn create_scheduled_meet_up(
+    gateway: &SqliteDatabaseGateway,
+    location: Location,
+    capacity: Option<u32>,
+) -> anyhow::Result<MeetUp> {
+    let user = create_random_user(gateway).await?;
+    let date = utc_now() + chrono::Duration::days(1);
+    let meet_up = create_meet_up(
+        gateway,
+        location.clone(),
+        date,
+        MeetUpState::Scheduled(build_talks(build_paper_with_user(user.id), date)),
+    )
+    .await?;
+    let (meet_up, _) = update_meet_up(
+        gateway,
+        gateway,
+        gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        location,
+        date,
+        capacity,
+    )
+    .await?;
+    Ok(meet_up)
+}
 
 #[::tokio::test]
 pub async fn register_meet_up_goers_without_meet_up() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let err = register_event_goer(&gateway, &gateway, &user.id)
+    let id = Ulid::new();
+    let err = register_event_goer(&gateway, &gateway, &gateway, &id, &user.id)
         .await
         .expect_err("Should error out");
-    assert_eq!("No future meetups found", err.to_string());
+    assert_eq!(format!("Meet up with id `{id}` not found"), err.to_string());
     Ok(())
 }
 
@@ -17,14 +54,14 @@ pub async fn register_meet_up_goers_without_meet_up() -> anyhow::Result<()> {
 pub async fn register_meet_up_goers_invalid_state() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let _ = create_meet_up(
+    let meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
         utc_now(),
         MeetUpState::CallForPapers,
     )
     .await?;
-    let err = register_event_goer(&gateway, &gateway, &user.id)
+    let err = register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id)
         .await
         .expect_err("Should error out");
     assert_eq!("Invalid meet up state: CallForPapers", err.to_string());
@@ -35,12 +72,12 @@ pub async fn register_meet_up_goers_invalid_state() -> anyhow::Result<()> {
 pub async fn register_meet_up_goers() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper = build_paper_with_user(user.id.clone());
+    let paper = build_paper_with_user(user.id);
     let meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
         utc_now(),
-        MeetUpState::Scheduled(paper),
+        MeetUpState::Scheduled(build_talks(paper, utc_now())),
     )
     .await?;
     assert!(
@@ -48,7 +85,7 @@ pub async fn register_meet_up_goers() -> anyhow::Result<()> {
             .is_user_registered_to_meet_up(&user.id, &meet_up.id)
             .await?
     );
-    register_event_goer(&gateway, &gateway, &user.id).await?;
+    register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
     assert!(
         gateway
             .is_user_registered_to_meet_up(&user.id, &meet_up.id)
@@ -56,3 +93,421 @@ pub async fn register_meet_up_goers() -> anyhow::Result<()> {
     );
     Ok(())
 }
+
+#[::tokio::test]
+pub async fn register_meet_up_goers_past_capacity_goes_to_waitlist() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up =
+        create_scheduled_meet_up(&gateway, Location::OnSite("location".into()), Some(1)).await?;
+    let mut statuses = vec![];
+    for _ in 0..3 {
+        let user = create_random_user(&gateway).await?;
+        statuses
+            .push(register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?);
+    }
+    assert_eq!(
+        vec![
+            RegistrationStatus::Attending,
+            RegistrationStatus::Waitlisted(1),
+            RegistrationStatus::Waitlisted(2),
+        ],
+        statuses
+    );
+    assert_eq!(
+        1,
+        gateway
+            .get_number_attendees_from_meet_up(&meet_up.id)
+            .await?
+    );
+    let (home_meet_ups, _) = show_home_page(&gateway, &gateway, None).await?;
+    assert_eq!(Some(0), home_meet_ups[0].1.remaining_seats);
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn register_meet_up_goers_twice_keeps_status() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up =
+        create_scheduled_meet_up(&gateway, Location::OnSite("location".into()), Some(1)).await?;
+    let attending = create_random_user(&gateway).await?;
+    let waitlisted = create_random_user(&gateway).await?;
+    register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &attending.id).await?;
+    register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &waitlisted.id).await?;
+    assert_eq!(
+        RegistrationStatus::Waitlisted(1),
+        register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &waitlisted.id).await?
+    );
+    assert_eq!(
+        RegistrationStatus::Attending,
+        register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &attending.id).await?
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn raising_capacity_promotes_waitlist_in_order() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up =
+        create_scheduled_meet_up(&gateway, Location::OnSite("location".into()), Some(1)).await?;
+    let mut users = vec![];
+    for _ in 0..4 {
+        let user = create_random_user(&gateway).await?;
+        register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+        users.push(user);
+    }
+    let (meet_up, promoted_user_ids) = update_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        meet_up.location,
+        meet_up.date,
+        Some(2),
+    )
+    .await?;
+    assert_eq!(Some(2), meet_up.capacity);
+    assert_eq!(vec![users[1].id], promoted_user_ids);
+    let mut statuses = vec![];
+    for user in &users {
+        statuses.push(
+            gateway
+                .get_registration_status(&user.id, &meet_up.id)
+                .await?,
+        );
+    }
+    assert_eq!(
+        vec![
+            Some(RegistrationStatus::Attending),
+            Some(RegistrationStatus::Attending),
+            Some(RegistrationStatus::Waitlisted(1)),
+            Some(RegistrationStatus::Waitlisted(2)),
+        ],
+        statuses
+    );
+    assert_eq!(
+        Vec::<Ulid>::new(),
+        gateway
+            .promote_waitlisted_users(&meet_up.id, meet_up.capacity)
+            .await?
+    );
+    assert_eq!(
+        vec![users[2].id, users[3].id],
+        gateway.promote_waitlisted_users(&meet_up.id, None).await?
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn online_meet_ups_have_no_capacity() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up = create_scheduled_meet_up(
+        &gateway,
+        Location::Online {
+            video_conference_link: "https://example.com".parse()?,
+            calendar_link: "https://example.com".parse()?,
+        },
+        Some(1),
+    )
+    .await?;
+    assert_eq!(None, meet_up.capacity);
+    for _ in 0..2 {
+        let user = create_random_user(&gateway).await?;
+        assert_eq!(
+            RegistrationStatus::Attending,
+            register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?
+        );
+    }
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn unregister_meet_up_goers_promotes_waitlist() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up =
+        create_scheduled_meet_up(&gateway, Location::OnSite("location".into()), Some(1)).await?;
+    let mut users = vec![];
+    for _ in 0..3 {
+        let user = create_random_user(&gateway).await?;
+        register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+        users.push(user);
+    }
+    assert_eq!(
+        vec![users[1].id],
+        unregister_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &users[0].id).await?
+    );
+    assert!(
+        !gateway
+            .is_user_registered_to_meet_up(&users[0].id, &meet_up.id)
+            .await?
+    );
+    assert_eq!(
+        Some(RegistrationStatus::Attending),
+        gateway
+            .get_registration_status(&users[1].id, &meet_up.id)
+            .await?
+    );
+    assert_eq!(
+        Some(RegistrationStatus::Waitlisted(1)),
+        gateway
+            .get_registration_status(&users[2].id, &meet_up.id)
+            .await?
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn get_meet_up_registration_after_unregister() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up =
+        create_scheduled_meet_up(&gateway, Location::OnSite("location".into()), Some(2)).await?;
+    let mut users = vec![];
+    for _ in 0..3 {
+        let user = create_random_user(&gateway).await?;
+        register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+        users.push(user);
+    }
+
+    // The waitlisted user takes the seat, which keeps the meet up full.
+    unregister_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &users[0].id).await?;
+    assert_eq!(
+        MeetUpRegistration {
+            status: None,
+            remaining_seats: Some(0),
+        },
+        get_meet_up_registration(&gateway, &meet_up, Some(&users[0].id)).await?
+    );
+
+    unregister_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &users[1].id).await?;
+    assert_eq!(
+        MeetUpRegistration {
+            status: None,
+            remaining_seats: Some(1),
+        },
+        get_meet_up_registration(&gateway, &meet_up, Some(&users[1].id)).await?
+    );
+    assert_eq!(
+        MeetUpRegistration {
+            status: Some(RegistrationStatus::Attending),
+            remaining_seats: Some(1),
+        },
+        get_meet_up_registration(&gateway, &meet_up, Some(&users[2].id)).await?
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn unregister_waitlisted_meet_up_goers_keeps_attendees() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up =
+        create_scheduled_meet_up(&gateway, Location::OnSite("location".into()), Some(1)).await?;
+    let mut users = vec![];
+    for _ in 0..3 {
+        let user = create_random_user(&gateway).await?;
+        register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+        users.push(user);
+    }
+    assert!(
+        unregister_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &users[1].id)
+            .await?
+            .is_empty()
+    );
+    assert_eq!(
+        1,
+        gateway
+            .get_number_attendees_from_meet_up(&meet_up.id)
+            .await?
+    );
+    assert_eq!(
+        Some(RegistrationStatus::Waitlisted(1)),
+        gateway
+            .get_registration_status(&users[2].id, &meet_up.id)
+            .await?
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn unregister_meet_up_goers_not_registered() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up =
+        create_scheduled_meet_up(&gateway, Location::OnSite("location".into()), None).await?;
+    let user = create_random_user(&gateway).await?;
+    unregister_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+    assert_eq!(
+        None,
+        gateway
+            .get_registration_status(&user.id, &meet_up.id)
+            .await?
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn unregister_meet_up_goers_invalid_state() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::Voting,
+    )
+    .await?;
+    let err = unregister_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id)
+        .await
+        .expect_err("Should error out");
+    assert_eq!("Invalid meet up state: Voting", err.to_string());
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn list_attendees_with_waitlist_last() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up =
+        create_scheduled_meet_up(&gateway, Location::OnSite("location".into()), Some(2)).await?;
+    let mut users = vec![];
+    for _ in 0..3 {
+        let user = create_random_user(&gateway).await?;
+        register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+        users.push(user);
+    }
+    let response = list_attendees(&gateway, &gateway, &meet_up.id).await?;
+    assert_eq!(
+        users
+            .iter()
+            .map(|user| (user.id, user.nickname.clone(), user.email.clone()))
+            .collect::<Vec<_>>(),
+        response
+            .attendees
+            .iter()
+            .map(|attendee| (
+                attendee.user_id,
+                attendee.nickname.clone(),
+                attendee.email.clone()
+            ))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![false, false, true],
+        response
+            .attendees
+            .iter()
+            .map(|attendee| attendee.waitlisted)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(0, response.checked_in());
+    assert_eq!(Some(1.0), response.no_show_rate());
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn list_attendees_without_attendees() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up =
+        create_scheduled_meet_up(&gateway, Location::OnSite("location".into()), None).await?;
+    let response = list_attendees(&gateway, &gateway, &meet_up.id).await?;
+    assert!(response.attendees.is_empty());
+    assert_eq!(None, response.no_show_rate());
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn check_in_meet_up_goers() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up =
+        create_scheduled_meet_up(&gateway, Location::OnSite("location".into()), None).await?;
+    let mut users = vec![];
+    for _ in 0..4 {
+        let user = create_random_user(&gateway).await?;
+        register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+        users.push(user);
+    }
+    for user in &users[..3] {
+        check_in_event_goer(
+            &gateway,
+            &gateway,
+            &gateway,
+            &Ulid::new(),
+            &meet_up.id,
+            &user.id,
+            true,
+        )
+        .await?;
+    }
+    check_in_event_goer(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &users[2].id,
+        false,
+    )
+    .await?;
+    let response = list_attendees(&gateway, &gateway, &meet_up.id).await?;
+    assert_eq!(
+        vec![true, true, false, false],
+        response
+            .attendees
+            .iter()
+            .map(|attendee| attendee.checked_in_at.is_some())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(2, response.checked_in());
+    assert_eq!(Some(0.5), response.no_show_rate());
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn check_in_meet_up_goers_not_registered() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up =
+        create_scheduled_meet_up(&gateway, Location::OnSite("location".into()), None).await?;
+    let user = create_random_user(&gateway).await?;
+    let err = check_in_event_goer(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &user.id,
+        true,
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!(
+        format!(
+            "User `{}` is not registered to meet up `{}`",
+            user.id, meet_up.id
+        ),
+        err.to_string()
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+pub async fn check_in_meet_up_goers_invalid_state() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::Voting,
+    )
+    .await?;
+    let err = check_in_event_goer(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &user.id,
+        true,
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!("Invalid meet up state: Voting", err.to_string());
+    Ok(())
+}
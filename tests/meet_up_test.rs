@@ -3,36 +3,113 @@ use chrono::{DateTime, Utc};
 use ulid::Ulid;
 
 use domain::{
-    create_new_meet_up, get_future_meet_up, get_meet_up, get_meet_up_metadata,
-    move_future_meet_up_to_done, move_future_meet_up_to_scheduled, move_future_meet_up_to_voting,
-    GetPastMeetUpError, Location, MeetUpState, Paper, PaperGateway, Vote, VoteGateway,
+    cancel_meet_up, create_new_meet_up, edit_paper, get_meet_up, get_meet_up_metadata,
+    get_meet_up_revision, list_calendar_meet_ups, list_future_meet_ups, move_meet_up_to_done,
+    move_meet_up_to_scheduled, move_meet_up_to_voting, show_call_for_papers, submit_paper,
+    update_meet_up, update_meet_up_talk, withdraw_paper, GetPastMeetUpError, Location,
+    MeetUpGateway, MeetUpSettings, MeetUpState, Paper, PaperGateway, Talk, Vote, VoteGateway,
+    VotingMethod,
 };
 use shared::utc_now;
 use tests::{
-    assert_meet_up_state, build_gateway, build_paper_with_user, create_meet_up, create_random_user,
+    assert_meet_up_state, build_gateway, build_paper_with_user, build_talks, create_meet_up,
+    create_random_user,
 };
 
 #[::tokio::test]
-async fn get_future_meet_up_without_meetup() -> Result<()> {
+async fn list_future_meet_ups_without_meetup() -> Result<()> {
     let gateway = build_gateway().await?;
-    let meet_up = get_future_meet_up(&gateway).await?;
-    assert_eq!(None, meet_up);
+    let meet_ups = list_future_meet_ups(&gateway).await?;
+    assert!(meet_ups.is_empty());
     Ok(())
 }
 
 #[::tokio::test]
-async fn create_and_get_future_meet_up() -> Result<()> {
+async fn list_calendar_meet_ups_with_past_and_future_meet_ups() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let now = utc_now();
+    let location = Location::OnSite("location".into());
+    let past_date = now - chrono::Duration::days(30);
+    let past_meet_up = create_meet_up(
+        &gateway,
+        location.clone(),
+        past_date,
+        MeetUpState::Done {
+            talks: build_talks(build_paper_with_user(user.id), past_date),
+            link: "https://example.com".parse()?,
+        },
+    )
+    .await?;
+    let cancelled_meet_up = create_meet_up(
+        &gateway,
+        location.clone(),
+        now + chrono::Duration::days(30),
+        MeetUpState::Cancelled,
+    )
+    .await?;
+    let future_meet_up = create_meet_up(
+        &gateway,
+        location,
+        now + chrono::Duration::days(10),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    assert_eq!(
+        vec![past_meet_up, future_meet_up, cancelled_meet_up],
+        list_calendar_meet_ups(&gateway).await?
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn meet_up_end_with_and_without_lineup() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date: DateTime<Utc> = "2024-12-12T19:00:00Z".parse()?;
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    assert!(meet_up.talks().is_empty());
+    assert_eq!(date + chrono::Duration::minutes(45), meet_up.end());
+
+    let mut talks = build_talks(build_paper_with_user(user.id), date);
+    talks[0].duration_minutes = 90;
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Scheduled(talks.clone()),
+    )
+    .await?;
+    assert_eq!(talks, meet_up.talks());
+    assert_eq!(date + chrono::Duration::minutes(90), meet_up.end());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn create_and_list_future_meet_ups() -> Result<()> {
     let gateway = build_gateway().await?;
     let created_meet_up = create_new_meet_up(
         &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
         Location::OnSite("location".into()),
         "2024-12-12T12:35:43Z".parse()?,
+        VotingMethod::Harmonic,
+        1,
+        None,
+        MeetUpSettings::default(),
     )
     .await?;
-    let meet_up = get_future_meet_up(&gateway)
-        .await?
-        .expect("meetup not found");
-    assert_eq!(created_meet_up, meet_up);
+    let meet_ups = list_future_meet_ups(&gateway).await?;
+    assert_eq!(vec![created_meet_up.clone()], meet_ups);
+    let meet_up = meet_ups.into_iter().next().expect("meetup not found");
     assert_eq!(Location::OnSite("location".into()), meet_up.location);
     assert_eq!(
         "2024-12-12T12:35:43Z".parse::<DateTime<Utc>>()?,
@@ -42,16 +119,59 @@ async fn create_and_get_future_meet_up() -> Result<()> {
     Ok(())
 }
 
+#[::tokio::test]
+async fn list_future_meet_ups_with_multiple_meet_ups() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let workshop = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        "2024-12-20T00:00:00Z".parse()?,
+        MeetUpState::Voting,
+    )
+    .await?;
+    let talk_night = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        "2024-12-10T00:00:00Z".parse()?,
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    let _ = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        "2024-12-01T00:00:00Z".parse()?,
+        MeetUpState::Done {
+            talks: build_talks(
+                build_paper_with_user(user.id),
+                "2024-12-01T00:00:00Z".parse()?,
+            ),
+            link: "https://example.com".parse()?,
+        },
+    )
+    .await?;
+    let meet_ups = list_future_meet_ups(&gateway).await?;
+    assert_eq!(vec![talk_night, workshop], meet_ups);
+    Ok(())
+}
+
 #[::tokio::test]
 async fn create_and_get_meet_up() -> Result<()> {
     let gateway = build_gateway().await?;
     let created_meet_up = create_new_meet_up(
         &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
         Location::Online {
             video_conference_link: "https://example.com".parse()?,
             calendar_link: "https://example.com".parse()?,
         },
         "2024-12-12T17:30:00Z".parse()?,
+        VotingMethod::Schulze,
+        2,
+        None,
+        MeetUpSettings::default(),
     )
     .await?;
     let meet_up = get_meet_up(&gateway, created_meet_up.id).await?;
@@ -68,6 +188,8 @@ async fn create_and_get_meet_up() -> Result<()> {
         meet_up.date
     );
     assert_eq!(MeetUpState::CallForPapers, meet_up.state);
+    assert_eq!(VotingMethod::Schulze, meet_up.voting_method);
+    assert_eq!(2, meet_up.number_of_talks);
     Ok(())
 }
 
@@ -115,14 +237,18 @@ async fn get_meet_up_metadata_should_return_only_when_done() -> Result<()> {
         &gateway,
         Location::OnSite("location".into()),
         "2024-12-14T00:00:00Z".parse()?,
-        MeetUpState::Scheduled(Paper {
-            id: Ulid::new(),
-            email: "test@email.com".into(),
-            user_id: user.id,
-            title: "Some title 1".into(),
-            description: "Some description".into(),
-            speaker: "Some speaker".into(),
-        }),
+        MeetUpState::Scheduled(build_talks(
+            Paper {
+                id: Ulid::new(),
+                email: "test@email.com".into(),
+                user_id: user.id,
+                title: "Some title 1".into(),
+                description: "Some description".into(),
+                speaker: "Some speaker".into(),
+                keep_in_pool: false,
+            },
+            "2024-12-14T00:00:00Z".parse()?,
+        )),
     )
     .await?;
     let meet_up_done = create_meet_up(
@@ -130,14 +256,18 @@ async fn get_meet_up_metadata_should_return_only_when_done() -> Result<()> {
         Location::OnSite("location".into()),
         "2024-12-15T00:00:00Z".parse()?,
         MeetUpState::Done {
-            paper: Paper {
-                id: Ulid::new(),
-                email: "test@email.com".into(),
-                user_id: user.id,
-                title: "Some title 2".into(),
-                description: "Some description".into(),
-                speaker: "Some speaker".into(),
-            },
+            talks: build_talks(
+                Paper {
+                    id: Ulid::new(),
+                    email: "test@email.com".into(),
+                    user_id: user.id,
+                    title: "Some title 2".into(),
+                    description: "Some description".into(),
+                    speaker: "Some speaker".into(),
+                    keep_in_pool: false,
+                },
+                "2024-12-15T00:00:00Z".parse()?,
+            ),
             link: "https://example.com".parse()?,
         },
     )
@@ -168,12 +298,13 @@ async fn get_meet_up_metadata_should_return_only_when_done() -> Result<()> {
 }
 
 #[::tokio::test]
-async fn move_meet_up_to_voting_without_future_meet_up() -> Result<()> {
+async fn move_meet_up_to_voting_not_found() -> Result<()> {
     let gateway = build_gateway().await?;
-    let err = move_future_meet_up_to_voting(&gateway)
+    let id = Ulid::new();
+    let err = move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &id)
         .await
         .expect_err("Should error out");
-    assert_eq!("No future meetups found", err.to_string());
+    assert_eq!(format!("Meet up with id `{id}` not found"), err.to_string());
     Ok(())
 }
 
@@ -187,7 +318,7 @@ async fn move_meet_up_to_voting_with_invalid_meet_up_state() -> Result<()> {
         MeetUpState::Voting,
     )
     .await?;
-    let err = move_future_meet_up_to_voting(&gateway)
+    let err = move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id)
         .await
         .expect_err("Should error out");
     assert_eq!("Invalid meet up state: Voting", err.to_string());
@@ -196,7 +327,7 @@ async fn move_meet_up_to_voting_with_invalid_meet_up_state() -> Result<()> {
 }
 
 #[::tokio::test]
-async fn move_meet_up_to_voting() -> Result<()> {
+async fn move_meet_up_to_voting_from_call_for_papers() -> Result<()> {
     let gateway = build_gateway().await?;
     let mut created_meet_up = create_meet_up(
         &gateway,
@@ -205,19 +336,44 @@ async fn move_meet_up_to_voting() -> Result<()> {
         MeetUpState::CallForPapers,
     )
     .await?;
-    let meet_up = move_future_meet_up_to_voting(&gateway).await?;
+    let meet_up =
+        move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &created_meet_up.id).await?;
     created_meet_up.state = MeetUpState::Voting;
     assert_eq!(created_meet_up, meet_up);
     Ok(())
 }
 
 #[::tokio::test]
-async fn move_meet_up_to_scheduled_without_future_meet_up() -> Result<()> {
+async fn move_meet_up_to_voting_only_changes_given_meet_up() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up_1 = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    let meet_up_2 = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up_2.id).await?;
+    assert_meet_up_state!(gateway, meet_up_1.id, MeetUpState::CallForPapers);
+    assert_meet_up_state!(gateway, meet_up_2.id, MeetUpState::Voting);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn move_meet_up_to_scheduled_not_found() -> Result<()> {
     let gateway = build_gateway().await?;
-    let err = move_future_meet_up_to_scheduled(&gateway, &gateway)
+    let id = Ulid::new();
+    let err = move_meet_up_to_scheduled(&gateway, &gateway, &gateway, &gateway, &Ulid::new(), &id)
         .await
         .expect_err("Should error out");
-    assert_eq!("No future meetups found", err.to_string());
+    assert_eq!(format!("Meet up with id `{id}` not found"), err.to_string());
     Ok(())
 }
 
@@ -231,9 +387,16 @@ async fn move_meet_up_to_scheduled_invalid_state() -> Result<()> {
         MeetUpState::CallForPapers,
     )
     .await?;
-    let err = move_future_meet_up_to_scheduled(&gateway, &gateway)
-        .await
-        .expect_err("Should error out");
+    let err = move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await
+    .expect_err("Should error out");
     assert_eq!("Invalid meet up state: CallForPapers", err.to_string());
     assert_meet_up_state!(gateway, meet_up.id, MeetUpState::CallForPapers);
     Ok(())
@@ -249,19 +412,26 @@ async fn move_meet_up_to_scheduled_without_papers() -> Result<()> {
         MeetUpState::Voting,
     )
     .await?;
-    let err = move_future_meet_up_to_scheduled(&gateway, &gateway)
-        .await
-        .expect_err("Should error out");
+    let err = move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await
+    .expect_err("Should error out");
     assert_eq!("No valid paper found", err.to_string());
     assert_meet_up_state!(gateway, meet_up.id, MeetUpState::Voting);
     Ok(())
 }
 
 #[::tokio::test]
-async fn move_meet_up_to_scheduled() -> Result<()> {
+async fn move_meet_up_to_scheduled_with_votes() -> Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper = build_paper_with_user(user.id.clone());
+    let paper = build_paper_with_user(user.id);
     let mut created_meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
@@ -270,29 +440,302 @@ async fn move_meet_up_to_scheduled() -> Result<()> {
     )
     .await?;
     gateway
-        .store_paper_with_meet_up(&paper, &created_meet_up.id, 100)
+        .store_paper_with_meet_up(&paper, &created_meet_up.id, 100, None)
         .await?;
     gateway
         .store_votes(vec![Vote {
-            paper_id: paper.id.clone(),
-            meet_up_id: created_meet_up.id.clone(),
+            paper_id: paper.id,
+            meet_up_id: created_meet_up.id,
             user_id: user.id,
-            vote: 1.0,
+            position: 0,
         }])
         .await?;
-    let meet_up = move_future_meet_up_to_scheduled(&gateway, &gateway).await?;
-    created_meet_up.state = MeetUpState::Scheduled(paper);
+    let meet_up = move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &created_meet_up.id,
+    )
+    .await?;
+    created_meet_up.state = MeetUpState::Scheduled(build_talks(paper, created_meet_up.date));
     assert_eq!(created_meet_up, meet_up);
     Ok(())
 }
 
 #[::tokio::test]
-async fn move_meet_up_to_done_future_meet_up() -> Result<()> {
+async fn move_meet_up_to_scheduled_picks_top_papers() -> Result<()> {
     let gateway = build_gateway().await?;
-    let err = move_future_meet_up_to_done(&gateway, "https://example.com".parse()?)
-        .await
-        .expect_err("Should error out");
-    assert_eq!("No future meetups found", err.to_string());
+    let user = create_random_user(&gateway).await?;
+    let date: DateTime<Utc> = "2024-12-12T22:00:00Z".parse()?;
+    let meet_up = create_new_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        Location::OnSite("location".into()),
+        date,
+        VotingMethod::Harmonic,
+        2,
+        None,
+        MeetUpSettings::default(),
+    )
+    .await?;
+    gateway.update_meet_up_to_voting(&meet_up.id).await?;
+    let papers = [
+        build_paper_with_user(user.id),
+        build_paper_with_user(user.id),
+        build_paper_with_user(user.id),
+    ];
+    for paper in &papers {
+        gateway
+            .store_paper_with_meet_up(paper, &meet_up.id, 100, None)
+            .await?;
+    }
+    gateway
+        .store_votes(
+            [&papers[2], &papers[0], &papers[1]]
+                .into_iter()
+                .enumerate()
+                .map(|(position, paper)| Vote {
+                    paper_id: paper.id,
+                    meet_up_id: meet_up.id,
+                    user_id: user.id,
+                    position: position as u32,
+                })
+                .collect(),
+        )
+        .await?;
+    let meet_up = move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await?;
+    let MeetUpState::Scheduled(talks) = &meet_up.state else {
+        panic!("Meet up should be scheduled");
+    };
+    assert_eq!(
+        vec![papers[2].id, papers[0].id],
+        talks.iter().map(|talk| talk.paper.id).collect::<Vec<_>>()
+    );
+    assert_eq!(date, talks[0].start);
+    assert_eq!(talks[0].end(), talks[1].start);
+    assert_eq!(meet_up, get_meet_up(&gateway, meet_up.id).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn update_meet_up_talk_changes_slot() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date: DateTime<Utc> = "2024-12-12T22:00:00Z".parse()?;
+    let paper = build_paper_with_user(user.id);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Scheduled(build_talks(paper.clone(), date)),
+    )
+    .await?;
+    let start: DateTime<Utc> = "2024-12-12T22:30:00Z".parse()?;
+    let updated_meet_up = update_meet_up_talk(
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &paper.id,
+        start,
+        20,
+    )
+    .await?;
+    assert_eq!(
+        MeetUpState::Scheduled(vec![Talk {
+            paper,
+            start,
+            duration_minutes: 20,
+        }]),
+        updated_meet_up.state
+    );
+    assert_eq!(updated_meet_up, get_meet_up(&gateway, meet_up.id).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn meet_up_revision_grows_with_every_change() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date = utc_now() + chrono::Duration::days(10);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    let created = get_meet_up_revision(&gateway, &meet_up.id).await?;
+    assert_eq!(0, created.sequence);
+
+    update_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        Location::OnSite("other location".into()),
+        date,
+        None,
+    )
+    .await?;
+    let updated = get_meet_up_revision(&gateway, &meet_up.id).await?;
+    assert_eq!(1, updated.sequence);
+    assert!(updated.last_modified >= created.last_modified);
+
+    move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+    assert_eq!(
+        2,
+        get_meet_up_revision(&gateway, &meet_up.id).await?.sequence
+    );
+
+    let paper = build_paper_with_user(user.id);
+    let scheduled_meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Scheduled(build_talks(paper.clone(), date)),
+    )
+    .await?;
+    let scheduled = get_meet_up_revision(&gateway, &scheduled_meet_up.id).await?;
+    update_meet_up_talk(
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &scheduled_meet_up.id,
+        &paper.id,
+        date + chrono::Duration::minutes(30),
+        20,
+    )
+    .await?;
+    assert_eq!(
+        scheduled.sequence + 1,
+        get_meet_up_revision(&gateway, &scheduled_meet_up.id)
+            .await?
+            .sequence
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn update_meet_up_talk_not_in_lineup() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::Scheduled(build_talks(build_paper_with_user(user.id), utc_now())),
+    )
+    .await?;
+    let paper_id = Ulid::new();
+    let err = update_meet_up_talk(
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &paper_id,
+        utc_now(),
+        20,
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!(
+        format!("Paper `{paper_id}` is not in the lineup"),
+        err.to_string()
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn update_meet_up_talk_without_duration() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let paper = build_paper_with_user(user.id);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::Scheduled(build_talks(paper.clone(), utc_now())),
+    )
+    .await?;
+    let err = update_meet_up_talk(
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &paper.id,
+        utc_now(),
+        0,
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!("Talk duration must be positive", err.to_string());
+    assert_eq!(meet_up, get_meet_up(&gateway, meet_up.id).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn get_meet_up_metadata_joins_lineup_titles() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date: DateTime<Utc> = "2024-12-12T22:00:00Z".parse()?;
+    let mut first = build_paper_with_user(user.id);
+    first.title = "First talk".into();
+    let mut second = build_paper_with_user(user.id);
+    second.title = "Second talk".into();
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Done {
+            talks: vec![
+                Talk {
+                    paper: second,
+                    start: date + chrono::Duration::minutes(45),
+                    duration_minutes: 15,
+                },
+                Talk {
+                    paper: first,
+                    start: date,
+                    duration_minutes: 45,
+                },
+            ],
+            link: "https://example.com".parse()?,
+        },
+    )
+    .await?;
+    let metadata = get_meet_up_metadata(&gateway, meet_up.id).await?;
+    assert_eq!("First talk / Second talk", metadata.title);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn move_meet_up_to_done_not_found() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let id = Ulid::new();
+    let err = move_meet_up_to_done(
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &id,
+        "https://example.com".parse()?,
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!(format!("Meet up with id `{id}` not found"), err.to_string());
     Ok(())
 }
 
@@ -306,31 +749,444 @@ async fn move_meet_up_to_done_invalid_state() -> Result<()> {
         MeetUpState::CallForPapers,
     )
     .await?;
-    let err = move_future_meet_up_to_done(&gateway, "https://example.com".parse()?)
-        .await
-        .expect_err("Should error out");
+    let err = move_meet_up_to_done(
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        "https://example.com".parse()?,
+    )
+    .await
+    .expect_err("Should error out");
     assert_eq!("Invalid meet up state: CallForPapers", err.to_string());
     assert_meet_up_state!(gateway, meet_up.id, MeetUpState::CallForPapers);
     Ok(())
 }
 
 #[::tokio::test]
-async fn move_meet_up_to_done() -> Result<()> {
+async fn move_meet_up_to_done_from_scheduled() -> Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper = build_paper_with_user(user.id.clone());
+    let date = utc_now();
+    let talks = build_talks(build_paper_with_user(user.id), date);
     let mut meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
-        utc_now(),
-        MeetUpState::Scheduled(paper.clone()),
+        date,
+        MeetUpState::Scheduled(talks.clone()),
+    )
+    .await?;
+    move_meet_up_to_done(
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        "https://example.com".parse()?,
     )
     .await?;
-    move_future_meet_up_to_done(&gateway, "https://example.com".parse()?).await?;
     meet_up.state = MeetUpState::Done {
-        paper,
+        talks,
         link: "https://example.com".parse()?,
     };
     assert_eq!(meet_up, get_meet_up(&gateway, meet_up.id).await?);
     Ok(())
 }
+
+#[::tokio::test]
+async fn update_meet_up_not_found() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let id = Ulid::new();
+    let err = update_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &id,
+        Location::OnSite("location".into()),
+        utc_now(),
+        None,
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!(format!("Meet up with id `{id}` not found"), err.to_string());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn update_meet_up_invalid_state() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::Done {
+            talks: build_talks(build_paper_with_user(user.id), utc_now()),
+            link: "https://example.com".parse()?,
+        },
+    )
+    .await?;
+    let err = update_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        Location::OnSite("other location".into()),
+        utc_now(),
+        None,
+    )
+    .await
+    .expect_err("Should error out");
+    assert!(err.to_string().starts_with("Invalid meet up state: Done"));
+    assert_eq!(meet_up, get_meet_up(&gateway, meet_up.id).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn update_meet_up_location_and_date() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let mut meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        "2024-12-12T00:00:00Z".parse()?,
+        MeetUpState::Voting,
+    )
+    .await?;
+    let (updated_meet_up, _) = update_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        Location::Online {
+            video_conference_link: "https://example.com".parse()?,
+            calendar_link: "https://example.com".parse()?,
+        },
+        "2024-12-19T00:00:00Z".parse()?,
+        None,
+    )
+    .await?;
+    meet_up.location = Location::Online {
+        video_conference_link: "https://example.com".parse()?,
+        calendar_link: "https://example.com".parse()?,
+    };
+    meet_up.date = "2024-12-19T00:00:00Z".parse()?;
+    assert_eq!(meet_up, updated_meet_up);
+    assert_eq!(meet_up, get_meet_up(&gateway, meet_up.id).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn cancel_meet_up_not_found() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let id = Ulid::new();
+    let err = cancel_meet_up(&gateway, &gateway, &Ulid::new(), &id)
+        .await
+        .expect_err("Should error out");
+    assert_eq!(format!("Meet up with id `{id}` not found"), err.to_string());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn cancel_meet_up_already_cancelled() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::Cancelled,
+    )
+    .await?;
+    let err = cancel_meet_up(&gateway, &gateway, &Ulid::new(), &meet_up.id)
+        .await
+        .expect_err("Should error out");
+    assert_eq!("Invalid meet up state: Cancelled", err.to_string());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn cancel_meet_up_from_scheduled() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let mut meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::Scheduled(build_talks(build_paper_with_user(user.id), utc_now())),
+    )
+    .await?;
+    let cancelled_meet_up = cancel_meet_up(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+    meet_up.state = MeetUpState::Cancelled;
+    assert_eq!(meet_up, cancelled_meet_up);
+    assert_meet_up_state!(gateway, meet_up.id, MeetUpState::Cancelled);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn list_future_meet_ups_shows_only_upcoming_cancelled_meet_ups() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let _ = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now() - chrono::Duration::days(1),
+        MeetUpState::Cancelled,
+    )
+    .await?;
+    let upcoming_cancelled_meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now() + chrono::Duration::days(1),
+        MeetUpState::Cancelled,
+    )
+    .await?;
+    let meet_ups = list_future_meet_ups(&gateway).await?;
+    assert_eq!(vec![upcoming_cancelled_meet_up], meet_ups);
+    Ok(())
+}
+
+fn build_pooled_paper_with_user(user_id: Ulid) -> Paper {
+    Paper {
+        keep_in_pool: true,
+        ..build_paper_with_user(user_id)
+    }
+}
+
+#[::tokio::test]
+async fn create_new_meet_up_carries_over_pooled_papers() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date: DateTime<Utc> = "2024-12-12T22:00:00Z".parse()?;
+    let selected = build_pooled_paper_with_user(user.id);
+    let pooled = build_pooled_paper_with_user(user.id);
+    let not_pooled = build_paper_with_user(user.id);
+    let previous_meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Done {
+            talks: build_talks(selected, date),
+            link: "https://example.com".parse()?,
+        },
+    )
+    .await?;
+    for paper in [&pooled, &not_pooled] {
+        gateway
+            .store_paper_with_meet_up(paper, &previous_meet_up.id, 100, None)
+            .await?;
+    }
+
+    let meet_up = create_new_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        Location::OnSite("location".into()),
+        date + chrono::Duration::days(30),
+        VotingMethod::Harmonic,
+        1,
+        None,
+        MeetUpSettings::default(),
+    )
+    .await?;
+    assert_eq!(
+        vec![pooled.clone()],
+        gateway.get_papers_from_meet_up(&meet_up.id).await?
+    );
+    let (_, papers, carried_over_papers, over_the_limit) =
+        show_call_for_papers(&gateway, &gateway, &meet_up.id, &user).await?;
+    assert!(papers.is_empty());
+    assert_eq!(vec![pooled], carried_over_papers);
+    assert!(!over_the_limit);
+
+    // Carried over papers do not count against the limit of papers per user.
+    for _ in 0..2 {
+        submit_paper(
+            &gateway,
+            &gateway,
+            &gateway,
+            &meet_up.id,
+            build_paper_with_user(user.id),
+        )
+        .await?;
+    }
+    let (_, papers, _, over_the_limit) =
+        show_call_for_papers(&gateway, &gateway, &meet_up.id, &user).await?;
+    assert_eq!(2, papers.len());
+    assert!(over_the_limit);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn carried_over_papers_can_be_edited_and_withdrawn() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date: DateTime<Utc> = "2024-12-12T22:00:00Z".parse()?;
+    let pooled = build_pooled_paper_with_user(user.id);
+    let previous_meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Done {
+            talks: build_talks(build_paper_with_user(user.id), date),
+            link: "https://example.com".parse()?,
+        },
+    )
+    .await?;
+    gateway
+        .store_paper_with_meet_up(&pooled, &previous_meet_up.id, 100, None)
+        .await?;
+    let meet_up = create_new_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        Location::OnSite("location".into()),
+        date + chrono::Duration::days(30),
+        VotingMethod::Harmonic,
+        1,
+        None,
+        MeetUpSettings::default(),
+    )
+    .await?;
+
+    let edited = edit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user,
+        Paper {
+            title: "New title".into(),
+            keep_in_pool: false,
+            ..pooled.clone()
+        },
+    )
+    .await?;
+    assert_eq!("New title", edited.title);
+    assert!(!edited.keep_in_pool);
+    assert_ne!(pooled.id, edited.id);
+    let (_, _, carried_over_papers, _) =
+        show_call_for_papers(&gateway, &gateway, &meet_up.id, &user).await?;
+    assert_eq!(vec![edited.clone()], carried_over_papers);
+    // The edit is a copy, the meet up the paper came from keeps it as it was submitted.
+    assert_eq!(pooled, gateway.get_paper(&pooled.id).await?);
+
+    withdraw_paper(&gateway, &gateway, &gateway, &meet_up.id, &user, &edited.id).await?;
+    let (_, _, carried_over_papers, _) =
+        show_call_for_papers(&gateway, &gateway, &meet_up.id, &user).await?;
+    assert!(carried_over_papers.is_empty());
+    assert!(gateway
+        .get_papers_from_meet_up(&meet_up.id)
+        .await?
+        .is_empty());
+    assert!(gateway.get_paper(&edited.id).await.is_err());
+    // The paper is still part of the meet up it was submitted to.
+    assert!(gateway
+        .get_papers_from_meet_up(&previous_meet_up.id)
+        .await?
+        .contains(&pooled));
+    Ok(())
+}
+
+#[::tokio::test]
+async fn create_new_meet_up_carries_over_papers_to_one_open_meet_up() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date: DateTime<Utc> = "2024-12-12T22:00:00Z".parse()?;
+    let pooled = build_pooled_paper_with_user(user.id);
+    let previous_meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Done {
+            talks: build_talks(build_paper_with_user(user.id), date),
+            link: "https://example.com".parse()?,
+        },
+    )
+    .await?;
+    gateway
+        .store_paper_with_meet_up(&pooled, &previous_meet_up.id, 100, None)
+        .await?;
+
+    let mut meet_ups = vec![];
+    for days in [30, 60] {
+        meet_ups.push(
+            create_new_meet_up(
+                &gateway,
+                &gateway,
+                &gateway,
+                &Ulid::new(),
+                Location::OnSite("location".into()),
+                utc_now() + chrono::Duration::days(days),
+                VotingMethod::Harmonic,
+                1,
+                None,
+                MeetUpSettings::default(),
+            )
+            .await?,
+        );
+    }
+
+    assert_eq!(
+        vec![pooled],
+        gateway.get_papers_from_meet_up(&meet_ups[0].id).await?
+    );
+    assert!(gateway
+        .get_papers_from_meet_up(&meet_ups[1].id)
+        .await?
+        .is_empty());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn create_new_meet_up_carries_over_from_latest_lineup() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let date: DateTime<Utc> = utc_now() - chrono::Duration::days(60);
+    let done_meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Done {
+            talks: build_talks(build_paper_with_user(user.id), date),
+            link: "https://example.com".parse()?,
+        },
+    )
+    .await?;
+    let scheduled_date = utc_now() + chrono::Duration::days(1);
+    let scheduled_meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        scheduled_date,
+        MeetUpState::Scheduled(build_talks(build_paper_with_user(user.id), scheduled_date)),
+    )
+    .await?;
+    let old_paper = build_pooled_paper_with_user(user.id);
+    gateway
+        .store_paper_with_meet_up(&old_paper, &done_meet_up.id, 100, None)
+        .await?;
+    let paper = build_pooled_paper_with_user(user.id);
+    gateway
+        .store_paper_with_meet_up(&paper, &scheduled_meet_up.id, 100, None)
+        .await?;
+
+    let meet_up = create_new_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        Location::OnSite("location".into()),
+        scheduled_date + chrono::Duration::days(30),
+        VotingMethod::Harmonic,
+        1,
+        None,
+        MeetUpSettings::default(),
+    )
+    .await?;
+    assert_eq!(
+        vec![paper],
+        gateway.get_papers_from_meet_up(&meet_up.id).await?
+    );
+    Ok(())
+}
@@ -0,0 +1,405 @@
+use domain::{
+    move_meet_up_to_scheduled, move_meet_up_to_voting, notify_lineup, notify_paper_received,
+    notify_registration_confirmed, notify_voting_opened, register_event_goer,
+    send_meet_up_reminders, store_votes, submit_paper, update_meet_up, Location, MailGateway,
+    MeetUpGateway, MeetUpSettings, MeetUpState, Notification, UserGateway,
+};
+use gateway::mail::LettreMailGateway;
+use shared::{test::set_now, utc_now};
+use tests::{
+    build_gateway, build_paper_with_user, build_talks, create_meet_up,
+    create_meet_up_with_settings, create_random_user, MailGatewayMock,
+};
+use ulid::Ulid;
+
+#[::tokio::test]
+async fn mail_notifications_are_enabled_by_default() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let unsubscribed_user = create_random_user(&gateway).await?;
+    gateway
+        .set_mail_notifications(&unsubscribed_user.id, false)
+        .await?;
+    assert!(gateway.has_mail_notifications(&user.id).await?);
+    assert!(
+        !gateway
+            .has_mail_notifications(&unsubscribed_user.id)
+            .await?
+    );
+    assert_eq!(
+        vec![user.id],
+        gateway
+            .list_users_with_mail_notifications()
+            .await?
+            .into_iter()
+            .map(|user| user.id)
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn notify_paper_received_to_account_email() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let mail_gateway = MailGatewayMock::default();
+    let user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up_with_settings(&gateway, MeetUpSettings::default()).await?;
+    let mut paper = build_paper_with_user(user.id);
+    paper.email = "speaker@example.com".into();
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    notify_paper_received(&mail_gateway, &gateway, &gateway, &meet_up.id, &paper).await?;
+    assert_eq!(
+        vec![(
+            user.email.clone(),
+            Notification::PaperReceived { meet_up, paper }
+        )],
+        mail_gateway.sent().await
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn notify_paper_received_to_unsubscribed_user() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let mail_gateway = MailGatewayMock::default();
+    let user = create_random_user(&gateway).await?;
+    gateway.set_mail_notifications(&user.id, false).await?;
+    let meet_up = create_meet_up_with_settings(&gateway, MeetUpSettings::default()).await?;
+    let paper = build_paper_with_user(user.id);
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    notify_paper_received(&mail_gateway, &gateway, &gateway, &meet_up.id, &paper).await?;
+    assert!(mail_gateway.sent().await.is_empty());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn notify_voting_opened_to_subscribed_users() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let mail_gateway = MailGatewayMock::default();
+    let user = create_random_user(&gateway).await?;
+    let unsubscribed_user = create_random_user(&gateway).await?;
+    gateway
+        .set_mail_notifications(&unsubscribed_user.id, false)
+        .await?;
+    let meet_up = create_meet_up_with_settings(&gateway, MeetUpSettings::default()).await?;
+    let meet_up = move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+    assert_eq!(
+        1,
+        notify_voting_opened(&mail_gateway, &gateway, &meet_up).await?
+    );
+    assert_eq!(
+        vec![(user.email, Notification::VotingOpened(meet_up))],
+        mail_gateway.sent().await
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn notify_voting_opened_with_failing_address() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let other_user = create_random_user(&gateway).await?;
+    let mail_gateway = MailGatewayMock::failing_for(&user.email);
+    let meet_up = create_meet_up_with_settings(&gateway, MeetUpSettings::default()).await?;
+    let meet_up = move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+    let err = notify_voting_opened(&mail_gateway, &gateway, &meet_up)
+        .await
+        .expect_err("Should error out");
+    assert_eq!(
+        format!(
+            "Could not send 1 of 2 emails: Invalid email address `{}`",
+            user.email
+        ),
+        err.to_string()
+    );
+    // The other users still get the email.
+    assert_eq!(
+        vec![(other_user.email, Notification::VotingOpened(meet_up))],
+        mail_gateway.sent().await
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn notify_lineup_to_selected_and_not_selected_papers() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let mail_gateway = MailGatewayMock::default();
+    let user = create_random_user(&gateway).await?;
+    let other_user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up_with_settings(&gateway, MeetUpSettings::default()).await?;
+    let mut selected_paper = build_paper_with_user(user.id);
+    selected_paper.email = "selected@example.com".into();
+    let mut not_selected_paper = build_paper_with_user(other_user.id);
+    not_selected_paper.email = "not_selected@example.com".into();
+    submit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        selected_paper.clone(),
+    )
+    .await?;
+    submit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        not_selected_paper.clone(),
+    )
+    .await?;
+    move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+    store_votes(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user.id,
+        vec![selected_paper.id, not_selected_paper.id],
+    )
+    .await?;
+    let meet_up = move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await?;
+    assert_eq!(
+        2,
+        notify_lineup(&mail_gateway, &gateway, &gateway, &meet_up).await?
+    );
+    let mut sent = mail_gateway.sent().await;
+    sent.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(
+        vec![
+            (
+                "not_selected@example.com".to_string(),
+                Notification::PaperNotSelected {
+                    meet_up: meet_up.clone(),
+                    paper: not_selected_paper
+                }
+            ),
+            (
+                "selected@example.com".to_string(),
+                Notification::PaperSelected {
+                    meet_up,
+                    paper: selected_paper
+                }
+            ),
+        ],
+        sent
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn notify_lineup_with_invalid_meet_up_state() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let mail_gateway = MailGatewayMock::default();
+    let meet_up = create_meet_up_with_settings(&gateway, MeetUpSettings::default()).await?;
+    let err = notify_lineup(&mail_gateway, &gateway, &gateway, &meet_up)
+        .await
+        .expect_err("Should error out");
+    assert_eq!("Invalid meet up state: CallForPapers", err.to_string());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn notify_registration_confirmed_to_users() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let mail_gateway = MailGatewayMock::default();
+    let user = create_random_user(&gateway).await?;
+    let unsubscribed_user = create_random_user(&gateway).await?;
+    gateway
+        .set_mail_notifications(&unsubscribed_user.id, false)
+        .await?;
+    let date = utc_now() + chrono::Duration::days(7);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Scheduled(build_talks(build_paper_with_user(user.id), date)),
+    )
+    .await?;
+    assert_eq!(
+        0,
+        notify_registration_confirmed(&mail_gateway, &gateway, &gateway, &meet_up.id, &[]).await?
+    );
+    assert_eq!(
+        1,
+        notify_registration_confirmed(
+            &mail_gateway,
+            &gateway,
+            &gateway,
+            &meet_up.id,
+            &[user.id, unsubscribed_user.id],
+        )
+        .await?
+    );
+    assert_eq!(
+        vec![(user.email, Notification::RegistrationConfirmed(meet_up))],
+        mail_gateway.sent().await
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn send_meet_up_reminders_the_day_before() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let mail_gateway = MailGatewayMock::default();
+    let user = create_random_user(&gateway).await?;
+    let waitlisted_user = create_random_user(&gateway).await?;
+    let now = utc_now();
+    let date = now + chrono::Duration::days(2);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Scheduled(build_talks(build_paper_with_user(user.id), date)),
+    )
+    .await?;
+    let (meet_up, _) = update_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        meet_up.location,
+        meet_up.date,
+        Some(1),
+    )
+    .await?;
+    register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+    register_event_goer(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &waitlisted_user.id,
+    )
+    .await?;
+
+    // Two days before the meet up.
+    assert!(
+        send_meet_up_reminders(&gateway, &gateway, &gateway, &mail_gateway)
+            .await?
+            .is_empty()
+    );
+
+    set_now(&(now + chrono::Duration::hours(36)));
+    let reminders = send_meet_up_reminders(&gateway, &gateway, &gateway, &mail_gateway).await?;
+    assert_eq!(1, reminders.len());
+    assert_eq!(meet_up.id, reminders[0].meet_up_id);
+    assert_eq!(1, *reminders[0].result.as_ref().expect("Should be sent"));
+    assert_eq!(
+        vec![(user.email, Notification::MeetUpReminder(meet_up.clone()))],
+        mail_gateway.sent().await
+    );
+
+    // Running it again, e.g. after a restart, does not send the reminder twice.
+    assert!(
+        send_meet_up_reminders(&gateway, &gateway, &gateway, &mail_gateway)
+            .await?
+            .is_empty()
+    );
+    assert_eq!(1, mail_gateway.sent().await.len());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn send_meet_up_reminders_after_new_date() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let mail_gateway = MailGatewayMock::default();
+    let user = create_random_user(&gateway).await?;
+    let date = utc_now() + chrono::Duration::hours(12);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        date,
+        MeetUpState::Scheduled(build_talks(build_paper_with_user(user.id), date)),
+    )
+    .await?;
+    register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+    assert_eq!(
+        1,
+        send_meet_up_reminders(&gateway, &gateway, &gateway, &mail_gateway)
+            .await?
+            .len()
+    );
+    update_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        meet_up.location.clone(),
+        meet_up.date,
+        None,
+    )
+    .await?;
+    assert!(
+        send_meet_up_reminders(&gateway, &gateway, &gateway, &mail_gateway)
+            .await?
+            .is_empty()
+    );
+
+    // The meet up was postponed, so the attendees are reminded of the new date.
+    update_meet_up(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        meet_up.location,
+        date + chrono::Duration::hours(6),
+        None,
+    )
+    .await?;
+    assert_eq!(
+        1,
+        send_meet_up_reminders(&gateway, &gateway, &gateway, &mail_gateway)
+            .await?
+            .len()
+    );
+    assert_eq!(2, mail_gateway.sent().await.len());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn file_drop_mail_gateway_writes_emails() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let folder = std::env::temp_dir().join(Ulid::new().to_string());
+    let mail_gateway = LettreMailGateway::file_drop(
+        &folder,
+        "RustCWB <no-reply@example.com>",
+        "https://example.com".parse()?,
+    )?;
+    let user = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up_with_settings(&gateway, MeetUpSettings::default()).await?;
+    let paper = build_paper_with_user(user.id);
+    mail_gateway
+        .send_mail(
+            "speaker@example.com",
+            &Notification::PaperReceived { meet_up, paper },
+        )
+        .await?;
+    let files = std::fs::read_dir(&folder)?.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(1, files.len());
+    let email = std::fs::read_to_string(files[0].path())?;
+    assert!(email.contains("To: speaker@example.com"));
+    assert!(email.contains("Subject: Recebemos sua proposta: title"));
+    std::fs::remove_dir_all(&folder)?;
+
+    let err = mail_gateway
+        .send_mail(
+            "email",
+            &Notification::VotingOpened(gateway.list_future_meet_ups().await?.remove(0)),
+        )
+        .await
+        .expect_err("Should error out");
+    assert_eq!("Invalid email address `email`", err.to_string());
+    Ok(())
+}
@@ -0,0 +1,258 @@
+use chrono::Duration;
+use serde_json::{json, Value};
+
+use domain::{
+    bootstrap_admin, get_user_role, login_with_oidc_code, LoginMethod, OidcError, OidcGateway,
+    UserGateway,
+};
+use gateway::oidc::OidcRestGateway;
+use shared::utc_now;
+use tests::{build_gateway, create_random_user, OidcIssuerMock};
+
+const CLIENT_ID: &str = "client_id";
+
+fn build_oidc_gateway(issuer: &OidcIssuerMock) -> OidcRestGateway {
+    OidcRestGateway::new(
+        "keycloak".into(),
+        "Keycloak".into(),
+        issuer.discovery_url(),
+        CLIENT_ID.into(),
+        "client_secret".into(),
+        &"https://rustcwb.dev".parse().unwrap(),
+    )
+}
+
+fn id_token_claims(issuer: &OidcIssuerMock, subject: &str, nonce: &str) -> Value {
+    json!({
+        "iss": issuer.issuer(),
+        "sub": subject,
+        "aud": CLIENT_ID,
+        "exp": (utc_now() + Duration::minutes(5)).timestamp(),
+        "iat": utc_now().timestamp(),
+        "nonce": nonce,
+        "email": "oidc@email.com",
+        "email_verified": true,
+        "preferred_username": "oidc_nickname",
+    })
+}
+
+#[::tokio::test]
+async fn authorize_url_has_the_state_and_nonce() -> anyhow::Result<()> {
+    let issuer = OidcIssuerMock::start().await?;
+    let oidc_gateway = build_oidc_gateway(&issuer);
+
+    let url = oidc_gateway
+        .authorize_url("some_state", "some_nonce")
+        .await?;
+
+    assert_eq!("/authorize", url.path());
+    let params = url.query_pairs().into_owned().collect::<Vec<_>>();
+    for (key, value) in [
+        ("response_type", "code"),
+        ("client_id", CLIENT_ID),
+        (
+            "redirect_uri",
+            "https://rustcwb.dev/oidc/keycloak/authorize",
+        ),
+        ("scope", "openid email profile"),
+        ("state", "some_state"),
+        ("nonce", "some_nonce"),
+    ] {
+        assert!(params.contains(&(key.into(), value.into())), "{key}");
+    }
+    Ok(())
+}
+
+#[::tokio::test]
+async fn login_with_oidc_code_creates_user_and_finds_it_again() -> anyhow::Result<()> {
+    let user_gateway = build_gateway().await?;
+    let issuer = OidcIssuerMock::start().await?;
+    let oidc_gateway = build_oidc_gateway(&issuer);
+    issuer
+        .set_id_token_claims(id_token_claims(&issuer, "subject_1", "nonce"))
+        .await;
+
+    let user = login_with_oidc_code(&user_gateway, &oidc_gateway, "code", "nonce").await?;
+
+    assert_eq!("oidc_nickname", user.nickname);
+    assert_eq!("oidc@email.com", user.email);
+    assert_eq!(
+        LoginMethod::Oidc {
+            provider: "keycloak".into(),
+            subject: "subject_1".into(),
+        },
+        user.login_method
+    );
+    let token_request = &issuer.token_requests().await[0];
+    assert_eq!("code", token_request["code"]);
+    assert_eq!("authorization_code", token_request["grant_type"]);
+    assert_eq!("client_secret", token_request["client_secret"]);
+
+    let mut claims = id_token_claims(&issuer, "subject_1", "other_nonce");
+    claims["email"] = "new@email.com".into();
+    issuer.set_id_token_claims(claims).await;
+    let logged_user =
+        login_with_oidc_code(&user_gateway, &oidc_gateway, "code", "other_nonce").await?;
+
+    assert_eq!(user.id, logged_user.id);
+    assert_eq!("new@email.com", logged_user.email);
+    assert_eq!(
+        "new@email.com",
+        user_gateway.get_user_with_id(&user.id).await?.email
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn login_with_oidc_code_does_not_match_other_users_by_email() -> anyhow::Result<()> {
+    let user_gateway = build_gateway().await?;
+    let github_user = create_random_user(&user_gateway).await?;
+    let issuer = OidcIssuerMock::start().await?;
+    let oidc_gateway = build_oidc_gateway(&issuer);
+    let mut claims = id_token_claims(&issuer, "subject_1", "nonce");
+    claims["email"] = github_user.email.clone().into();
+    issuer.set_id_token_claims(claims).await;
+
+    let user = login_with_oidc_code(&user_gateway, &oidc_gateway, "code", "nonce").await?;
+
+    assert_ne!(github_user.id, user.id);
+    assert_eq!(
+        github_user.login_method,
+        user_gateway
+            .get_user_with_id(&github_user.id)
+            .await?
+            .login_method
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn exchange_code_reads_email_from_userinfo() -> anyhow::Result<()> {
+    let issuer = OidcIssuerMock::start().await?;
+    let oidc_gateway = build_oidc_gateway(&issuer);
+    let mut claims = id_token_claims(&issuer, "subject_1", "nonce");
+    claims.as_object_mut().unwrap().remove("email");
+    claims.as_object_mut().unwrap().remove("email_verified");
+    claims.as_object_mut().unwrap().remove("preferred_username");
+    issuer.set_id_token_claims(claims).await;
+    issuer
+        .set_user_info(json!({
+            "sub": "subject_1",
+            "email": "userinfo@email.com",
+            "email_verified": true,
+        }))
+        .await;
+
+    let oidc_user = oidc_gateway.exchange_code("code", "nonce").await?;
+
+    assert_eq!("userinfo@email.com", oidc_user.email);
+    assert_eq!("userinfo", oidc_user.nickname);
+
+    issuer.set_user_info(json!({ "sub": "subject_1" })).await;
+    assert!(matches!(
+        oidc_gateway.exchange_code("code", "nonce").await,
+        Err(OidcError::MissingEmail)
+    ));
+    Ok(())
+}
+
+#[::tokio::test]
+async fn exchange_code_accepts_audience_list() -> anyhow::Result<()> {
+    let issuer = OidcIssuerMock::start().await?;
+    let oidc_gateway = build_oidc_gateway(&issuer);
+    let mut claims = id_token_claims(&issuer, "subject_1", "nonce");
+    claims["aud"] = json!(["other_client", CLIENT_ID]);
+    issuer.set_id_token_claims(claims).await;
+
+    let oidc_user = oidc_gateway.exchange_code("code", "nonce").await?;
+
+    assert_eq!("subject_1", oidc_user.subject);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn exchange_code_rejects_invalid_id_tokens() -> anyhow::Result<()> {
+    let issuer = OidcIssuerMock::start().await?;
+    let oidc_gateway = build_oidc_gateway(&issuer);
+    let claims = id_token_claims(&issuer, "subject_1", "nonce");
+    for (claim, value) in [
+        ("nonce", json!("other_nonce")),
+        ("aud", json!("other_client")),
+        ("iss", json!("https://other.issuer")),
+        ("exp", json!((utc_now() - Duration::seconds(1)).timestamp())),
+    ] {
+        let mut invalid_claims = claims.clone();
+        invalid_claims[claim] = value;
+        issuer.set_id_token_claims(invalid_claims).await;
+
+        let result = oidc_gateway.exchange_code("code", "nonce").await;
+
+        assert!(
+            matches!(result, Err(OidcError::InvalidIdToken(_))),
+            "{claim}: {result:?}"
+        );
+    }
+    Ok(())
+}
+
+#[::tokio::test]
+async fn exchange_code_rejects_unverified_emails() -> anyhow::Result<()> {
+    let issuer = OidcIssuerMock::start().await?;
+    let oidc_gateway = build_oidc_gateway(&issuer);
+    let mut claims = id_token_claims(&issuer, "subject_1", "nonce");
+    claims["email_verified"] = false.into();
+    issuer.set_id_token_claims(claims.clone()).await;
+    assert!(matches!(
+        oidc_gateway.exchange_code("code", "nonce").await,
+        Err(OidcError::UnverifiedEmail(email)) if email == "oidc@email.com"
+    ));
+
+    claims.as_object_mut().unwrap().remove("email_verified");
+    issuer.set_id_token_claims(claims.clone()).await;
+    assert!(matches!(
+        oidc_gateway.exchange_code("code", "nonce").await,
+        Err(OidcError::UnverifiedEmail(_))
+    ));
+
+    claims.as_object_mut().unwrap().remove("email");
+    claims.as_object_mut().unwrap().remove("email_verified");
+    issuer.set_id_token_claims(claims).await;
+    issuer
+        .set_user_info(json!({ "sub": "subject_1", "email": "userinfo@email.com" }))
+        .await;
+    assert!(matches!(
+        oidc_gateway.exchange_code("code", "nonce").await,
+        Err(OidcError::UnverifiedEmail(_))
+    ));
+    Ok(())
+}
+
+#[::tokio::test]
+async fn oidc_users_with_admin_emails_get_no_role() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let issuer = OidcIssuerMock::start().await?;
+    let oidc_gateway = build_oidc_gateway(&issuer);
+    let admin_emails = ["admin@rustcwb.dev".to_string()];
+    let mut claims = id_token_claims(&issuer, "subject_1", "nonce");
+    claims["email"] = "admin@rustcwb.dev".into();
+    claims["email_verified"] = false.into();
+    issuer.set_id_token_claims(claims.clone()).await;
+
+    assert!(
+        login_with_oidc_code(&gateway, &oidc_gateway, "code", "nonce")
+            .await
+            .is_err()
+    );
+    assert!(gateway
+        .list_users_with_email("admin@rustcwb.dev")
+        .await?
+        .is_empty());
+
+    // Even verified, the email is only trusted for GitHub users.
+    claims["email_verified"] = true.into();
+    issuer.set_id_token_claims(claims).await;
+    let user = login_with_oidc_code(&gateway, &oidc_gateway, "code", "nonce").await?;
+    assert!(!bootstrap_admin(&gateway, &gateway, &gateway, &user, &admin_emails).await?);
+    assert_eq!(None, get_user_role(&gateway, &user).await?);
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use std::slice;
+
+use anyhow::Result;
+
+use domain::{
+    bootstrap_admin, bootstrap_admins, get_user_role, grant_role, list_roles, revoke_role,
+    AdminBootstrap, ChangeRoleError, LoginMethod, Role, User, UserGateway,
+};
+use tests::{build_gateway, create_random_user, create_user_with_login_method};
+use ulid::Ulid;
+
+#[::tokio::test]
+async fn bootstrap_admins_from_the_listed_emails() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let admin = create_random_user(&gateway).await?;
+    let user = create_random_user(&gateway).await?;
+    let admin_emails = vec![admin.email.to_uppercase(), "new@rustcwb.org".to_string()];
+
+    assert_eq!(
+        vec![AdminBootstrap::Granted(admin.id)],
+        bootstrap_admins(&gateway, &gateway, &gateway, &admin_emails).await?
+    );
+    assert!(
+        bootstrap_admins(&gateway, &gateway, &gateway, &admin_emails)
+            .await?
+            .is_empty()
+    );
+    assert_eq!(Some(Role::Admin), get_user_role(&gateway, &admin).await?);
+    assert!(!bootstrap_admin(&gateway, &gateway, &gateway, &user, &admin_emails).await?);
+    assert_eq!(None, get_user_role(&gateway, &user).await?);
+
+    let overview = list_roles(&gateway, &gateway).await?;
+    assert_eq!(vec![(admin.clone(), Role::Admin)], overview.users);
+    assert_eq!(1, overview.changes.len());
+    let (change, changed_user, changed_by) = &overview.changes[0];
+    assert_eq!(Some(Role::Admin), change.role);
+    assert_eq!(admin, *changed_user);
+    assert_eq!(None, *changed_by);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn bootstrap_admin_ignores_the_email_case() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+
+    assert!(
+        bootstrap_admin(
+            &gateway,
+            &gateway,
+            &gateway,
+            &user,
+            &[user.email.to_uppercase()]
+        )
+        .await?
+    );
+    assert_eq!(Some(Role::Admin), get_user_role(&gateway, &user).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn admin_grants_and_revokes_roles() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let admin = create_random_user(&gateway).await?;
+    let user = create_random_user(&gateway).await?;
+    bootstrap_admin(
+        &gateway,
+        &gateway,
+        &gateway,
+        &admin,
+        slice::from_ref(&admin.email),
+    )
+    .await?;
+
+    let granted = grant_role(
+        &gateway,
+        &gateway,
+        &gateway,
+        &admin,
+        &user.id,
+        Role::Organizer,
+    )
+    .await?;
+    assert_eq!(user, granted);
+    assert_eq!(Some(Role::Organizer), get_user_role(&gateway, &user).await?);
+    grant_role(&gateway, &gateway, &gateway, &admin, &user.id, Role::Admin).await?;
+    assert_eq!(Some(Role::Admin), get_user_role(&gateway, &user).await?);
+    revoke_role(&gateway, &gateway, &admin, &user.id).await?;
+    assert_eq!(None, get_user_role(&gateway, &user).await?);
+
+    let overview = list_roles(&gateway, &gateway).await?;
+    assert_eq!(vec![(admin.clone(), Role::Admin)], overview.users);
+    let history = overview
+        .changes
+        .iter()
+        .map(|(change, changed_user, changed_by)| {
+            (
+                change.role,
+                changed_user.id,
+                changed_by.as_ref().map(|changed_by| changed_by.id),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            (None, user.id, Some(admin.id)),
+            (Some(Role::Admin), user.id, Some(admin.id)),
+            (Some(Role::Organizer), user.id, Some(admin.id)),
+            (Some(Role::Admin), admin.id, None),
+        ],
+        history
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn only_admins_change_roles() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let admin = create_random_user(&gateway).await?;
+    let organizer = create_random_user(&gateway).await?;
+    let user = create_random_user(&gateway).await?;
+    bootstrap_admin(
+        &gateway,
+        &gateway,
+        &gateway,
+        &admin,
+        slice::from_ref(&admin.email),
+    )
+    .await?;
+    grant_role(
+        &gateway,
+        &gateway,
+        &gateway,
+        &admin,
+        &organizer.id,
+        Role::Organizer,
+    )
+    .await?;
+
+    assert!(matches!(
+        grant_role(
+            &gateway,
+            &gateway,
+            &gateway,
+            &organizer,
+            &user.id,
+            Role::Organizer
+        )
+        .await,
+        Err(ChangeRoleError::NotAllowed)
+    ));
+    assert!(matches!(
+        grant_role(&gateway, &gateway, &gateway, &user, &user.id, Role::Admin).await,
+        Err(ChangeRoleError::NotAllowed)
+    ));
+    assert!(matches!(
+        revoke_role(&gateway, &gateway, &organizer, &admin.id).await,
+        Err(ChangeRoleError::NotAllowed)
+    ));
+    assert_eq!(None, get_user_role(&gateway, &user).await?);
+    assert_eq!(Some(Role::Admin), get_user_role(&gateway, &admin).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn admins_can_not_change_their_own_role() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let admin = create_random_user(&gateway).await?;
+    bootstrap_admin(
+        &gateway,
+        &gateway,
+        &gateway,
+        &admin,
+        slice::from_ref(&admin.email),
+    )
+    .await?;
+
+    assert!(matches!(
+        revoke_role(&gateway, &gateway, &admin, &admin.id).await,
+        Err(ChangeRoleError::OwnRole)
+    ));
+    assert!(matches!(
+        grant_role(
+            &gateway,
+            &gateway,
+            &gateway,
+            &admin,
+            &admin.id,
+            Role::Organizer
+        )
+        .await,
+        Err(ChangeRoleError::OwnRole)
+    ));
+    assert_eq!(Some(Role::Admin), get_user_role(&gateway, &admin).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn change_role_of_unknown_user() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let admin = create_random_user(&gateway).await?;
+    let user = create_random_user(&gateway).await?;
+    bootstrap_admin(
+        &gateway,
+        &gateway,
+        &gateway,
+        &admin,
+        slice::from_ref(&admin.email),
+    )
+    .await?;
+    let unknown_id = Ulid::new();
+
+    assert!(matches!(
+        grant_role(&gateway, &gateway, &gateway, &admin, &unknown_id, Role::Admin).await,
+        Err(ChangeRoleError::UserNotFound(id)) if id == unknown_id
+    ));
+    assert!(matches!(
+        revoke_role(&gateway, &gateway, &admin, &user.id).await,
+        Err(ChangeRoleError::NoRole(id)) if id == user.id
+    ));
+    Ok(())
+}
+
+#[::tokio::test]
+async fn bootstrap_admins_skips_emails_shared_by_github_users() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let other = create_random_user(&gateway).await?;
+    let other = gateway
+        .store_user(User {
+            email: user.email.clone(),
+            ..other
+        })
+        .await?;
+    let admin_emails = vec![user.email.clone()];
+
+    let results = bootstrap_admins(&gateway, &gateway, &gateway, &admin_emails).await?;
+    let [AdminBootstrap::SharedEmail(email, user_ids)] = results.as_slice() else {
+        panic!("Unexpected {results:?}");
+    };
+    assert_eq!(&user.email, email);
+    assert_eq!(2, user_ids.len());
+    assert!(user_ids.contains(&user.id) && user_ids.contains(&other.id));
+    assert!(!bootstrap_admin(&gateway, &gateway, &gateway, &user, &admin_emails).await?);
+    assert!(!bootstrap_admin(&gateway, &gateway, &gateway, &other, &admin_emails).await?);
+    assert_eq!(None, get_user_role(&gateway, &user).await?);
+    assert_eq!(None, get_user_role(&gateway, &other).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn bootstrap_admins_only_trusts_github_users() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let oidc_user = create_user_with_login_method(
+        &gateway,
+        LoginMethod::Oidc {
+            provider: "keycloak".into(),
+            subject: "subject".into(),
+        },
+    )
+    .await?;
+    let admin_emails = vec![oidc_user.email.clone()];
+
+    assert!(
+        bootstrap_admins(&gateway, &gateway, &gateway, &admin_emails)
+            .await?
+            .is_empty()
+    );
+    assert!(!bootstrap_admin(&gateway, &gateway, &gateway, &oidc_user, &admin_emails).await?);
+    assert_eq!(None, get_user_role(&gateway, &oidc_user).await?);
+
+    let github_user = create_random_user(&gateway).await?;
+    let github_user = gateway
+        .store_user(User {
+            email: oidc_user.email.clone(),
+            ..github_user
+        })
+        .await?;
+    assert_eq!(
+        vec![AdminBootstrap::Granted(github_user.id)],
+        bootstrap_admins(&gateway, &gateway, &gateway, &admin_emails).await?
+    );
+    assert_eq!(None, get_user_role(&gateway, &oidc_user).await?);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn bootstrap_admin_does_not_undo_a_revocation() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let admin = create_random_user(&gateway).await?;
+    let user = create_random_user(&gateway).await?;
+    let admin_emails = vec![admin.email.clone(), user.email.clone()];
+    bootstrap_admins(&gateway, &gateway, &gateway, &admin_emails).await?;
+    assert_eq!(Some(Role::Admin), get_user_role(&gateway, &user).await?);
+
+    revoke_role(&gateway, &gateway, &admin, &user.id).await?;
+
+    // Logging in again and restarting the server.
+    assert!(!bootstrap_admin(&gateway, &gateway, &gateway, &user, &admin_emails).await?);
+    assert!(
+        bootstrap_admins(&gateway, &gateway, &gateway, &admin_emails)
+            .await?
+            .is_empty()
+    );
+    assert_eq!(None, get_user_role(&gateway, &user).await?);
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+
+use domain::{
+    advance_meet_ups, store_votes, submit_paper, MeetUpGateway, MeetUpSettings, MeetUpState,
+};
+use shared::{test::set_now, utc_now};
+use tests::{
+    assert_meet_up_state, build_gateway, build_paper_with_user, create_meet_up_with_settings,
+    create_random_user,
+};
+
+#[::tokio::test]
+async fn advance_meet_ups_before_deadlines() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let now = utc_now();
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            call_for_papers_deadline: Some(now + chrono::Duration::hours(1)),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    let transitions =
+        advance_meet_ups(&gateway, &gateway, &gateway, &gateway, &mut HashSet::new()).await?;
+    assert!(transitions.is_empty());
+    assert_meet_up_state!(gateway, meet_up.id, MeetUpState::CallForPapers);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn advance_meet_ups_without_deadlines() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up = create_meet_up_with_settings(&gateway, MeetUpSettings::default()).await?;
+    set_now(&(utc_now() + chrono::Duration::hours(12)));
+    let transitions =
+        advance_meet_ups(&gateway, &gateway, &gateway, &gateway, &mut HashSet::new()).await?;
+    assert!(transitions.is_empty());
+    assert_meet_up_state!(gateway, meet_up.id, MeetUpState::CallForPapers);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn advance_meet_ups_after_call_for_papers_deadline() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let now = utc_now();
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            call_for_papers_deadline: Some(now + chrono::Duration::hours(1)),
+            voting_deadline: Some(now + chrono::Duration::hours(2)),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    set_now(&(now + chrono::Duration::minutes(90)));
+    let transitions =
+        advance_meet_ups(&gateway, &gateway, &gateway, &gateway, &mut HashSet::new()).await?;
+    assert_eq!(1, transitions.len());
+    assert_eq!(meet_up.id, transitions[0].meet_up_id);
+    let moved = transitions[0].result.as_ref().expect("Should have moved");
+    assert_eq!(MeetUpState::Voting, moved.state);
+    assert_meet_up_state!(gateway, meet_up.id, MeetUpState::Voting);
+
+    // Running it again, e.g. after a restart, does not repeat the transition.
+    let transitions =
+        advance_meet_ups(&gateway, &gateway, &gateway, &gateway, &mut HashSet::new()).await?;
+    assert!(transitions.is_empty());
+    assert_meet_up_state!(gateway, meet_up.id, MeetUpState::Voting);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn advance_meet_ups_after_voting_deadline() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let paper = build_paper_with_user(user.id);
+    let now = utc_now();
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            call_for_papers_deadline: Some(now + chrono::Duration::hours(1)),
+            voting_deadline: Some(now + chrono::Duration::hours(2)),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    set_now(&(now + chrono::Duration::minutes(90)));
+    advance_meet_ups(&gateway, &gateway, &gateway, &gateway, &mut HashSet::new()).await?;
+    store_votes(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user.id,
+        vec![paper.id],
+    )
+    .await?;
+
+    set_now(&(now + chrono::Duration::hours(3)));
+    let transitions =
+        advance_meet_ups(&gateway, &gateway, &gateway, &gateway, &mut HashSet::new()).await?;
+    assert_eq!(1, transitions.len());
+    assert!(transitions[0].result.is_ok());
+    let scheduled = gateway.get_meet_up(&meet_up.id).await?;
+    let MeetUpState::Scheduled(talks) = scheduled.state else {
+        panic!("Should be scheduled, got {:?}", scheduled.state);
+    };
+    assert_eq!(
+        vec![paper.id],
+        talks.iter().map(|talk| talk.paper.id).collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn advance_meet_ups_after_both_deadlines_without_votes() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let now = utc_now();
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            call_for_papers_deadline: Some(now + chrono::Duration::hours(1)),
+            voting_deadline: Some(now + chrono::Duration::hours(2)),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    set_now(&(now + chrono::Duration::hours(3)));
+    let transitions =
+        advance_meet_ups(&gateway, &gateway, &gateway, &gateway, &mut HashSet::new()).await?;
+    assert_eq!(2, transitions.len());
+    assert!(transitions[0].result.is_ok());
+    let err = transitions[1]
+        .result
+        .as_ref()
+        .expect_err("Should not schedule without votes");
+    assert_eq!("No valid paper found", err.to_string());
+    assert_meet_up_state!(gateway, meet_up.id, MeetUpState::Voting);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn advance_meet_ups_reports_empty_meet_ups_once() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let now = utc_now();
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            call_for_papers_deadline: Some(now + chrono::Duration::hours(1)),
+            voting_deadline: Some(now + chrono::Duration::hours(2)),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    set_now(&(now + chrono::Duration::hours(3)));
+    let mut stalled = HashSet::new();
+
+    let transitions =
+        advance_meet_ups(&gateway, &gateway, &gateway, &gateway, &mut stalled).await?;
+    assert_eq!(2, transitions.len());
+    assert!(transitions[1].result.is_err());
+    assert_eq!(HashSet::from([meet_up.id]), stalled);
+
+    for _ in 0..3 {
+        let transitions =
+            advance_meet_ups(&gateway, &gateway, &gateway, &gateway, &mut stalled).await?;
+        assert!(transitions.is_empty());
+    }
+    assert_meet_up_state!(gateway, meet_up.id, MeetUpState::Voting);
+
+    // After a restart it is tried, and reported, once more.
+    let transitions =
+        advance_meet_ups(&gateway, &gateway, &gateway, &gateway, &mut HashSet::new()).await?;
+    assert_eq!(1, transitions.len());
+    assert!(transitions[0].result.is_err());
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use anyhow::Result;
+
+use domain::{
+    end_session, issue_csrf_token, list_audit_events, list_sessions, login_with_session,
+    revoke_session, start_session, verify_csrf_token, AuditAction, AuditFilter, RevokeSessionError,
+};
+use shared::{test::set_now, utc_now};
+use tests::{build_gateway, create_random_user};
+
+#[::tokio::test]
+async fn start_session_and_login_with_it() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let (session, token) = start_session(&gateway, &user, " Firefox ").await?;
+    assert_eq!("Firefox", session.device);
+    assert_eq!(utc_now() + chrono::Duration::days(1), session.expire_at);
+
+    let (logged_user, logged_session) = login_with_session(&gateway, &gateway, &token).await?;
+    assert_eq!(user, logged_user);
+    assert_eq!(session, logged_session);
+    assert!(login_with_session(&gateway, &gateway, "unknown")
+        .await
+        .is_err());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn login_on_another_device_keeps_the_first_session() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let (laptop, laptop_token) = start_session(&gateway, &user, "Laptop").await?;
+    let (phone, phone_token) = start_session(&gateway, &user, "Phone").await?;
+    assert_ne!(laptop_token, phone_token);
+
+    login_with_session(&gateway, &gateway, &laptop_token).await?;
+    login_with_session(&gateway, &gateway, &phone_token).await?;
+    let ids = list_sessions(&gateway, &user)
+        .await?
+        .into_iter()
+        .map(|session| session.id)
+        .collect::<Vec<_>>();
+    assert_eq!(vec![phone.id, laptop.id], ids);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn expired_sessions_are_rejected() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let (session, token) = start_session(&gateway, &user, "Laptop").await?;
+
+    set_now(&(session.expire_at + chrono::Duration::seconds(1)));
+    assert!(login_with_session(&gateway, &gateway, &token)
+        .await
+        .is_err());
+    assert!(list_sessions(&gateway, &user).await?.is_empty());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn revoke_session_of_another_device() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let other_user = create_random_user(&gateway).await?;
+    let (laptop, laptop_token) = start_session(&gateway, &user, "Laptop").await?;
+    let (phone, phone_token) = start_session(&gateway, &user, "Phone").await?;
+
+    let err = revoke_session(&gateway, &gateway, &other_user, &phone.id)
+        .await
+        .expect_err("Should not revoke sessions of other users");
+    assert!(matches!(err, RevokeSessionError::NotFound(id) if id == phone.id));
+
+    revoke_session(&gateway, &gateway, &user, &phone.id).await?;
+    assert!(login_with_session(&gateway, &gateway, &phone_token)
+        .await
+        .is_err());
+    login_with_session(&gateway, &gateway, &laptop_token).await?;
+    let sessions = list_sessions(&gateway, &user).await?;
+    assert_eq!(vec![laptop], sessions);
+    let err = revoke_session(&gateway, &gateway, &user, &phone.id)
+        .await
+        .expect_err("Should not revoke twice");
+    assert!(matches!(err, RevokeSessionError::NotFound(_)));
+
+    let events = list_audit_events(&gateway, &gateway, &AuditFilter::default()).await?;
+    assert_eq!(1, events.len());
+    assert_eq!(AuditAction::RevokeSession, events[0].0.action);
+    assert_eq!(Some(phone.id), events[0].0.target_id);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn end_session_logs_only_that_device_out() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let (laptop, laptop_token) = start_session(&gateway, &user, "Laptop").await?;
+    let (_, phone_token) = start_session(&gateway, &user, "Phone").await?;
+
+    end_session(&gateway, &laptop).await?;
+    assert!(login_with_session(&gateway, &gateway, &laptop_token)
+        .await
+        .is_err());
+    login_with_session(&gateway, &gateway, &phone_token).await?;
+    Ok(())
+}
+
+#[::tokio::test]
+async fn csrf_tokens_belong_to_one_session() -> Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let (_, laptop_token) = start_session(&gateway, &user, "Laptop").await?;
+    let (_, phone_token) = start_session(&gateway, &user, "Phone").await?;
+    let csrf_token = issue_csrf_token(b"secret", &laptop_token);
+
+    assert_eq!(csrf_token, issue_csrf_token(b"secret", &laptop_token));
+    assert!(verify_csrf_token(b"secret", &laptop_token, &csrf_token));
+    assert!(!verify_csrf_token(b"secret", &phone_token, &csrf_token));
+    assert!(!verify_csrf_token(
+        b"other secret",
+        &laptop_token,
+        &csrf_token
+    ));
+    assert!(!verify_csrf_token(b"secret", &laptop_token, ""));
+    assert!(!verify_csrf_token(b"secret", &laptop_token, "not hex"));
+    Ok(())
+}
@@ -1,15 +1,17 @@
 use domain::{
-    register_event_goer, show_admin_page, submit_paper, Location, MeetUpState,
-    ShowAdminPageResponse,
+    register_event_goer, show_admin_page, submit_paper, unregister_event_goer, Location,
+    MeetUpState, ShowAdminPageResponse,
 };
 use shared::utc_now;
-use tests::{build_gateway, build_paper_with_user, create_meet_up, create_random_user};
+use tests::{
+    build_gateway, build_paper_with_user, build_talks, create_meet_up, create_random_user,
+};
 
 #[::tokio::test]
 async fn show_admin_page_without_future_meet_up() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let response = show_admin_page(&gateway, &gateway, &gateway).await?;
-    assert_eq!(ShowAdminPageResponse::NoMeetUp, response);
+    assert!(response.is_empty());
     Ok(())
 }
 
@@ -25,7 +27,7 @@ async fn show_admin_page_with_future_meet_up_and_no_papers() -> anyhow::Result<(
     .await?;
     let response = show_admin_page(&gateway, &gateway, &gateway).await?;
     assert_eq!(
-        ShowAdminPageResponse::MeetUpWithPapers(meet_up, 0),
+        vec![ShowAdminPageResponse::MeetUpWithPapers(meet_up, 0)],
         response
     );
     Ok(())
@@ -35,7 +37,7 @@ async fn show_admin_page_with_future_meet_up_and_no_papers() -> anyhow::Result<(
 async fn show_admin_page_with_future_meet_up_and_papers() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper = build_paper_with_user(user.id.clone());
+    let paper = build_paper_with_user(user.id);
     let meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
@@ -43,11 +45,11 @@ async fn show_admin_page_with_future_meet_up_and_papers() -> anyhow::Result<()>
         MeetUpState::CallForPapers,
     )
     .await?;
-    submit_paper(&gateway, &gateway, paper).await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper).await?;
 
     let response = show_admin_page(&gateway, &gateway, &gateway).await?;
     assert_eq!(
-        ShowAdminPageResponse::MeetUpWithPapers(meet_up, 1),
+        vec![ShowAdminPageResponse::MeetUpWithPapers(meet_up, 1)],
         response
     );
     Ok(())
@@ -57,19 +59,43 @@ async fn show_admin_page_with_future_meet_up_and_papers() -> anyhow::Result<()>
 async fn show_admin_page_with_attendees() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper = build_paper_with_user(user.id.clone());
+    let paper = build_paper_with_user(user.id);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::Scheduled(build_talks(paper, utc_now())),
+    )
+    .await?;
+    register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+
+    let response = show_admin_page(&gateway, &gateway, &gateway).await?;
+    assert_eq!(
+        vec![ShowAdminPageResponse::MeetUpWithAttendees(meet_up, 1)],
+        response
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn show_admin_page_with_attendees_after_withdrawal() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let other_user = create_random_user(&gateway).await?;
     let meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
         utc_now(),
-        MeetUpState::Scheduled(paper),
+        MeetUpState::Scheduled(build_talks(build_paper_with_user(user.id), utc_now())),
     )
     .await?;
-    register_event_goer(&gateway, &gateway, &user.id).await?;
+    register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+    register_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &other_user.id).await?;
+    unregister_event_goer(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
 
     let response = show_admin_page(&gateway, &gateway, &gateway).await?;
     assert_eq!(
-        ShowAdminPageResponse::MeetUpWithAttendees(meet_up, 1),
+        vec![ShowAdminPageResponse::MeetUpWithAttendees(meet_up, 1)],
         response
     );
     Ok(())
@@ -1,14 +1,24 @@
-use domain::{register_event_goer, show_home_page, Location, MeetUpMetadata, MeetUpState};
+use domain::{
+    register_event_goer, show_home_page, Location, MeetUpMetadata, MeetUpRegistration, MeetUpState,
+    RegistrationStatus,
+};
 use shared::utc_now;
-use tests::{build_gateway, build_paper_with_user, create_meet_up, create_random_user};
+use tests::{
+    build_gateway, build_paper_with_user, build_talks, create_meet_up, create_random_user,
+};
+
+fn attending() -> MeetUpRegistration {
+    MeetUpRegistration {
+        status: Some(RegistrationStatus::Attending),
+        remaining_seats: None,
+    }
+}
 
 #[::tokio::test]
 async fn show_home_page_with_no_entities() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
-    let (meet_up, meet_ups_metadata, is_registered_user) =
-        show_home_page(&gateway, &gateway, None).await?;
-    assert_eq!(None, meet_up);
-    assert_eq!(false, is_registered_user);
+    let (meet_ups, meet_ups_metadata) = show_home_page(&gateway, &gateway, None).await?;
+    assert!(meet_ups.is_empty());
     assert_eq!(Vec::<MeetUpMetadata>::new(), meet_ups_metadata);
     Ok(())
 }
@@ -23,10 +33,11 @@ async fn show_home_page_with_future_meet_up() -> anyhow::Result<()> {
         MeetUpState::CallForPapers,
     )
     .await?;
-    let (home_meet_up, meet_ups_metadata, is_registered_user) =
-        show_home_page(&gateway, &gateway, None).await?;
-    assert_eq!(Some(meet_up), home_meet_up);
-    assert_eq!(false, is_registered_user);
+    let (home_meet_ups, meet_ups_metadata) = show_home_page(&gateway, &gateway, None).await?;
+    assert_eq!(
+        vec![(meet_up, MeetUpRegistration::default())],
+        home_meet_ups
+    );
     assert_eq!(Vec::<MeetUpMetadata>::new(), meet_ups_metadata);
     Ok(())
 }
@@ -35,13 +46,13 @@ async fn show_home_page_with_future_meet_up() -> anyhow::Result<()> {
 async fn show_home_page_with_future_and_past_meet_ups() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper = build_paper_with_user(user.id.clone());
+    let paper = build_paper_with_user(user.id);
     let past_meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
         utc_now(),
         MeetUpState::Done {
-            paper: paper.clone(),
+            talks: build_talks(paper.clone(), utc_now()),
             link: "https://example.com".parse()?,
         },
     )
@@ -53,10 +64,12 @@ async fn show_home_page_with_future_and_past_meet_ups() -> anyhow::Result<()> {
         MeetUpState::CallForPapers,
     )
     .await?;
-    let (home_meet_up, meet_ups_metadata, is_registered_user) =
+    let (home_meet_ups, meet_ups_metadata) =
         show_home_page(&gateway, &gateway, Some(&user.id)).await?;
-    assert_eq!(Some(future_meet_up), home_meet_up);
-    assert_eq!(false, is_registered_user);
+    assert_eq!(
+        vec![(future_meet_up, MeetUpRegistration::default())],
+        home_meet_ups
+    );
     assert_eq!(
         vec![MeetUpMetadata::new(
             past_meet_up.id,
@@ -72,18 +85,20 @@ async fn show_home_page_with_future_and_past_meet_ups() -> anyhow::Result<()> {
 async fn show_home_page_with_user_registered_to_meet_up_but_not_logged() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper = build_paper_with_user(user.id.clone());
+    let paper = build_paper_with_user(user.id);
     let future_meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
         utc_now(),
-        MeetUpState::Scheduled(paper),
+        MeetUpState::Scheduled(build_talks(paper, utc_now())),
     )
     .await?;
-    register_event_goer(&gateway, &gateway, &user.id).await?;
-    let (home_meet_up, _, is_registered_user) = show_home_page(&gateway, &gateway, None).await?;
-    assert_eq!(Some(future_meet_up), home_meet_up);
-    assert_eq!(false, is_registered_user);
+    register_event_goer(&gateway, &gateway, &gateway, &future_meet_up.id, &user.id).await?;
+    let (home_meet_ups, _) = show_home_page(&gateway, &gateway, None).await?;
+    assert_eq!(
+        vec![(future_meet_up, MeetUpRegistration::default())],
+        home_meet_ups
+    );
     Ok(())
 }
 
@@ -91,18 +106,52 @@ async fn show_home_page_with_user_registered_to_meet_up_but_not_logged() -> anyh
 async fn show_home_page_with_user_registered_to_meet_up() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper = build_paper_with_user(user.id.clone());
+    let paper = build_paper_with_user(user.id);
     let future_meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
         utc_now(),
-        MeetUpState::Scheduled(paper),
+        MeetUpState::Scheduled(build_talks(paper, utc_now())),
     )
     .await?;
-    register_event_goer(&gateway, &gateway, &user.id).await?;
-    let (home_meet_up, _, is_registered_user) =
-        show_home_page(&gateway, &gateway, Some(&user.id)).await?;
-    assert_eq!(Some(future_meet_up), home_meet_up);
-    assert_eq!(true, is_registered_user);
+    register_event_goer(&gateway, &gateway, &gateway, &future_meet_up.id, &user.id).await?;
+    let (home_meet_ups, _) = show_home_page(&gateway, &gateway, Some(&user.id)).await?;
+    assert_eq!(vec![(future_meet_up, attending())], home_meet_ups);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn show_home_page_with_multiple_future_meet_ups() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let talk_night = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        "2024-12-10T00:00:00Z".parse()?,
+        MeetUpState::Scheduled(build_talks(
+            build_paper_with_user(user.id),
+            "2024-12-10T00:00:00Z".parse()?,
+        )),
+    )
+    .await?;
+    let workshop = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        "2024-12-20T00:00:00Z".parse()?,
+        MeetUpState::Scheduled(build_talks(
+            build_paper_with_user(user.id),
+            "2024-12-20T00:00:00Z".parse()?,
+        )),
+    )
+    .await?;
+    register_event_goer(&gateway, &gateway, &gateway, &workshop.id, &user.id).await?;
+    let (home_meet_ups, _) = show_home_page(&gateway, &gateway, Some(&user.id)).await?;
+    assert_eq!(
+        vec![
+            (talk_night, MeetUpRegistration::default()),
+            (workshop, attending())
+        ],
+        home_meet_ups
+    );
     Ok(())
 }
@@ -1,13 +1,25 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::routing::{get, post};
+use axum::{Form, Json, Router};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{DateTime, Utc};
 use fake::faker::internet::en::FreeEmail;
 use fake::faker::name::en::FirstName;
 use fake::Fake;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
 use tokio::sync::Mutex;
 use ulid::Ulid;
+use url::Url;
 
 use domain::{
-    AccessToken, ExchangeCodeError, GithubGateway, Location, LoginMethod, MeetUp, MeetUpGateway,
-    MeetUpState, Paper, PaperGateway, RefreshTokenError, User, UserGateway, UserInfoGithubError,
+    AccessToken, ExchangeCodeError, GithubGateway, GithubUser, Location, LoginMethod, MailGateway,
+    MeetUp, MeetUpGateway, MeetUpSettings, MeetUpState, Notification, Paper, PaperGateway,
+    RefreshTokenError, SendMailError, Talk, User, UserGateway, UserInfoGithubError, VotingMethod,
+    DEFAULT_TALK_DURATION_MINUTES,
 };
 use gateway::SqliteDatabaseGateway;
 
@@ -29,51 +41,94 @@ pub async fn create_meet_up(
     date: DateTime<Utc>,
     state: MeetUpState,
 ) -> anyhow::Result<MeetUp> {
-    let meet_up = gateway.new_meet_up(Ulid::new(), location, date).await?;
+    let meet_up = gateway
+        .new_meet_up(&MeetUp::new(
+            Ulid::new(),
+            MeetUpState::CallForPapers,
+            location,
+            date,
+            VotingMethod::default(),
+            1,
+            None,
+        ))
+        .await?;
     Ok(match state {
         MeetUpState::CallForPapers => meet_up,
         MeetUpState::Voting => gateway.update_meet_up_to_voting(&meet_up.id).await?,
-        MeetUpState::Scheduled(paper) => {
-            gateway.update_meet_up_to_voting(&meet_up.id).await?;
-            gateway
-                .store_paper_with_meet_up(&paper, &meet_up.id, 100)
-                .await?;
-            gateway
-                .update_meet_up_to_scheduled(&meet_up.id, &paper.id)
-                .await?
+        MeetUpState::Scheduled(talks) => {
+            schedule_talks(gateway, &meet_up.id, &talks).await?;
+            gateway.get_meet_up(&meet_up.id).await?
         }
-        MeetUpState::Done { paper, link } => {
-            gateway.update_meet_up_to_voting(&meet_up.id).await?;
-            gateway
-                .store_paper_with_meet_up(&paper, &meet_up.id, 100)
-                .await?;
-            gateway
-                .update_meet_up_to_scheduled(&meet_up.id, &paper.id)
-                .await?;
+        MeetUpState::Done { talks, link } => {
+            schedule_talks(gateway, &meet_up.id, &talks).await?;
             gateway.finish_meet_up(&meet_up.id, link).await?;
             gateway.get_meet_up(&meet_up.id).await?
         }
+        MeetUpState::Cancelled => gateway.cancel_meet_up(&meet_up.id).await?,
     })
 }
 
-pub async fn create_random_user(gateway: &SqliteDatabaseGateway) -> anyhow::Result<User> {
+/// An on site meet up in call for papers, one day from now.
+pub async fn create_meet_up_with_settings(
+    gateway: &SqliteDatabaseGateway,
+    settings: MeetUpSettings,
+) -> anyhow::Result<MeetUp> {
     Ok(gateway
-        .store_user(User {
-            id: Ulid::new(),
-            email: FreeEmail().fake(),
-            nickname: FirstName().fake(),
-            access_token: AccessToken::generate_new(),
-            login_method: LoginMethod::Github {
-                access_token: AccessToken::generate_new(),
-                refresh_token: AccessToken::generate_new(),
-            },
-        })
+        .new_meet_up(
+            &MeetUp::new(
+                Ulid::new(),
+                MeetUpState::CallForPapers,
+                Location::OnSite("location".into()),
+                shared::utc_now() + chrono::Duration::days(1),
+                VotingMethod::default(),
+                1,
+                None,
+            )
+            .with_settings(settings),
+        )
         .await?)
 }
 
-pub async fn create_user_with_access_token_and_login_method(
+async fn schedule_talks(
+    gateway: &SqliteDatabaseGateway,
+    meet_up_id: &Ulid,
+    talks: &[Talk],
+) -> anyhow::Result<()> {
+    gateway.update_meet_up_to_voting(meet_up_id).await?;
+    for talk in talks {
+        gateway
+            .store_paper_with_meet_up(&talk.paper, meet_up_id, 100, None)
+            .await?;
+    }
+    gateway
+        .update_meet_up_to_scheduled(meet_up_id, talks)
+        .await?;
+    Ok(())
+}
+
+/// A single talk lineup starting with the meet up.
+pub fn build_talks(paper: Paper, date: DateTime<Utc>) -> Vec<Talk> {
+    vec![Talk {
+        paper,
+        start: date,
+        duration_minutes: DEFAULT_TALK_DURATION_MINUTES,
+    }]
+}
+
+pub async fn create_random_user(gateway: &SqliteDatabaseGateway) -> anyhow::Result<User> {
+    create_user_with_login_method(
+        gateway,
+        LoginMethod::Github {
+            github_id: Some((1..i64::MAX).fake()),
+            access_token: AccessToken::generate_new(),
+            refresh_token: AccessToken::generate_new(),
+        },
+    )
+    .await
+}
+
+pub async fn create_user_with_login_method(
     gateway: &SqliteDatabaseGateway,
-    access_token: AccessToken,
     login_method: LoginMethod,
 ) -> anyhow::Result<User> {
     Ok(gateway
@@ -81,7 +136,6 @@ pub async fn create_user_with_access_token_and_login_method(
             id: Ulid::new(),
             email: FreeEmail().fake(),
             nickname: FirstName().fake(),
-            access_token,
             login_method,
         })
         .await?)
@@ -95,6 +149,7 @@ pub fn build_paper_with_user(user_id: Ulid) -> Paper {
         description: "description".into(),
         speaker: "speaker".into(),
         email: "email".into(),
+        keep_in_pool: false,
     }
 }
 
@@ -102,7 +157,7 @@ pub struct GithubGatewayMock {
     internal: Mutex<InternalGithubGatewayMock>,
 }
 
-type UserInfoCallable = dyn Fn(&AccessToken) -> Result<(String, String), UserInfoGithubError>;
+type UserInfoCallable = dyn Fn(&AccessToken) -> Result<GithubUser, UserInfoGithubError>;
 type RefreshTokenCallable =
     dyn Fn(&AccessToken) -> Result<(AccessToken, AccessToken), RefreshTokenError>;
 type ExchangeCodeCallable = dyn Fn(&str) -> Result<(AccessToken, AccessToken), ExchangeCodeError>;
@@ -128,7 +183,7 @@ impl Default for GithubGatewayMock {
 impl GithubGatewayMock {
     pub async fn push_user_info(
         self,
-        callable: impl Fn(&AccessToken) -> Result<(String, String), UserInfoGithubError> + 'static,
+        callable: impl Fn(&AccessToken) -> Result<GithubUser, UserInfoGithubError> + 'static,
     ) -> Self {
         self.internal
             .lock()
@@ -168,7 +223,7 @@ impl GithubGateway for GithubGatewayMock {
     async fn user_info(
         &self,
         access_token: &AccessToken,
-    ) -> Result<(String, String), UserInfoGithubError> {
+    ) -> Result<GithubUser, UserInfoGithubError> {
         self.internal.lock().await.user_infos.pop().unwrap()(access_token)
     }
 
@@ -186,3 +241,121 @@ impl GithubGateway for GithubGatewayMock {
         self.internal.lock().await.exchange_codes.pop().unwrap()(code)
     }
 }
+
+/// Keeps the sent emails in memory, failing for the addresses in `failing_addresses`.
+#[derive(Default)]
+pub struct MailGatewayMock {
+    sent: Mutex<Vec<(String, Notification)>>,
+    failing_addresses: Vec<String>,
+}
+
+impl MailGatewayMock {
+    pub fn failing_for(address: &str) -> Self {
+        Self {
+            failing_addresses: vec![address.to_string()],
+            ..Self::default()
+        }
+    }
+
+    pub async fn sent(&self) -> Vec<(String, Notification)> {
+        self.sent.lock().await.clone()
+    }
+}
+
+impl MailGateway for MailGatewayMock {
+    async fn send_mail(&self, to: &str, notification: &Notification) -> Result<(), SendMailError> {
+        if self.failing_addresses.iter().any(|address| address == to) {
+            return Err(SendMailError::InvalidAddress(to.to_string()));
+        }
+        self.sent
+            .lock()
+            .await
+            .push((to.to_string(), notification.clone()));
+        Ok(())
+    }
+}
+
+/// A local OpenID Connect issuer, answering the token requests with the ID token claims and
+/// userinfo set by the test.
+pub struct OidcIssuerMock {
+    url: Url,
+    internal: Arc<Mutex<InternalOidcIssuerMock>>,
+}
+
+#[derive(Default)]
+struct InternalOidcIssuerMock {
+    id_token_claims: Value,
+    user_info: Value,
+    token_requests: Vec<HashMap<String, String>>,
+}
+
+impl OidcIssuerMock {
+    pub async fn start() -> anyhow::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url: Url = format!("http://{}", listener.local_addr()?).parse()?;
+        let internal = Arc::new(Mutex::new(InternalOidcIssuerMock::default()));
+        let discovery = json!({
+            "issuer": url.as_str().trim_end_matches('/'),
+            "authorization_endpoint": url.join("/authorize")?,
+            "token_endpoint": url.join("/token")?,
+            "userinfo_endpoint": url.join("/userinfo")?,
+        });
+        let router = Router::new()
+            .route(
+                "/.well-known/openid-configuration",
+                get(move || async move { Json(discovery) }),
+            )
+            .route("/token", post(oidc_token))
+            .route("/userinfo", get(oidc_user_info))
+            .with_state(internal.clone());
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        Ok(Self { url, internal })
+    }
+
+    pub fn issuer(&self) -> String {
+        self.url.as_str().trim_end_matches('/').to_string()
+    }
+
+    pub fn discovery_url(&self) -> Url {
+        self.url
+            .join("/.well-known/openid-configuration")
+            .expect("Valid path")
+    }
+
+    pub async fn set_id_token_claims(&self, claims: Value) {
+        self.internal.lock().await.id_token_claims = claims;
+    }
+
+    pub async fn set_user_info(&self, user_info: Value) {
+        self.internal.lock().await.user_info = user_info;
+    }
+
+    pub async fn token_requests(&self) -> Vec<HashMap<String, String>> {
+        self.internal.lock().await.token_requests.clone()
+    }
+}
+
+async fn oidc_token(
+    State(internal): State<Arc<Mutex<InternalOidcIssuerMock>>>,
+    Form(params): Form<HashMap<String, String>>,
+) -> Json<Value> {
+    let mut internal = internal.lock().await;
+    internal.token_requests.push(params);
+    let encode = |value: &Value| URL_SAFE_NO_PAD.encode(value.to_string());
+    let id_token = format!(
+        "{}.{}.{}",
+        encode(&json!({ "alg": "RS256", "typ": "JWT" })),
+        encode(&internal.id_token_claims),
+        URL_SAFE_NO_PAD.encode("signature"),
+    );
+    Json(json!({
+        "access_token": "oidc_access_token",
+        "token_type": "Bearer",
+        "expires_in": 3600,
+        "id_token": id_token,
+    }))
+}
+
+async fn oidc_user_info(State(internal): State<Arc<Mutex<InternalOidcIssuerMock>>>) -> Json<Value> {
+    Json(internal.lock().await.user_info.clone())
+}
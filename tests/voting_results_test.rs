@@ -0,0 +1,614 @@
+use domain::{
+    create_new_meet_up, move_meet_up_back_to_voting, move_meet_up_to_scheduled,
+    move_meet_up_to_voting, reschedule_meet_up_with_next_paper, schedule_meet_up_with_paper,
+    show_voting_results, store_votes, submit_paper, Location, MeetUp, MeetUpSettings, MeetUpState,
+    Paper, VotingMethod,
+};
+use gateway::SqliteDatabaseGateway;
+use shared::utc_now;
+use tests::{
+    assert_meet_up_state, build_gateway, build_paper_with_user, create_meet_up, create_random_user,
+};
+use ulid::Ulid;
+
+fn lineup(meet_up: &MeetUp) -> Vec<Ulid> {
+    match &meet_up.state {
+        MeetUpState::Scheduled(talks) | MeetUpState::Done { talks, .. } => {
+            talks.iter().map(|talk| talk.paper.id).collect()
+        }
+        _ => vec![],
+    }
+}
+
+/// Creates a meet up in voting with three papers ranked `[best, second, third]`.
+async fn create_voted_meet_up(
+    gateway: &SqliteDatabaseGateway,
+    number_of_talks: u8,
+) -> anyhow::Result<(MeetUp, [Paper; 3])> {
+    let user_1 = create_random_user(gateway).await?;
+    let user_2 = create_random_user(gateway).await?;
+    let meet_up = create_new_meet_up(
+        gateway,
+        gateway,
+        gateway,
+        &Ulid::new(),
+        Location::OnSite("location".into()),
+        utc_now(),
+        VotingMethod::default(),
+        number_of_talks,
+        None,
+        MeetUpSettings::default(),
+    )
+    .await?;
+    let best = build_paper_with_user(user_1.id);
+    let second = build_paper_with_user(user_1.id);
+    let third = build_paper_with_user(user_2.id);
+    for paper in [&best, &second, &third] {
+        submit_paper(gateway, gateway, gateway, &meet_up.id, paper.clone()).await?;
+    }
+    move_meet_up_to_voting(gateway, gateway, &Ulid::new(), &meet_up.id).await?;
+    for user in [&user_1, &user_2] {
+        store_votes(
+            gateway,
+            gateway,
+            gateway,
+            gateway,
+            &meet_up.id,
+            &user.id,
+            vec![best.id, second.id, third.id],
+        )
+        .await?;
+    }
+    Ok((meet_up, [best, second, third]))
+}
+
+#[::tokio::test]
+async fn show_voting_results_ranks_every_paper() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let (meet_up, [best, second, third]) = create_voted_meet_up(&gateway, 1).await?;
+    let response = show_voting_results(&gateway, &gateway, &gateway, &meet_up.id).await?;
+    assert_eq!(
+        vec![best.id, second.id, third.id],
+        response
+            .ranking
+            .iter()
+            .map(|(paper, _)| paper.id)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(2.0, response.ranking[0].1.score);
+    assert_eq!(2, response.ranking[0].1.first_place_votes);
+    assert_eq!(2, response.voters);
+    assert!(response.ranking.iter().all(|(_, ranked)| !ranked.tied));
+    assert!(response.overrides.is_empty());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn show_voting_results_reports_ties() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user_1 = create_random_user(&gateway).await?;
+    let user_2 = create_random_user(&gateway).await?;
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    let first_submitted = build_paper_with_user(user_1.id);
+    let mut last_submitted = build_paper_with_user(user_2.id);
+    last_submitted.id = Ulid::from_parts(first_submitted.id.timestamp_ms() + 1, 0);
+    submit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        first_submitted.clone(),
+    )
+    .await?;
+    submit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        last_submitted.clone(),
+    )
+    .await?;
+    move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+    store_votes(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user_1.id,
+        vec![last_submitted.id, first_submitted.id],
+    )
+    .await?;
+    store_votes(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user_2.id,
+        vec![first_submitted.id, last_submitted.id],
+    )
+    .await?;
+
+    let response = show_voting_results(&gateway, &gateway, &gateway, &meet_up.id).await?;
+    assert_eq!(
+        vec![(first_submitted.id, true), (last_submitted.id, true)],
+        response
+            .ranking
+            .iter()
+            .map(|(paper, ranked)| (paper.id, ranked.tied))
+            .collect::<Vec<_>>()
+    );
+    let scheduled_meet_up = move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await?;
+    assert_eq!(vec![first_submitted.id], lineup(&scheduled_meet_up));
+    Ok(())
+}
+
+/// Ballots `[a, b, c]`, `[b, c, a]` and `[c, b, a]`: `b` wins every head to head, but every paper
+/// has a single first preference.
+async fn create_meet_up_with_cyclic_ballots(
+    gateway: &SqliteDatabaseGateway,
+    voting_method: VotingMethod,
+) -> anyhow::Result<(MeetUp, [Paper; 3])> {
+    let meet_up = create_new_meet_up(
+        gateway,
+        gateway,
+        gateway,
+        &Ulid::new(),
+        Location::OnSite("location".into()),
+        utc_now(),
+        voting_method,
+        1,
+        None,
+        MeetUpSettings::default(),
+    )
+    .await?;
+    let mut users = vec![];
+    let mut papers = vec![];
+    for timestamp in 1..=3 {
+        let user = create_random_user(gateway).await?;
+        let mut paper = build_paper_with_user(user.id);
+        paper.id = Ulid::from_parts(timestamp, 0);
+        submit_paper(gateway, gateway, gateway, &meet_up.id, paper.clone()).await?;
+        users.push(user);
+        papers.push(paper);
+    }
+    move_meet_up_to_voting(gateway, gateway, &Ulid::new(), &meet_up.id).await?;
+    let [a, b, c] = [papers[0].id, papers[1].id, papers[2].id];
+    for (user, ballot) in users
+        .iter()
+        .zip([vec![a, b, c], vec![b, c, a], vec![c, b, a]])
+    {
+        store_votes(
+            gateway,
+            gateway,
+            gateway,
+            gateway,
+            &meet_up.id,
+            &user.id,
+            ballot,
+        )
+        .await?;
+    }
+    let papers: [Paper; 3] = papers
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("Expected three papers"))?;
+    Ok((meet_up, papers))
+}
+
+#[::tokio::test]
+async fn show_voting_results_uses_meet_up_voting_method() -> anyhow::Result<()> {
+    for (voting_method, expected_winner) in [
+        (VotingMethod::Harmonic, 1),
+        (VotingMethod::Borda, 1),
+        (VotingMethod::Schulze, 1),
+        (VotingMethod::InstantRunoff, 0),
+    ] {
+        let gateway = build_gateway().await?;
+        let (meet_up, papers) = create_meet_up_with_cyclic_ballots(&gateway, voting_method).await?;
+        let response = show_voting_results(&gateway, &gateway, &gateway, &meet_up.id).await?;
+        assert_eq!(
+            papers[expected_winner].id, response.ranking[0].0.id,
+            "{voting_method}"
+        );
+        assert_eq!(3, response.voters);
+    }
+    Ok(())
+}
+
+#[::tokio::test]
+async fn instant_runoff_reports_papers_eliminated_together_as_tied() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let (meet_up, papers) =
+        create_meet_up_with_cyclic_ballots(&gateway, VotingMethod::InstantRunoff).await?;
+    let response = show_voting_results(&gateway, &gateway, &gateway, &meet_up.id).await?;
+    assert!(response.ranking.iter().all(|(_, ranked)| ranked.tied));
+    let scheduled_meet_up = move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await?;
+    assert_eq!(vec![papers[0].id], lineup(&scheduled_meet_up));
+    Ok(())
+}
+
+#[::tokio::test]
+async fn show_voting_results_with_invalid_meet_up_state() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    let err = show_voting_results(&gateway, &gateway, &gateway, &meet_up.id)
+        .await
+        .expect_err("Should error out");
+    assert_eq!("Invalid meet up state: CallForPapers", err.to_string());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn schedule_meet_up_with_paper_overrides_voting() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let (meet_up, [_, _, third]) = create_voted_meet_up(&gateway, 1).await?;
+    let scheduled_meet_up = schedule_meet_up_with_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &third.id,
+    )
+    .await?;
+    assert_eq!(vec![third.id], lineup(&scheduled_meet_up));
+    let overrides = show_voting_results(&gateway, &gateway, &gateway, &meet_up.id)
+        .await?
+        .overrides;
+    assert_eq!(1, overrides.len());
+    assert_eq!(None, overrides[0].previous_paper_id);
+    assert_eq!(Some(third.id), overrides[0].paper_id);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn schedule_meet_up_with_paper_appends_to_lineup() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let (meet_up, [best, _, third]) = create_voted_meet_up(&gateway, 1).await?;
+    move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await?;
+    let scheduled_meet_up = schedule_meet_up_with_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &third.id,
+    )
+    .await?;
+    assert_eq!(vec![best.id, third.id], lineup(&scheduled_meet_up));
+    let MeetUpState::Scheduled(talks) = scheduled_meet_up.state else {
+        panic!("Meet up should be scheduled");
+    };
+    assert_eq!(talks[0].end(), talks[1].start);
+
+    let err = schedule_meet_up_with_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &best.id,
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!(
+        format!("Paper `{}` is already in the lineup", best.id),
+        err.to_string()
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn schedule_meet_up_with_paper_from_another_meet_up() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let (meet_up, _) = create_voted_meet_up(&gateway, 1).await?;
+    let (_, [other_paper, _, _]) = create_voted_meet_up(&gateway, 1).await?;
+    let err = schedule_meet_up_with_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &other_paper.id,
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!(
+        format!(
+            "Paper `{}` was not submitted to meet up `{}`",
+            other_paper.id, meet_up.id
+        ),
+        err.to_string()
+    );
+    assert_meet_up_state!(gateway, meet_up.id, MeetUpState::Voting);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn schedule_meet_up_with_paper_not_found() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let id = Ulid::new();
+    let err = schedule_meet_up_with_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &id,
+        &Ulid::new(),
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!(format!("Meet up with id `{id}` not found"), err.to_string());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn reschedule_meet_up_with_next_paper_follows_ranking() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let (meet_up, [best, second, third]) = create_voted_meet_up(&gateway, 1).await?;
+    let scheduled_meet_up = move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await?;
+    assert_eq!(vec![best.id], lineup(&scheduled_meet_up));
+
+    let rescheduled_meet_up = reschedule_meet_up_with_next_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &best.id,
+    )
+    .await?;
+    assert_eq!(vec![second.id], lineup(&rescheduled_meet_up));
+    let rescheduled_meet_up = reschedule_meet_up_with_next_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &second.id,
+    )
+    .await?;
+    assert_eq!(vec![third.id], lineup(&rescheduled_meet_up));
+    let err = reschedule_meet_up_with_next_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &third.id,
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!(
+        format!("No paper ranked after `{}`", third.title),
+        err.to_string()
+    );
+
+    let overrides = show_voting_results(&gateway, &gateway, &gateway, &meet_up.id)
+        .await?
+        .overrides;
+    assert_eq!(
+        vec![
+            (Some(best.id), Some(second.id)),
+            (Some(second.id), Some(third.id))
+        ],
+        overrides
+            .iter()
+            .map(|schedule_override| (
+                schedule_override.previous_paper_id,
+                schedule_override.paper_id
+            ))
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn reschedule_meet_up_with_next_paper_skips_papers_in_lineup() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let (meet_up, [best, second, third]) = create_voted_meet_up(&gateway, 2).await?;
+    let scheduled_meet_up = move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await?;
+    assert_eq!(vec![best.id, second.id], lineup(&scheduled_meet_up));
+
+    let rescheduled_meet_up = reschedule_meet_up_with_next_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &best.id,
+    )
+    .await?;
+    assert_eq!(vec![third.id, second.id], lineup(&rescheduled_meet_up));
+    let (MeetUpState::Scheduled(before), MeetUpState::Scheduled(after)) =
+        (scheduled_meet_up.state, rescheduled_meet_up.state)
+    else {
+        panic!("Meet up should be scheduled");
+    };
+    assert_eq!(before[0].start, after[0].start);
+    assert_eq!(before[0].duration_minutes, after[0].duration_minutes);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn reschedule_meet_up_with_next_paper_not_in_lineup() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let (meet_up, [_, _, third]) = create_voted_meet_up(&gateway, 1).await?;
+    move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await?;
+    let err = reschedule_meet_up_with_next_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &third.id,
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!(
+        format!("Paper `{}` is not in the lineup", third.id),
+        err.to_string()
+    );
+    Ok(())
+}
+
+#[::tokio::test]
+async fn reschedule_meet_up_with_next_paper_with_invalid_meet_up_state() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let (meet_up, [best, _, _]) = create_voted_meet_up(&gateway, 1).await?;
+    let err = reschedule_meet_up_with_next_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+        &best.id,
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!("Invalid meet up state: Voting", err.to_string());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn move_meet_up_back_to_voting_from_scheduled() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let (meet_up, [best, _, _]) = create_voted_meet_up(&gateway, 1).await?;
+    move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await?;
+    let voting_meet_up =
+        move_meet_up_back_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+    assert_eq!(MeetUpState::Voting, voting_meet_up.state);
+    let overrides = show_voting_results(&gateway, &gateway, &gateway, &meet_up.id)
+        .await?
+        .overrides;
+    assert_eq!(1, overrides.len());
+    assert_eq!(Some(best.id), overrides[0].previous_paper_id);
+    assert_eq!(None, overrides[0].paper_id);
+    Ok(())
+}
+
+#[::tokio::test]
+async fn move_meet_up_back_to_voting_drops_whole_lineup() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let (meet_up, [best, second, _]) = create_voted_meet_up(&gateway, 2).await?;
+    move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await?;
+    move_meet_up_back_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+    let overrides = show_voting_results(&gateway, &gateway, &gateway, &meet_up.id)
+        .await?
+        .overrides;
+    assert_eq!(
+        vec![(Some(best.id), None), (Some(second.id), None)],
+        overrides
+            .iter()
+            .map(|schedule_override| (
+                schedule_override.previous_paper_id,
+                schedule_override.paper_id
+            ))
+            .collect::<Vec<_>>()
+    );
+    let scheduled_meet_up = move_meet_up_to_scheduled(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &Ulid::new(),
+        &meet_up.id,
+    )
+    .await?;
+    assert_eq!(vec![best.id, second.id], lineup(&scheduled_meet_up));
+    Ok(())
+}
+
+#[::tokio::test]
+async fn move_meet_up_back_to_voting_with_invalid_meet_up_state() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let (meet_up, _) = create_voted_meet_up(&gateway, 1).await?;
+    let err = move_meet_up_back_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id)
+        .await
+        .expect_err("Should error out");
+    assert_eq!("Invalid meet up state: Voting", err.to_string());
+    Ok(())
+}
@@ -1,19 +1,25 @@
 use assertables::{assert_contains, assert_contains_as_result};
 
 use domain::{
-    move_future_meet_up_to_voting, show_voting, store_votes, submit_paper, Location, MeetUpState,
+    move_meet_up_to_voting, show_voting, store_votes, submit_paper, Location, MeetUpSettings,
+    MeetUpState, StoreVotesError, VoteGateway,
 };
 use shared::utc_now;
-use tests::{build_gateway, build_paper_with_user, create_meet_up, create_random_user};
+use tests::{
+    build_gateway, build_paper_with_user, create_meet_up, create_meet_up_with_settings,
+    create_random_user,
+};
+use ulid::Ulid;
 
 #[::tokio::test]
 async fn show_voting_without_meet_up() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let err = show_voting(&gateway, &gateway, &gateway, &user.id)
+    let id = Ulid::new();
+    let err = show_voting(&gateway, &gateway, &gateway, &id, &user.id)
         .await
         .expect_err("Should error out");
-    assert_eq!("No future meetups found", err.to_string());
+    assert_eq!(format!("Meet up with id `{id}` not found"), err.to_string());
     Ok(())
 }
 
@@ -21,14 +27,14 @@ async fn show_voting_without_meet_up() -> anyhow::Result<()> {
 async fn show_voting_with_invalid_meet_up_state() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let _ = create_meet_up(
+    let meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
         utc_now(),
         MeetUpState::CallForPapers,
     )
     .await?;
-    let err = show_voting(&gateway, &gateway, &gateway, &user.id)
+    let err = show_voting(&gateway, &gateway, &gateway, &meet_up.id, &user.id)
         .await
         .expect_err("Should error out");
     assert_eq!("Invalid meet up state: CallForPapers", err.to_string());
@@ -46,7 +52,8 @@ async fn show_voting_without_papers() -> anyhow::Result<()> {
         MeetUpState::Voting,
     )
     .await?;
-    let (voting_meet_up, papers) = show_voting(&gateway, &gateway, &gateway, &user.id).await?;
+    let (voting_meet_up, papers) =
+        show_voting(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
     assert_eq!(meet_up, voting_meet_up);
     assert!(papers.is_empty());
     Ok(())
@@ -56,8 +63,8 @@ async fn show_voting_without_papers() -> anyhow::Result<()> {
 async fn show_voting_with_papers() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper_1 = build_paper_with_user(user.id.clone());
-    let paper_2 = build_paper_with_user(user.id.clone());
+    let paper_1 = build_paper_with_user(user.id);
+    let paper_2 = build_paper_with_user(user.id);
     let mut meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
@@ -65,11 +72,12 @@ async fn show_voting_with_papers() -> anyhow::Result<()> {
         MeetUpState::CallForPapers,
     )
     .await?;
-    submit_paper(&gateway, &gateway, paper_1.clone()).await?;
-    submit_paper(&gateway, &gateway, paper_2.clone()).await?;
-    move_future_meet_up_to_voting(&gateway).await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper_1.clone()).await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper_2.clone()).await?;
+    move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
     meet_up.state = MeetUpState::Voting;
-    let (voting_meet_up, papers) = show_voting(&gateway, &gateway, &gateway, &user.id).await?;
+    let (voting_meet_up, papers) =
+        show_voting(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
     assert_eq!(meet_up, voting_meet_up);
     assert_contains!(papers, &paper_1);
     assert_contains!(papers, &paper_2);
@@ -80,8 +88,8 @@ async fn show_voting_with_papers() -> anyhow::Result<()> {
 async fn store_and_show_voting() -> anyhow::Result<()> {
     let gateway = build_gateway().await?;
     let user = create_random_user(&gateway).await?;
-    let paper_1 = build_paper_with_user(user.id.clone());
-    let paper_2 = build_paper_with_user(user.id.clone());
+    let paper_1 = build_paper_with_user(user.id);
+    let paper_2 = build_paper_with_user(user.id);
     let mut meet_up = create_meet_up(
         &gateway,
         Location::OnSite("location".into()),
@@ -89,13 +97,165 @@ async fn store_and_show_voting() -> anyhow::Result<()> {
         MeetUpState::CallForPapers,
     )
     .await?;
-    submit_paper(&gateway, &gateway, paper_1.clone()).await?;
-    submit_paper(&gateway, &gateway, paper_2.clone()).await?;
-    move_future_meet_up_to_voting(&gateway).await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper_1.clone()).await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper_2.clone()).await?;
+    move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
     meet_up.state = MeetUpState::Voting;
-    store_votes(&gateway, &gateway, &user.id, vec![paper_2.id, paper_1.id]).await?;
-    let (voting_meet_up, papers) = show_voting(&gateway, &gateway, &gateway, &user.id).await?;
+    store_votes(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user.id,
+        vec![paper_2.id, paper_1.id],
+    )
+    .await?;
+    let (voting_meet_up, papers) =
+        show_voting(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
     assert_eq!(meet_up, voting_meet_up);
     assert_eq!(vec![paper_2, paper_1], papers);
     Ok(())
 }
+
+#[::tokio::test]
+async fn store_votes_with_invalid_meet_up_state() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let paper = build_paper_with_user(user.id);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    let err = store_votes(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user.id,
+        vec![paper.id],
+    )
+    .await
+    .expect_err("Should error out");
+    assert_eq!("Invalid meet up state: CallForPapers", err.to_string());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn store_votes_after_voting_deadline() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let paper = build_paper_with_user(user.id);
+    let deadline = utc_now() - chrono::Duration::minutes(1);
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            voting_deadline: Some(deadline),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+    let err = store_votes(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user.id,
+        vec![paper.id],
+    )
+    .await
+    .expect_err("Should error out");
+    assert!(matches!(err, StoreVotesError::VotingClosed(at) if at == deadline));
+
+    // Showing the voting page after the deadline does not start a ballot either.
+    let (_, papers) = show_voting(&gateway, &gateway, &gateway, &meet_up.id, &user.id).await?;
+    assert_eq!(vec![paper], papers);
+    assert!(gateway.get_votes_for_meet_up(&meet_up.id).await?.is_empty());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn store_votes_before_voting_deadline() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let paper = build_paper_with_user(user.id);
+    let meet_up = create_meet_up_with_settings(
+        &gateway,
+        MeetUpSettings {
+            voting_deadline: Some(utc_now() + chrono::Duration::hours(1)),
+            ..MeetUpSettings::default()
+        },
+    )
+    .await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+    store_votes(
+        &gateway,
+        &gateway,
+        &gateway,
+        &gateway,
+        &meet_up.id,
+        &user.id,
+        vec![paper.id],
+    )
+    .await?;
+    assert_eq!(1, gateway.get_votes_for_meet_up(&meet_up.id).await?.len());
+    Ok(())
+}
+
+#[::tokio::test]
+async fn store_votes_with_foreign_or_repeated_paper() -> anyhow::Result<()> {
+    let gateway = build_gateway().await?;
+    let user = create_random_user(&gateway).await?;
+    let paper = build_paper_with_user(user.id);
+    let foreign_paper = build_paper_with_user(user.id);
+    let meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    let other_meet_up = create_meet_up(
+        &gateway,
+        Location::OnSite("location".into()),
+        utc_now(),
+        MeetUpState::CallForPapers,
+    )
+    .await?;
+    submit_paper(&gateway, &gateway, &gateway, &meet_up.id, paper.clone()).await?;
+    submit_paper(
+        &gateway,
+        &gateway,
+        &gateway,
+        &other_meet_up.id,
+        foreign_paper.clone(),
+    )
+    .await?;
+    move_meet_up_to_voting(&gateway, &gateway, &Ulid::new(), &meet_up.id).await?;
+
+    for ballot in [vec![paper.id, foreign_paper.id], vec![paper.id, paper.id]] {
+        let err = store_votes(
+            &gateway,
+            &gateway,
+            &gateway,
+            &gateway,
+            &meet_up.id,
+            &user.id,
+            ballot.clone(),
+        )
+        .await
+        .expect_err("Should error out");
+        assert!(matches!(err, StoreVotesError::InvalidPaper(id) if id == ballot[1]));
+    }
+    assert!(gateway.get_votes_for_meet_up(&meet_up.id).await?.is_empty());
+    Ok(())
+}
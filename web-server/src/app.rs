@@ -5,48 +5,76 @@ use anyhow::{bail, Result};
 use axum::http::header::{REFERRER_POLICY, STRICT_TRANSPORT_SECURITY, X_CONTENT_TYPE_OPTIONS};
 use axum::http::HeaderValue;
 use axum::routing::{get, post};
-use axum::Router;
-use minijinja::Environment;
+use axum::{middleware, Router};
+use minijinja::{AutoEscape, Environment};
 use tower_http::{services::ServeDir, set_header::SetResponseHeaderLayer};
+use url::Url;
 
+use domain::{backfill_github_ids, bootstrap_admins, AdminBootstrap};
 use gateway::github::GithubRestGateway;
+use gateway::mail::LettreMailGateway;
+use gateway::oidc::OidcRestGateway;
 use gateway::SqliteDatabaseGateway;
 
 use crate::controllers::admin::admin_router;
-use crate::controllers::call_for_papers::{call_for_papers, save_call_for_papers};
+use crate::controllers::api::api_router;
+use crate::controllers::calendar::{calendar, meet_up_calendar};
+use crate::controllers::call_for_papers::{
+    call_for_papers, save_call_for_papers, update_paper, withdraw,
+};
+use crate::controllers::feed::feed;
 use crate::controllers::index::index;
 use crate::controllers::meet_up::{meet_up, meet_up_metadata};
-use crate::controllers::meet_up_goers::register;
-use crate::controllers::user::{github_login, logout, user};
+use crate::controllers::meet_up_goers::{register, unregister};
+use crate::controllers::user::{
+    github_login, login, login_with_provider, logout, new_api_token, oidc_login, revoke,
+    revoke_session, update_mail_notifications, user,
+};
 use crate::controllers::voting::{paper_details, paper_no_details, store_vote, voting};
+use crate::csrf::{csrf_protection, csrf_token};
+use crate::scheduler::spawn_scheduler;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn build_app<T: Clone + Send + Sync + 'static>(
     assets_dir: impl AsRef<Path>,
     database_url: String,
-    admin_details: (String, String),
+    admin_emails: Vec<String>,
     (client_id, client_secret): (String, String),
+    mail_gateway: LettreMailGateway,
+    site_url: Url,
+    secret_key: String,
+    oidc_gateways: Vec<OidcRestGateway>,
 ) -> Result<Router<T>> {
-    Ok(Router::new()
-        .route("/", get(index))
-        .nest("/admin", admin_router())
-        .route("/callForPapers", get(call_for_papers))
-        .route("/callForPapers", post(save_call_for_papers))
-        .route("/voting", get(voting))
-        .route("/voting/paperDetails/:id", get(paper_details))
-        .route("/voting/paperNoDetails/:id", get(paper_no_details))
-        .route("/storeVote", post(store_vote))
-        .route("/meetUp/:id", get(meet_up))
-        .route("/meetUp/metadata/:id", get(meet_up_metadata))
-        .route("/user", get(user))
-        .route("/github/authorize", get(github_login))
-        .route("/logout", get(logout))
-        .route("/register", post(register))
-        .with_state(Arc::new(AppState::new(
-            SqliteDatabaseGateway::new(&database_url).await?,
-            GithubRestGateway::new(client_id.clone(), client_secret),
-            client_id,
-            admin_details,
-        )?))
+    let state = Arc::new(AppState::new(
+        SqliteDatabaseGateway::new(&database_url).await?,
+        GithubRestGateway::new(client_id.clone(), client_secret),
+        mail_gateway,
+        client_id,
+        admin_emails,
+        site_url,
+        secret_key.into_bytes(),
+        oidc_gateways,
+    )?);
+    for result in bootstrap_admins(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.admin_emails,
+    )
+    .await?
+    {
+        match result {
+            AdminBootstrap::Granted(user_id) => {
+                tracing::info!("Granted the admin role from ADMIN_EMAILS to {user_id}")
+            }
+            AdminBootstrap::SharedEmail(email, user_ids) => tracing::warn!(
+                "Not granting the admin role to {email}, the GitHub users {user_ids:?} share it"
+            ),
+        }
+    }
+    spawn_scheduler(state.clone());
+    spawn_github_id_backfill(state.clone());
+    Ok(routes(state)
         .fallback_service(ServeDir::new(assets_dir.as_ref()))
         .layer(SetResponseHeaderLayer::if_not_present(
             STRICT_TRANSPORT_SECURITY,
@@ -62,11 +90,83 @@ pub async fn build_app<T: Clone + Send + Sync + 'static>(
         )))
 }
 
+/// Pages and API behind the CSRF protection, without the static files.
+pub fn routes<T: Clone + Send + Sync + 'static>(state: Arc<AppState>) -> Router<T> {
+    Router::new()
+        .route("/", get(index))
+        .nest("/admin", admin_router())
+        .nest("/api/v1", api_router())
+        .route("/callForPapers/:id", get(call_for_papers))
+        .route("/callForPapers/:id", post(save_call_for_papers))
+        .route("/callForPapers/:id/papers/:paper_id", post(update_paper))
+        .route(
+            "/callForPapers/:id/papers/:paper_id/withdraw",
+            post(withdraw),
+        )
+        .route("/voting/:id", get(voting))
+        .route("/voting/paperDetails/:id", get(paper_details))
+        .route("/voting/paperNoDetails/:id", get(paper_no_details))
+        .route("/storeVote/:id", post(store_vote))
+        .route("/meetUp/:id", get(meet_up))
+        .route("/meetUp/metadata/:id", get(meet_up_metadata))
+        .route("/meetUp/:id/calendar.ics", get(meet_up_calendar))
+        .route("/calendar.ics", get(calendar))
+        .route("/feed.atom", get(feed))
+        .route("/user", get(user))
+        .route("/user/mailNotifications", post(update_mail_notifications))
+        .route("/user/apiTokens", post(new_api_token))
+        .route("/user/apiTokens/:id/revoke", post(revoke))
+        .route("/user/sessions/:id/revoke", post(revoke_session))
+        .route("/login", get(login))
+        .route("/login/:provider", get(login_with_provider))
+        .route("/github/authorize", get(github_login))
+        .route("/oidc/:provider/authorize", get(oidc_login))
+        .route("/logout", get(logout))
+        .route("/register/:id", post(register))
+        .route("/unregister/:id", post(unregister))
+        .layer(middleware::from_fn_with_state(
+            state.clone(),
+            csrf_protection,
+        ))
+        .with_state(state)
+}
+
+/// Looks up the GitHub ids missing since before they were stored, without delaying the startup.
+fn spawn_github_id_backfill(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        match backfill_github_ids(&state.database_gateway, &state.github_gateway).await {
+            Ok(results) => {
+                for (user_id, result) in results {
+                    match result {
+                        Ok(github_id) => {
+                            tracing::info!("Stored GitHub id {github_id} of user {user_id}")
+                        }
+                        Err(err) => {
+                            tracing::warn!(
+                                "Could not look up the GitHub id of user {user_id}: {err}"
+                            )
+                        }
+                    }
+                }
+            }
+            Err(err) => tracing::error!("Could not backfill the GitHub ids: {err}"),
+        }
+    });
+}
+
 pub struct AppState {
-    pub admin_details: (String, String),
+    /// Users with these emails become admins when they log in.
+    pub admin_emails: Vec<String>,
     pub database_gateway: SqliteDatabaseGateway,
     pub github_gateway: GithubRestGateway,
+    pub mail_gateway: LettreMailGateway,
     pub github_client_id: String,
+    /// Public address of the site, for absolute links.
+    pub site_url: Url,
+    /// Signs the login state, never shown to the users.
+    pub secret_key: Vec<u8>,
+    /// Login providers besides GitHub, in the order of their buttons.
+    pub oidc_gateways: Vec<OidcRestGateway>,
     pub minijinja_enviroment: Environment<'static>,
 }
 
@@ -83,20 +183,34 @@ macro_rules! add_template {
 }
 
 impl AppState {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         database_gateway: SqliteDatabaseGateway,
         github_gateway: GithubRestGateway,
+        mail_gateway: LettreMailGateway,
         github_client_id: String,
-        admin_details: (String, String),
+        admin_emails: Vec<String>,
+        site_url: Url,
+        secret_key: Vec<u8>,
+        oidc_gateways: Vec<OidcRestGateway>,
     ) -> Result<Self> {
         let mut env = Environment::new();
+        // The names lose the ".html" suffix that minijinja uses to turn the escaping on, and the
+        // pages show text sent by users and identity providers.
+        env.set_auto_escape_callback(|_| AutoEscape::Html);
+        env.add_function("csrf_token", csrf_token);
         add_template!(env, "templates/base.html");
         add_template!(env, "templates/home.html");
         add_template!(env, "templates/admin.html");
+        add_template!(env, "templates/roles.html");
+        add_template!(env, "templates/audit.html");
         add_template!(env, "templates/user.html");
+        add_template!(env, "templates/login.html");
         add_template!(env, "templates/call_for_papers.html");
         add_template!(env, "templates/voting.html");
         add_template!(env, "templates/success.html");
+        add_template!(env, "templates/voting_results.html");
+        add_template!(env, "templates/attendees.html");
         add_template!(env, "templates/components/vote_paper/paper.html");
         add_template!(env, "templates/components/vote_paper/paper_details.html");
         add_template!(env, "templates/components/past_meet_ups/past_meet_ups.html");
@@ -111,7 +225,7 @@ impl AppState {
         );
         add_template!(
             env,
-            "templates/components/admin/future_meet_up/no_future_meet_up.html"
+            "templates/components/admin/future_meet_up/new_meet_up.html"
         );
         add_template!(
             env,
@@ -126,6 +240,14 @@ impl AppState {
             env,
             "templates/components/admin/future_meet_up/location.html"
         );
+        add_template!(
+            env,
+            "templates/components/admin/future_meet_up/edit_meet_up.html"
+        );
+        add_template!(
+            env,
+            "templates/components/admin/future_meet_up/cancelled.html"
+        );
         add_template!(
             env,
             "templates/components/future_meet_ups/future_meet_up.html"
@@ -137,20 +259,65 @@ impl AppState {
         add_template!(env, "templates/components/future_meet_ups/voting.html");
         add_template!(env, "templates/components/future_meet_ups/scheduled.html");
         add_template!(env, "templates/components/future_meet_ups/location.html");
+        add_template!(env, "templates/components/future_meet_ups/cancelled.html");
         add_template!(
             env,
             "templates/components/future_meet_ups/register_button.html"
         );
+        add_template!(env, "templates/components/call_for_papers/edit_paper.html");
         Ok(Self {
-            admin_details,
+            admin_emails,
             github_gateway,
+            mail_gateway,
             github_client_id,
+            site_url,
+            secret_key,
+            oidc_gateways,
             database_gateway,
             minijinja_enviroment: env,
         })
     }
 
+    /// Browsers drop secure cookies over plain HTTP, which local development uses.
+    pub fn secure_cookies(&self) -> bool {
+        self.site_url.scheme() == "https"
+    }
+
+    pub fn oidc_gateway(&self, id: &str) -> Option<&OidcRestGateway> {
+        self.oidc_gateways
+            .iter()
+            .find(|oidc_gateway| oidc_gateway.id() == id)
+    }
+
     pub fn get_minijinja_env(&self) -> &Environment<'static> {
         &self.minijinja_enviroment
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use minijinja::context;
+
+    use crate::test_support::test_state;
+
+    #[tokio::test]
+    async fn templates_escape_html() -> anyhow::Result<()> {
+        let state = test_state().await?;
+        let tmpl = state.get_minijinja_env().get_template("roles")?;
+
+        let html = tmpl.render(context! {
+            users => vec![context! {
+                nickname => "<script>alert(1)</script>",
+                email => "evil@rustcwb.dev",
+            }],
+            errors => Vec::<String>::new(),
+        })?;
+
+        assert!(
+            html.contains("&lt;script&gt;alert(1)&lt;&#x2f;script&gt;"),
+            "{html}"
+        );
+        assert!(!html.contains("<script>alert(1)"));
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::{Html, IntoResponse},
+};
+use axum_htmx::HxRequest;
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use domain::{check_in_event_goer, list_attendees, Attendee, User};
+
+use crate::{
+    app::AppState,
+    controllers::{format_time, HtmlError, MeetUpPresenter, UserPresenter},
+    extractors::AdminUser,
+};
+
+pub async fn attendees(
+    AdminUser(user, _): AdminUser,
+    HxRequest(is_hx_request): HxRequest,
+    Path(id): Path<Ulid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    render_attendees(&state, &id, user, is_hx_request).await
+}
+
+pub async fn attendees_csv(
+    _: AdminUser,
+    Path(id): Path<Ulid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, HtmlError> {
+    let response = list_attendees(&state.database_gateway, &state.database_gateway, &id).await?;
+    let mut writer = csv::Writer::from_writer(vec![]);
+    writer.write_record([
+        "nickname",
+        "email",
+        "status",
+        "registered_at",
+        "checked_in_at",
+    ])?;
+    for attendee in response.attendees {
+        let status = status(&attendee);
+        writer.write_record([
+            escape_formula(attendee.nickname),
+            escape_formula(attendee.email),
+            status.into(),
+            attendee.registered_at.to_rfc3339(),
+            attendee
+                .checked_in_at
+                .map(|checked_in_at| checked_in_at.to_rfc3339())
+                .unwrap_or_default(),
+        ])?;
+    }
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"attendees-{id}.csv\""),
+            ),
+        ],
+        writer.into_inner()?,
+    ))
+}
+
+pub async fn check_in(
+    AdminUser(admin, _): AdminUser,
+    Path((id, user_id)): Path<(Ulid, Ulid)>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    check_in_event_goer(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &admin.id,
+        &id,
+        &user_id,
+        true,
+    )
+    .await?;
+    render_attendees(&state, &id, admin, true).await
+}
+
+pub async fn undo_check_in(
+    AdminUser(admin, _): AdminUser,
+    Path((id, user_id)): Path<(Ulid, Ulid)>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    check_in_event_goer(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &admin.id,
+        &id,
+        &user_id,
+        false,
+    )
+    .await?;
+    render_attendees(&state, &id, admin, true).await
+}
+
+async fn render_attendees(
+    state: &AppState,
+    id: &Ulid,
+    user: User,
+    is_hx_request: bool,
+) -> Result<Html<String>, HtmlError> {
+    let tmpl = state.get_minijinja_env().get_template("attendees")?;
+    let response = list_attendees(&state.database_gateway, &state.database_gateway, id).await?;
+    let checked_in = response.checked_in();
+    let no_show_rate = response
+        .no_show_rate()
+        .map(|rate| format!("{:.0}%", rate * 100.0));
+
+    let context = context! {
+        attendees => response
+            .attendees
+            .iter()
+            .map(AttendeePresenter::from)
+            .collect::<Vec<_>>(),
+        meet_up => MeetUpPresenter::from(response.meet_up),
+        checked_in => checked_in,
+        no_show_rate => no_show_rate,
+        user => UserPresenter::from(user),
+    };
+    match is_hx_request {
+        true => Ok(Html(tmpl.eval_to_state(context)?.render_block("content")?)),
+        false => Ok(Html(tmpl.render(context)?)),
+    }
+}
+
+/// Keeps spreadsheets from running a cell as a formula, nicknames are free text from the login
+/// providers.
+fn escape_formula(cell: String) -> String {
+    match cell.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        true => format!("'{cell}"),
+        false => cell,
+    }
+}
+
+fn status(attendee: &Attendee) -> &'static str {
+    match attendee.waitlisted {
+        true => "waitlisted",
+        false => "attending",
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AttendeePresenter {
+    user_id: Ulid,
+    nickname: String,
+    email: String,
+    status: String,
+    registered_at: String,
+    checked_in_at: Option<String>,
+}
+
+impl From<&Attendee> for AttendeePresenter {
+    fn from(attendee: &Attendee) -> Self {
+        Self {
+            user_id: attendee.user_id,
+            nickname: attendee.nickname.clone(),
+            email: attendee.email.clone(),
+            status: status(attendee).into(),
+            registered_at: format_time(attendee.registered_at),
+            checked_in_at: attendee.checked_in_at.map(format_time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+
+    use domain::{
+        Location, LoginMethod, MeetUp, MeetUpGateway, MeetUpGoersGateway, MeetUpState, Role,
+        UserGateway, VotingMethod,
+    };
+    use shared::utc_now;
+
+    use crate::{
+        extractors::SESSION_COOKIE,
+        test_support::{body_text, create_user, send, session_token, test_state},
+    };
+
+    use super::*;
+
+    #[test]
+    fn escape_formula_prefixes_formulas() {
+        for cell in ["=1+1", "+1", "-1", "@SUM(A1)", "\tx", "\rx"] {
+            assert_eq!(format!("'{cell}"), escape_formula(cell.to_string()));
+        }
+        assert_eq!("ferris", escape_formula("ferris".to_string()));
+    }
+
+    #[tokio::test]
+    async fn attendees_csv_escapes_formulas() -> anyhow::Result<()> {
+        let state = test_state().await?;
+        let admin = create_user(&state, Some(Role::Admin)).await?;
+        let session = session_token(&state, &admin).await?;
+        let id = Ulid::new();
+        let attendee = state
+            .database_gateway
+            .store_user(User::new(
+                id,
+                "=HYPERLINK(\"https://evil.example\")".into(),
+                format!("{id}@rustcwb.dev"),
+                LoginMethod::Oidc {
+                    provider: "test".into(),
+                    subject: id.to_string(),
+                },
+            ))
+            .await?;
+        let meet_up = state
+            .database_gateway
+            .new_meet_up(&MeetUp::new(
+                Ulid::new(),
+                MeetUpState::CallForPapers,
+                Location::OnSite("location".into()),
+                utc_now(),
+                VotingMethod::default(),
+                1,
+                None,
+            ))
+            .await?;
+        state
+            .database_gateway
+            .update_meet_up_to_voting(&meet_up.id)
+            .await?;
+        state
+            .database_gateway
+            .update_meet_up_to_scheduled(&meet_up.id, &[])
+            .await?;
+        state
+            .database_gateway
+            .register_user_to_meet_up(&attendee.id, &meet_up.id, None)
+            .await?;
+
+        let response = send(
+            &state,
+            Request::get(format!("/admin/meetUp/{}/attendees.csv", meet_up.id))
+                .header("cookie", format!("{SESSION_COOKIE}={session}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+        let csv = body_text(response).await?;
+        assert!(
+            csv.contains("\"'=HYPERLINK(\"\"https://evil.example\"\")\""),
+            "{csv}"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::Html,
+};
+use axum_htmx::HxRequest;
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use domain::{list_audit_events, AuditEvent, AuditFilter, User, AUDIT_LOG_LIMIT};
+
+use crate::{
+    app::AppState,
+    controllers::{format_time, HtmlError, UserPresenter},
+    extractors::AdminUser,
+};
+
+use super::meet_up::empty_string_as_none;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditParams {
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    meet_up_id: Option<Ulid>,
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    actor_id: Option<Ulid>,
+}
+
+pub async fn audit(
+    AdminUser(user, _): AdminUser,
+    HxRequest(is_hx_request): HxRequest,
+    Query(params): Query<AuditParams>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    let tmpl = state.get_minijinja_env().get_template("audit")?;
+    let filter = AuditFilter {
+        meet_up_id: params.meet_up_id,
+        actor_id: params.actor_id,
+    };
+    let events =
+        list_audit_events(&state.database_gateway, &state.database_gateway, &filter).await?;
+
+    let context = context! {
+        events => events
+            .into_iter()
+            .map(AuditEventPresenter::from)
+            .collect::<Vec<_>>(),
+        meet_up_id => filter.meet_up_id,
+        actor_id => filter.actor_id,
+        limit => AUDIT_LOG_LIMIT,
+        user => UserPresenter::from(user),
+    };
+    match is_hx_request {
+        true => Ok(Html(tmpl.eval_to_state(context)?.render_block("content")?)),
+        false => Ok(Html(tmpl.render(context)?)),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AuditEventPresenter {
+    date: String,
+    action: String,
+    actor_id: Option<Ulid>,
+    /// `None` when done by the server, or the user no longer exists.
+    actor_email: Option<String>,
+    meet_up_id: Option<Ulid>,
+    target_id: Option<Ulid>,
+    payload: String,
+}
+
+impl From<(AuditEvent, Option<User>)> for AuditEventPresenter {
+    fn from((event, actor): (AuditEvent, Option<User>)) -> Self {
+        Self {
+            date: format_time(event.date),
+            action: event.action.to_string(),
+            actor_id: event.actor_id,
+            actor_email: actor.map(|actor| actor.email),
+            meet_up_id: event.meet_up_id,
+            target_id: event.target_id,
+            payload: event.payload.to_string(),
+        }
+    }
+}
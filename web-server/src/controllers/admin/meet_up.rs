@@ -1,55 +1,173 @@
 use anyhow::anyhow;
-use axum::{extract::State, response::Html, Form};
+use axum::{
+    extract::{Path, State},
+    response::Html,
+    Form,
+};
 use chrono::{DateTime, NaiveDateTime, Utc};
 use minijinja::context;
 use serde::{Deserialize, Deserializer};
-use std::sync::Arc;
+use std::{fmt::Display, str::FromStr, sync::Arc};
+use ulid::Ulid;
 use url::Url;
 
 use domain::{
-    create_new_meet_up, move_future_meet_up_to_done, move_future_meet_up_to_scheduled,
-    move_future_meet_up_to_voting,
+    cancel_meet_up, create_new_meet_up, move_meet_up_to_done, move_meet_up_to_scheduled,
+    move_meet_up_to_voting, notify_lineup, notify_registration_confirmed, notify_voting_opened,
+    update_meet_up, update_meet_up_talk, Location, MeetUpSettings, VotingMethod,
+    DEFAULT_PAPERS_PER_USER_PER_MEET_UP,
 };
 
 use crate::{
     app::AppState,
     controllers::{admin::MeetUpPresenter, HtmlError},
     extractors::AdminUser,
+    notifications::spawn_notification,
 };
 
 pub async fn create_meet_up(
-    _: AdminUser,
+    AdminUser(admin, _): AdminUser,
     State(state): State<Arc<AppState>>,
-    Form(params): Form<CreateFutureMeetUpParam>,
+    Form(params): Form<MeetUpParam>,
 ) -> Result<Html<String>, HtmlError> {
     let tmpl = state
         .get_minijinja_env()
         .get_template("components/admin/future_meet_up/future_meet_up")?;
-    let location = match params.location_type.as_str() {
-        "OnSite" => domain::Location::OnSite(params.location_address),
-        "Online" => domain::Location::Online {
-            video_conference_link: params.location_video_conference_link.parse()?,
-            calendar_link: params.location_calendar_link.parse()?,
+    let meet_up = create_new_meet_up(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &admin.id,
+        params.location()?,
+        params.date,
+        params.voting_method,
+        params.number_of_talks,
+        params.capacity,
+        MeetUpSettings {
+            papers_per_user: params.papers_per_user,
+            max_papers: params.max_papers,
+            call_for_papers_deadline: params.call_for_papers_deadline,
+            voting_deadline: params.voting_deadline,
         },
-        other => return Err(anyhow!("Invalid location type {other}").into()),
+    )
+    .await?;
+
+    let context = context! {
+        future_meet_up => MeetUpPresenter::from(meet_up),
+    };
+    Ok(Html(tmpl.render(context)?))
+}
+
+pub async fn update(
+    AdminUser(admin, _): AdminUser,
+    Path(id): Path<Ulid>,
+    State(state): State<Arc<AppState>>,
+    Form(params): Form<MeetUpParam>,
+) -> Result<Html<String>, HtmlError> {
+    let tmpl = state
+        .get_minijinja_env()
+        .get_template("components/admin/future_meet_up/future_meet_up")?;
+    let (meet_up, promoted_user_ids) = update_meet_up(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &admin.id,
+        &id,
+        params.location()?,
+        params.date,
+        params.capacity,
+    )
+    .await?;
+    let notification_state = state.clone();
+    spawn_notification(async move {
+        notify_registration_confirmed(
+            &notification_state.mail_gateway,
+            &notification_state.database_gateway,
+            &notification_state.database_gateway,
+            &id,
+            &promoted_user_ids,
+        )
+        .await
+    });
+
+    let context = context! {
+        future_meet_up => MeetUpPresenter::from(meet_up),
     };
-    let meet_up = create_new_meet_up(&state.database_gateway, location, params.date).await?;
+    Ok(Html(tmpl.render(context)?))
+}
+
+pub async fn cancel(
+    AdminUser(admin, _): AdminUser,
+    Path(id): Path<Ulid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    let tmpl = state
+        .get_minijinja_env()
+        .get_template("components/admin/future_meet_up/future_meet_up")?;
+    let meet_up = cancel_meet_up(
+        &state.database_gateway,
+        &state.database_gateway,
+        &admin.id,
+        &id,
+    )
+    .await?;
 
     let context = context! {
         future_meet_up => MeetUpPresenter::from(meet_up),
-        client_id => state.github_client_id.clone(),
     };
     Ok(Html(tmpl.render(context)?))
 }
 
 #[derive(Debug, Clone, Deserialize)]
-pub struct CreateFutureMeetUpParam {
+pub struct MeetUpParam {
     location_type: String,
     location_address: String,
     location_video_conference_link: String,
     location_calendar_link: String,
     #[serde(deserialize_with = "from_datetime_local_form")]
     date: DateTime<Utc>,
+    /// Only used when creating a meet up.
+    #[serde(default)]
+    voting_method: VotingMethod,
+    /// Only used when creating a meet up.
+    #[serde(default = "default_number_of_talks")]
+    number_of_talks: u8,
+    /// Only kept for on site meet ups, empty means unlimited seats.
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    capacity: Option<u32>,
+    /// Only used when creating a meet up.
+    #[serde(default = "default_papers_per_user")]
+    papers_per_user: u8,
+    /// Only used when creating a meet up, empty means no limit.
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    max_papers: Option<u32>,
+    /// Only used when creating a meet up, empty means no deadline.
+    #[serde(default, deserialize_with = "optional_datetime_local_form")]
+    call_for_papers_deadline: Option<DateTime<Utc>>,
+    /// Only used when creating a meet up, empty means no deadline.
+    #[serde(default, deserialize_with = "optional_datetime_local_form")]
+    voting_deadline: Option<DateTime<Utc>>,
+}
+
+fn default_number_of_talks() -> u8 {
+    1
+}
+
+fn default_papers_per_user() -> u8 {
+    DEFAULT_PAPERS_PER_USER_PER_MEET_UP
+}
+
+impl MeetUpParam {
+    fn location(&self) -> anyhow::Result<Location> {
+        Ok(match self.location_type.as_str() {
+            "OnSite" => Location::OnSite(self.location_address.clone()),
+            "Online" => Location::Online {
+                video_conference_link: self.location_video_conference_link.parse()?,
+                calendar_link: self.location_calendar_link.parse()?,
+            },
+            other => return Err(anyhow!("Invalid location type {other}")),
+        })
+    }
 }
 
 fn from_datetime_local_form<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
@@ -67,55 +185,145 @@ where
         })
 }
 
+fn optional_datetime_local_form<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    match s.trim() {
+        "" => Ok(None),
+        s => from_datetime_local_form(serde::de::value::StrDeserializer::new(s)).map(Some),
+    }
+}
+
+pub(super) fn empty_string_as_none<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let s = String::deserialize(deserializer)?;
+    match s.trim() {
+        "" => Ok(None),
+        s => s.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
 pub async fn go_for_voting(
-    _: AdminUser,
+    AdminUser(admin, _): AdminUser,
+    Path(id): Path<Ulid>,
     State(state): State<Arc<AppState>>,
 ) -> Result<Html<String>, HtmlError> {
     let tmpl = state
         .get_minijinja_env()
         .get_template("components/admin/future_meet_up/future_meet_up")?;
-    let meet_up = move_future_meet_up_to_voting(&state.database_gateway).await?;
+    let meet_up = move_meet_up_to_voting(
+        &state.database_gateway,
+        &state.database_gateway,
+        &admin.id,
+        &id,
+    )
+    .await?;
+    let (notification_state, notification_meet_up) = (state.clone(), meet_up.clone());
+    spawn_notification(async move {
+        notify_voting_opened(
+            &notification_state.mail_gateway,
+            &notification_state.database_gateway,
+            &notification_meet_up,
+        )
+        .await
+    });
 
     let context = context! {
         future_meet_up => MeetUpPresenter::from(meet_up),
-        client_id => state.github_client_id.clone(),
     };
     Ok(Html(tmpl.render(context)?))
 }
 
 pub async fn schedule(
-    _: AdminUser,
+    AdminUser(admin, _): AdminUser,
+    Path(id): Path<Ulid>,
     State(state): State<Arc<AppState>>,
 ) -> Result<Html<String>, HtmlError> {
     let tmpl = state
         .get_minijinja_env()
         .get_template("components/admin/future_meet_up/future_meet_up")?;
-    let meet_up =
-        move_future_meet_up_to_scheduled(&state.database_gateway, &state.database_gateway).await?;
+    let meet_up = move_meet_up_to_scheduled(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &admin.id,
+        &id,
+    )
+    .await?;
+    let (notification_state, notification_meet_up) = (state.clone(), meet_up.clone());
+    spawn_notification(async move {
+        notify_lineup(
+            &notification_state.mail_gateway,
+            &notification_state.database_gateway,
+            &notification_state.database_gateway,
+            &notification_meet_up,
+        )
+        .await
+    });
 
     let context = context! {
         future_meet_up => MeetUpPresenter::from(meet_up),
-        client_id => state.github_client_id.clone(),
     };
     Ok(Html(tmpl.render(context)?))
 }
 
-pub async fn finish(
-    _: AdminUser,
+pub async fn update_talk(
+    AdminUser(admin, _): AdminUser,
+    Path((id, paper_id)): Path<(Ulid, Ulid)>,
     State(state): State<Arc<AppState>>,
-    Form(params): Form<FinishFutureMeetUpParam>,
+    Form(params): Form<TalkParam>,
 ) -> Result<Html<String>, HtmlError> {
     let tmpl = state
         .get_minijinja_env()
         .get_template("components/admin/future_meet_up/future_meet_up")?;
-    move_future_meet_up_to_done(&state.database_gateway, params.link).await?;
+    let meet_up = update_meet_up_talk(
+        &state.database_gateway,
+        &state.database_gateway,
+        &admin.id,
+        &id,
+        &paper_id,
+        params.start,
+        params.duration_minutes,
+    )
+    .await?;
 
     let context = context! {
-        client_id => state.github_client_id.clone(),
+        future_meet_up => MeetUpPresenter::from(meet_up),
     };
     Ok(Html(tmpl.render(context)?))
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct TalkParam {
+    #[serde(deserialize_with = "from_datetime_local_form")]
+    start: DateTime<Utc>,
+    duration_minutes: u32,
+}
+
+pub async fn finish(
+    AdminUser(admin, _): AdminUser,
+    Path(id): Path<Ulid>,
+    State(state): State<Arc<AppState>>,
+    Form(params): Form<FinishFutureMeetUpParam>,
+) -> Result<Html<String>, HtmlError> {
+    move_meet_up_to_done(
+        &state.database_gateway,
+        &state.database_gateway,
+        &admin.id,
+        &id,
+        params.link,
+    )
+    .await?;
+    Ok(Html(String::new()))
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct FinishFutureMeetUpParam {
     link: Url,
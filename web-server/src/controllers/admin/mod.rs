@@ -9,33 +9,53 @@ use axum::{
 use axum_htmx::HxRequest;
 use minijinja::context;
 
+use attendees::{attendees, attendees_csv, check_in, undo_check_in};
+use audit::audit;
 use domain::show_admin_page;
-use meet_up::{create_meet_up, finish, go_for_voting, schedule};
+use meet_up::{cancel, create_meet_up, finish, go_for_voting, schedule, update, update_talk};
+use roles::{grant, revoke, roles};
+use voting_results::{reopen_voting, schedule_next, schedule_paper, voting_results};
 
-use crate::extractors::MaybeUser;
 use crate::{app::AppState, controllers::MeetUpPresenter, extractors::AdminUser};
 
 use super::{HtmlError, UserPresenter};
 
+pub mod attendees;
+pub mod audit;
 pub mod meet_up;
+pub mod roles;
+pub mod voting_results;
 
 pub fn admin_router() -> Router<Arc<AppState>> {
     Router::new()
         .route("/", get(admin))
         .route("/createMeetUp", post(create_meet_up))
-        .route("/voting", post(go_for_voting))
-        .route("/schedule", post(schedule))
-        .route("/finish", post(finish))
+        .route("/meetUp/:id/voting", post(go_for_voting))
+        .route("/meetUp/:id/schedule", post(schedule))
+        .route("/meetUp/:id/finish", post(finish))
+        .route("/meetUp/:id/update", post(update))
+        .route("/meetUp/:id/cancel", post(cancel))
+        .route("/meetUp/:id/talks/:paper_id", post(update_talk))
+        .route("/meetUp/:id/results", get(voting_results))
+        .route("/meetUp/:id/schedule/:paper_id", post(schedule_paper))
+        .route("/meetUp/:id/scheduleNext/:paper_id", post(schedule_next))
+        .route("/meetUp/:id/reopenVoting", post(reopen_voting))
+        .route("/meetUp/:id/attendees", get(attendees))
+        .route("/meetUp/:id/attendees.csv", get(attendees_csv))
+        .route("/meetUp/:id/checkIn/:user_id", post(check_in))
+        .route("/meetUp/:id/undoCheckIn/:user_id", post(undo_check_in))
+        .route("/audit", get(audit))
+        .route("/roles", get(roles).post(grant))
+        .route("/roles/:user_id/revoke", post(revoke))
 }
 
 pub async fn admin(
-    _: AdminUser,
-    maybe_user: MaybeUser,
+    AdminUser(user, _): AdminUser,
     HxRequest(is_hx_request): HxRequest,
     State(state): State<Arc<AppState>>,
 ) -> Result<Html<String>, HtmlError> {
     let tmpl = state.get_minijinja_env().get_template("admin")?;
-    let meet_up_response = show_admin_page(
+    let meet_up_responses = show_admin_page(
         &state.database_gateway,
         &state.database_gateway,
         &state.database_gateway,
@@ -43,11 +63,15 @@ pub async fn admin(
     .await?;
 
     let context = context! {
-        n_papers => meet_up_response.n_papers(),
-        n_attendees => meet_up_response.n_attendees(),
-        future_meet_up => meet_up_response.into_meet_up().map(MeetUpPresenter::from),
-        user => maybe_user.0.map(UserPresenter::from),
-        client_id => state.github_client_id.clone(),
+        future_meet_ups => meet_up_responses
+            .into_iter()
+            .map(|response| (
+                response.n_papers(),
+                response.n_attendees(),
+                MeetUpPresenter::from(response.into_meet_up()),
+            ))
+            .collect::<Vec<(Option<usize>, Option<usize>, MeetUpPresenter)>>(),
+        user => UserPresenter::from(user),
     };
     match is_hx_request {
         true => Ok(Html(tmpl.eval_to_state(context)?.render_block("content")?)),
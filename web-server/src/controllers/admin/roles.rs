@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    response::Html,
+    Form,
+};
+use axum_htmx::HxRequest;
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use domain::{
+    grant_role, list_roles, list_users, revoke_role, ChangeRoleError, LoginMethod, Role,
+    RoleChange, User,
+};
+
+use crate::{
+    app::AppState,
+    controllers::{format_time, HtmlError, UserPresenter},
+    extractors::AdminUser,
+};
+
+pub async fn roles(
+    AdminUser(user, role): AdminUser,
+    HxRequest(is_hx_request): HxRequest,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    render_roles(&state, user, role, vec![], is_hx_request).await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GrantRoleParams {
+    user_id: Ulid,
+    role: Role,
+}
+
+pub async fn grant(
+    AdminUser(user, role): AdminUser,
+    State(state): State<Arc<AppState>>,
+    Form(params): Form<GrantRoleParams>,
+) -> Result<Html<String>, HtmlError> {
+    let errors = match grant_role(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &user,
+        &params.user_id,
+        params.role,
+    )
+    .await
+    {
+        Ok(_) => vec![],
+        Err(ChangeRoleError::Unknown(err)) => return Err(HtmlError::from(err)),
+        Err(err) => vec![err.to_string()],
+    };
+    render_roles(&state, user, role, errors, true).await
+}
+
+pub async fn revoke(
+    AdminUser(user, role): AdminUser,
+    Path(user_id): Path<Ulid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    let errors = match revoke_role(
+        &state.database_gateway,
+        &state.database_gateway,
+        &user,
+        &user_id,
+    )
+    .await
+    {
+        Ok(()) => vec![],
+        Err(ChangeRoleError::Unknown(err)) => return Err(HtmlError::from(err)),
+        Err(err) => vec![err.to_string()],
+    };
+    render_roles(&state, user, role, errors, true).await
+}
+
+async fn render_roles(
+    state: &AppState,
+    user: User,
+    role: Role,
+    errors: Vec<String>,
+    is_hx_request: bool,
+) -> Result<Html<String>, HtmlError> {
+    let tmpl = state.get_minijinja_env().get_template("roles")?;
+    let overview = list_roles(&state.database_gateway, &state.database_gateway).await?;
+    let all_users = match role {
+        Role::Admin => list_users(&state.database_gateway).await?,
+        _ => vec![],
+    };
+
+    let context = context! {
+        users => overview
+            .users
+            .into_iter()
+            .map(|(user, role)| UserRolePresenter {
+                user_id: user.id,
+                login: login_name(&user.login_method),
+                nickname: user.nickname,
+                email: user.email,
+                role: role.to_string(),
+            })
+            .collect::<Vec<_>>(),
+        all_users => all_users
+            .into_iter()
+            .map(|user| UserOptionPresenter {
+                user_id: user.id,
+                login: login_name(&user.login_method),
+                nickname: user.nickname,
+                email: user.email,
+            })
+            .collect::<Vec<_>>(),
+        changes => overview
+            .changes
+            .into_iter()
+            .map(RoleChangePresenter::from)
+            .collect::<Vec<_>>(),
+        roles => Role::ALL.map(|role| role.to_string()),
+        can_change_roles => role == Role::Admin,
+        errors => errors,
+        user_id => user.id,
+        user => UserPresenter::from(user),
+    };
+    match is_hx_request {
+        true => Ok(Html(tmpl.eval_to_state(context)?.render_block("content")?)),
+        false => Ok(Html(tmpl.render(context)?)),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UserRolePresenter {
+    user_id: Ulid,
+    nickname: String,
+    email: String,
+    /// Tells apart the accounts that share an email.
+    login: String,
+    role: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UserOptionPresenter {
+    user_id: Ulid,
+    nickname: String,
+    email: String,
+    login: String,
+}
+
+fn login_name(login_method: &LoginMethod) -> String {
+    match login_method {
+        LoginMethod::Github { .. } => "GitHub".to_string(),
+        LoginMethod::Oidc { provider, .. } => provider.clone(),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RoleChangePresenter {
+    email: String,
+    /// `None` when revoked.
+    role: Option<String>,
+    /// `None` when granted from `ADMIN_EMAILS`.
+    changed_by: Option<String>,
+    date: String,
+}
+
+impl From<(RoleChange, User, Option<User>)> for RoleChangePresenter {
+    fn from((change, user, changed_by): (RoleChange, User, Option<User>)) -> Self {
+        Self {
+            email: user.email,
+            role: change.role.map(|role| role.to_string()),
+            changed_by: changed_by.map(|changed_by| changed_by.email),
+            date: format_time(change.date),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use axum::{
+    extract::{Path, State},
+    response::Html,
+};
+use axum_htmx::HxRequest;
+use minijinja::context;
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use domain::{
+    move_meet_up_back_to_voting, reschedule_meet_up_with_next_paper, schedule_meet_up_with_paper,
+    show_voting_results, MeetUpState, User,
+};
+
+use crate::{
+    app::AppState,
+    controllers::{format_time, HtmlError, MeetUpPresenter, UserPresenter},
+    extractors::AdminUser,
+};
+
+pub async fn voting_results(
+    AdminUser(user, _): AdminUser,
+    HxRequest(is_hx_request): HxRequest,
+    Path(id): Path<Ulid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    render_voting_results(&state, &id, user, is_hx_request).await
+}
+
+pub async fn schedule_paper(
+    AdminUser(admin, _): AdminUser,
+    Path((id, paper_id)): Path<(Ulid, Ulid)>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    schedule_meet_up_with_paper(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &admin.id,
+        &id,
+        &paper_id,
+    )
+    .await?;
+    render_voting_results(&state, &id, admin, true).await
+}
+
+pub async fn schedule_next(
+    AdminUser(admin, _): AdminUser,
+    Path((id, paper_id)): Path<(Ulid, Ulid)>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    reschedule_meet_up_with_next_paper(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &admin.id,
+        &id,
+        &paper_id,
+    )
+    .await?;
+    render_voting_results(&state, &id, admin, true).await
+}
+
+pub async fn reopen_voting(
+    AdminUser(admin, _): AdminUser,
+    Path(id): Path<Ulid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    move_meet_up_back_to_voting(
+        &state.database_gateway,
+        &state.database_gateway,
+        &admin.id,
+        &id,
+    )
+    .await?;
+    render_voting_results(&state, &id, admin, true).await
+}
+
+async fn render_voting_results(
+    state: &AppState,
+    id: &Ulid,
+    user: User,
+    is_hx_request: bool,
+) -> Result<Html<String>, HtmlError> {
+    let tmpl = state.get_minijinja_env().get_template("voting_results")?;
+    let response = show_voting_results(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        id,
+    )
+    .await?;
+    let lineup = match &response.meet_up.state {
+        MeetUpState::Scheduled(talks) | MeetUpState::Done { talks, .. } => talks
+            .iter()
+            .map(|talk| talk.paper.id)
+            .collect::<HashSet<Ulid>>(),
+        _ => HashSet::new(),
+    };
+    let titles = response
+        .ranking
+        .iter()
+        .map(|(paper, _)| (paper.id, paper.title.clone()))
+        .collect::<HashMap<Ulid, String>>();
+
+    let context = context! {
+        meet_up => MeetUpPresenter::from(response.meet_up),
+        voters => response.voters,
+        ranking => response
+            .ranking
+            .into_iter()
+            .map(|(paper, ranked)| RankedPaperPresenter {
+                id: paper.id,
+                scheduled: lineup.contains(&paper.id),
+                title: paper.title,
+                speaker: paper.speaker,
+                score: format!("{:.2}", ranked.score),
+                first_place_votes: ranked.first_place_votes,
+                tied: ranked.tied,
+            })
+            .collect::<Vec<_>>(),
+        overrides => response
+            .overrides
+            .into_iter()
+            .map(|schedule_override| ScheduleOverridePresenter {
+                previous_paper_title: schedule_override
+                    .previous_paper_id
+                    .and_then(|paper_id| titles.get(&paper_id).cloned()),
+                paper_title: schedule_override
+                    .paper_id
+                    .and_then(|paper_id| titles.get(&paper_id).cloned()),
+                date: format_time(schedule_override.date),
+            })
+            .collect::<Vec<_>>(),
+        user => UserPresenter::from(user),
+    };
+    match is_hx_request {
+        true => Ok(Html(tmpl.eval_to_state(context)?.render_block("content")?)),
+        false => Ok(Html(tmpl.render(context)?)),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RankedPaperPresenter {
+    id: Ulid,
+    title: String,
+    speaker: String,
+    score: String,
+    first_place_votes: usize,
+    tied: bool,
+    scheduled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ScheduleOverridePresenter {
+    previous_paper_title: Option<String>,
+    paper_title: Option<String>,
+    date: String,
+}
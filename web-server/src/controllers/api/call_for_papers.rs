@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use axum_extra::extract::WithRejection;
+use serde::Deserialize;
+use ulid::Ulid;
+
+use domain::{notify_paper_received, ApiTokenScope, Paper, SubmitPaperError};
+
+use crate::{app::AppState, extractors::ApiUser, notifications::spawn_notification};
+
+use super::{ApiError, PaperPresenter};
+
+#[derive(Debug, Deserialize)]
+pub struct PaperParams {
+    title: String,
+    description: String,
+    speaker: String,
+    email: String,
+    #[serde(default)]
+    keep_in_pool: bool,
+}
+
+pub async fn submit_paper(
+    api_user: ApiUser,
+    State(state): State<Arc<AppState>>,
+    WithRejection(Path(id), _): WithRejection<Path<Ulid>, ApiError>,
+    WithRejection(Json(params), _): WithRejection<Json<PaperParams>, ApiError>,
+) -> Result<(StatusCode, Json<PaperPresenter>), ApiError> {
+    api_user.require_scope(ApiTokenScope::SubmitPaper)?;
+    for (field, value) in [
+        ("title", &params.title),
+        ("description", &params.description),
+        ("speaker", &params.speaker),
+        ("email", &params.email),
+    ] {
+        if value.trim().is_empty() {
+            return Err(ApiError::bad_request(format!("`{field}` is required")));
+        }
+    }
+    let paper = Paper {
+        id: Ulid::new(),
+        title: params.title,
+        email: params.email,
+        description: params.description,
+        speaker: params.speaker,
+        user_id: api_user.user.id,
+        keep_in_pool: params.keep_in_pool,
+    };
+    match domain::submit_paper(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &id,
+        paper.clone(),
+    )
+    .await
+    {
+        Ok(_) => {}
+        Err(SubmitPaperError::MeetUpNotFound(id)) => {
+            return Err(ApiError::not_found(format!("Meet up `{id}` not found")))
+        }
+        Err(SubmitPaperError::Unknown(err)) => return Err(err.into()),
+        Err(err) => return Err(ApiError::unprocessable(err.to_string())),
+    }
+    let notification_state = state.clone();
+    let notification_paper = paper.clone();
+    spawn_notification(async move {
+        notify_paper_received(
+            &notification_state.mail_gateway,
+            &notification_state.database_gateway,
+            &notification_state.database_gateway,
+            &id,
+            &notification_paper,
+        )
+        .await
+    });
+    Ok((StatusCode::CREATED, Json(paper.into())))
+}
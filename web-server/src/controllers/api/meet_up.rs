@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use axum_extra::extract::WithRejection;
+use ulid::Ulid;
+
+use domain::{get_meet_up, list_future_meet_ups, show_meet_up_details, MeetUpGateway};
+
+use crate::app::AppState;
+
+use super::{
+    ApiError, MeetUpDetailsPresenter, MeetUpMetadataPresenter, MeetUpPresenter, PageParams,
+    PagePresenter, TalkPresenter,
+};
+
+/// Upcoming meet ups, soonest first.
+pub async fn future_meet_ups(
+    State(state): State<Arc<AppState>>,
+    WithRejection(Query(params), _): WithRejection<Query<PageParams>, ApiError>,
+) -> Result<Json<PagePresenter<MeetUpPresenter>>, ApiError> {
+    let meet_ups = list_future_meet_ups(&state.database_gateway).await?;
+    Ok(Json(PagePresenter::new(meet_ups, &params)?))
+}
+
+/// Done meet ups, latest first.
+pub async fn past_meet_ups(
+    State(state): State<Arc<AppState>>,
+    WithRejection(Query(params), _): WithRejection<Query<PageParams>, ApiError>,
+) -> Result<Json<PagePresenter<MeetUpMetadataPresenter>>, ApiError> {
+    let meet_ups = state.database_gateway.list_past_meet_ups().await?;
+    Ok(Json(PagePresenter::new(meet_ups, &params)?))
+}
+
+pub async fn meet_up(
+    State(state): State<Arc<AppState>>,
+    WithRejection(Path(id), _): WithRejection<Path<Ulid>, ApiError>,
+) -> Result<Json<MeetUpDetailsPresenter>, ApiError> {
+    let details =
+        show_meet_up_details(&state.database_gateway, &state.database_gateway, &id).await?;
+    Ok(Json(details.into()))
+}
+
+/// The lineup, empty until the meet up is scheduled.
+pub async fn meet_up_talks(
+    State(state): State<Arc<AppState>>,
+    WithRejection(Path(id), _): WithRejection<Path<Ulid>, ApiError>,
+) -> Result<Json<Vec<TalkPresenter>>, ApiError> {
+    let meet_up = get_meet_up(&state.database_gateway, id).await?;
+    Ok(Json(
+        meet_up
+            .talks()
+            .iter()
+            .cloned()
+            .map(TalkPresenter::from)
+            .collect(),
+    ))
+}
@@ -0,0 +1,406 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::rejection::{JsonRejection, PathRejection, QueryRejection},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+use url::Url;
+
+use domain::{
+    GetPastMeetUpError, Location, MeetUp, MeetUpDetails, MeetUpMetadata, MeetUpRegistration,
+    MeetUpState, Paper, RegistrationStatus, Talk, User, UserMeetUpActivity, Vote,
+};
+
+use crate::app::AppState;
+
+use call_for_papers::submit_paper;
+use meet_up::{future_meet_ups, meet_up, meet_up_talks, past_meet_ups};
+use user::{user, user_meet_up_activity};
+use voting::store_votes;
+
+pub mod call_for_papers;
+pub mod meet_up;
+pub mod user;
+pub mod voting;
+
+/// Version 1 of the JSON API, for bots and widgets that would otherwise scrape the site.
+pub fn api_router() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/meetUps", get(future_meet_ups))
+        .route("/meetUps/past", get(past_meet_ups))
+        .route("/meetUps/:id", get(meet_up))
+        .route("/meetUps/:id/talks", get(meet_up_talks))
+        .route("/meetUps/:id/me", get(user_meet_up_activity))
+        .route("/meetUps/:id/papers", post(submit_paper))
+        .route("/meetUps/:id/votes", put(store_votes))
+        .route("/user", get(user))
+        .fallback(|| async { ApiError::not_found("Route not found") })
+}
+
+/// Rendered as `{"error": {"code": ..., "message": ...}}`.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            code: "bad_request",
+            message: message.into(),
+        }
+    }
+
+    pub fn unauthorized() -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            code: "unauthorized",
+            message: "Login or a valid API token is required".into(),
+        }
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::FORBIDDEN,
+            code: "forbidden",
+            message: message.into(),
+        }
+    }
+
+    /// The request is well formed, but breaks a rule like a deadline or a limit.
+    pub fn unprocessable(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::UNPROCESSABLE_ENTITY,
+            code: "unprocessable",
+            message: message.into(),
+        }
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            code: "not_found",
+            message: message.into(),
+        }
+    }
+
+    fn internal() -> Self {
+        Self {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            code: "internal_error",
+            message: "An error occurred while processing your request".into(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ApiErrorBody<'a> {
+    error: ApiErrorDetails<'a>,
+}
+
+#[derive(Serialize)]
+struct ApiErrorDetails<'a> {
+    code: &'a str,
+    message: &'a str,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ApiErrorBody {
+            error: ApiErrorDetails {
+                code: self.code,
+                message: &self.message,
+            },
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+impl<E> From<E> for ApiError
+where
+    E: Into<anyhow::Error>,
+{
+    fn from(err: E) -> Self {
+        let err = err.into();
+        if let Some(GetPastMeetUpError::NotFound(id)) = err.downcast_ref() {
+            return ApiError::not_found(format!("Meet up `{id}` not found"));
+        }
+        if let Some(rejection) = err.downcast_ref::<PathRejection>() {
+            return ApiError::bad_request(rejection.body_text());
+        }
+        if let Some(rejection) = err.downcast_ref::<JsonRejection>() {
+            return ApiError::bad_request(rejection.body_text());
+        }
+        if let Some(rejection) = err.downcast_ref::<QueryRejection>() {
+            return ApiError::bad_request(rejection.body_text());
+        }
+        tracing::error!("Unexpected error: {}", err);
+        ApiError::internal()
+    }
+}
+
+const MAX_PER_PAGE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct PageParams {
+    #[serde(default = "default_page")]
+    page: usize,
+    #[serde(default = "default_per_page")]
+    per_page: usize,
+}
+
+fn default_page() -> usize {
+    1
+}
+
+fn default_per_page() -> usize {
+    20
+}
+
+#[derive(Debug, Serialize)]
+pub struct PagePresenter<T> {
+    items: Vec<T>,
+    page: usize,
+    per_page: usize,
+    total_items: usize,
+    total_pages: usize,
+}
+
+impl<T> PagePresenter<T> {
+    /// Pages start at 1. Pages after the last one are empty.
+    fn new<U: Into<T>>(items: Vec<U>, params: &PageParams) -> Result<Self, ApiError> {
+        if params.page == 0 {
+            return Err(ApiError::bad_request("`page` starts at 1"));
+        }
+        if !(1..=MAX_PER_PAGE).contains(&params.per_page) {
+            return Err(ApiError::bad_request(format!(
+                "`per_page` must be between 1 and {MAX_PER_PAGE}"
+            )));
+        }
+        let total_items = items.len();
+        Ok(Self {
+            items: items
+                .into_iter()
+                .skip((params.page - 1) * params.per_page)
+                .take(params.per_page)
+                .map(Into::into)
+                .collect(),
+            page: params.page,
+            per_page: params.per_page,
+            total_items,
+            total_pages: total_items.div_ceil(params.per_page),
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MeetUpPresenter {
+    id: Ulid,
+    state: &'static str,
+    date: DateTime<Utc>,
+    end: DateTime<Utc>,
+    location: Location,
+    capacity: Option<u32>,
+    number_of_talks: u8,
+    voting_method: String,
+    talks: Vec<TalkPresenter>,
+    /// Only done meet ups have a recording.
+    recording_link: Option<Url>,
+    call_for_papers_deadline: Option<DateTime<Utc>>,
+    voting_deadline: Option<DateTime<Utc>>,
+}
+
+impl From<MeetUp> for MeetUpPresenter {
+    fn from(meet_up: MeetUp) -> Self {
+        let end = meet_up.end();
+        let (state, talks, recording_link) = match meet_up.state {
+            MeetUpState::CallForPapers => ("CallForPapers", vec![], None),
+            MeetUpState::Voting => ("Voting", vec![], None),
+            MeetUpState::Scheduled(talks) => ("Scheduled", talks, None),
+            MeetUpState::Done { talks, link } => ("Done", talks, Some(link)),
+            MeetUpState::Cancelled => ("Cancelled", vec![], None),
+        };
+        Self {
+            id: meet_up.id,
+            state,
+            date: meet_up.date,
+            end,
+            location: meet_up.location,
+            capacity: meet_up.capacity,
+            number_of_talks: meet_up.number_of_talks,
+            voting_method: meet_up.voting_method.to_string(),
+            talks: talks.into_iter().map(TalkPresenter::from).collect(),
+            recording_link,
+            call_for_papers_deadline: meet_up.settings.call_for_papers_deadline,
+            voting_deadline: meet_up.settings.voting_deadline,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MeetUpDetailsPresenter {
+    #[serde(flatten)]
+    meet_up: MeetUpPresenter,
+    /// The lineup titles, only for done meet ups.
+    title: Option<String>,
+    attendees: usize,
+    remaining_seats: Option<usize>,
+}
+
+impl From<MeetUpDetails> for MeetUpDetailsPresenter {
+    fn from(details: MeetUpDetails) -> Self {
+        Self {
+            meet_up: details.meet_up.into(),
+            title: details.metadata.map(|metadata| metadata.title),
+            attendees: details.attendees,
+            remaining_seats: details.remaining_seats,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MeetUpMetadataPresenter {
+    id: Ulid,
+    title: String,
+    date: DateTime<Utc>,
+}
+
+impl From<MeetUpMetadata> for MeetUpMetadataPresenter {
+    fn from(metadata: MeetUpMetadata) -> Self {
+        Self {
+            id: metadata.id,
+            title: metadata.title,
+            date: metadata.date,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TalkPresenter {
+    paper_id: Ulid,
+    title: String,
+    description: String,
+    speaker: String,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    duration_minutes: u32,
+}
+
+impl From<Talk> for TalkPresenter {
+    fn from(talk: Talk) -> Self {
+        Self {
+            end: talk.end(),
+            paper_id: talk.paper.id,
+            title: talk.paper.title,
+            description: talk.paper.description,
+            speaker: talk.paper.speaker,
+            start: talk.start,
+            duration_minutes: talk.duration_minutes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserPresenter {
+    id: Ulid,
+    nickname: String,
+    email: String,
+}
+
+impl From<User> for UserPresenter {
+    fn from(user: User) -> Self {
+        Self {
+            id: user.id,
+            nickname: user.nickname,
+            email: user.email,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserMeetUpActivityPresenter {
+    papers: Vec<PaperPresenter>,
+    votes: Vec<VotePresenter>,
+    registration: RegistrationPresenter,
+}
+
+impl From<UserMeetUpActivity> for UserMeetUpActivityPresenter {
+    fn from(activity: UserMeetUpActivity) -> Self {
+        Self {
+            papers: activity.papers.into_iter().map(Into::into).collect(),
+            votes: activity.votes.into_iter().map(Into::into).collect(),
+            registration: activity.registration.into(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PaperPresenter {
+    id: Ulid,
+    title: String,
+    description: String,
+    speaker: String,
+    email: String,
+    keep_in_pool: bool,
+}
+
+impl From<Paper> for PaperPresenter {
+    fn from(paper: Paper) -> Self {
+        Self {
+            id: paper.id,
+            title: paper.title,
+            description: paper.description,
+            speaker: paper.speaker,
+            email: paper.email,
+            keep_in_pool: paper.keep_in_pool,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct VotePresenter {
+    paper_id: Ulid,
+    /// Starts at 0 for the best ranked paper.
+    position: u32,
+}
+
+impl From<Vote> for VotePresenter {
+    fn from(vote: Vote) -> Self {
+        Self {
+            paper_id: vote.paper_id,
+            position: vote.position,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegistrationPresenter {
+    /// `Attending`, `Waitlisted` or `None` when not registered.
+    status: Option<&'static str>,
+    waitlist_position: Option<usize>,
+    remaining_seats: Option<usize>,
+}
+
+impl From<MeetUpRegistration> for RegistrationPresenter {
+    fn from(registration: MeetUpRegistration) -> Self {
+        let (status, waitlist_position) = match registration.status {
+            Some(RegistrationStatus::Attending) => (Some("Attending"), None),
+            Some(RegistrationStatus::Waitlisted(position)) => (Some("Waitlisted"), Some(position)),
+            None => (None, None),
+        };
+        Self {
+            status,
+            waitlist_position,
+            remaining_seats: registration.remaining_seats,
+        }
+    }
+}
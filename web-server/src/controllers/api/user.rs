@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use axum_extra::extract::WithRejection;
+use ulid::Ulid;
+
+use domain::{show_user_meet_up_activity, ApiTokenScope};
+
+use crate::{app::AppState, extractors::ApiUser};
+
+use super::{ApiError, UserMeetUpActivityPresenter, UserPresenter};
+
+pub async fn user(api_user: ApiUser) -> Result<Json<UserPresenter>, ApiError> {
+    api_user.require_scope(ApiTokenScope::ReadOnly)?;
+    Ok(Json(api_user.user.into()))
+}
+
+/// The papers, votes and registration of the logged user in the meet up.
+pub async fn user_meet_up_activity(
+    api_user: ApiUser,
+    State(state): State<Arc<AppState>>,
+    WithRejection(Path(id), _): WithRejection<Path<Ulid>, ApiError>,
+) -> Result<Json<UserMeetUpActivityPresenter>, ApiError> {
+    api_user.require_scope(ApiTokenScope::ReadOnly)?;
+    let activity = show_user_meet_up_activity(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &id,
+        &api_user.user,
+    )
+    .await?;
+    Ok(Json(activity.into()))
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use axum::Json;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use axum_extra::extract::WithRejection;
+use serde::Deserialize;
+use ulid::Ulid;
+
+use domain::{ApiTokenScope, StoreVotesError};
+
+use crate::{app::AppState, extractors::ApiUser};
+
+use super::ApiError;
+
+#[derive(Debug, Deserialize)]
+pub struct VotesParams {
+    /// Every paper of the meet up, best ranked first.
+    paper_ids: Vec<Ulid>,
+}
+
+/// Replaces the ballot of the user.
+pub async fn store_votes(
+    api_user: ApiUser,
+    State(state): State<Arc<AppState>>,
+    WithRejection(Path(id), _): WithRejection<Path<Ulid>, ApiError>,
+    WithRejection(Json(params), _): WithRejection<Json<VotesParams>, ApiError>,
+) -> Result<StatusCode, ApiError> {
+    api_user.require_scope(ApiTokenScope::Vote)?;
+    match domain::store_votes(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &id,
+        &api_user.user.id,
+        params.paper_ids,
+    )
+    .await
+    {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(StoreVotesError::MeetUpNotFound(id)) => {
+            Err(ApiError::not_found(format!("Meet up `{id}` not found")))
+        }
+        Err(StoreVotesError::Unknown(err)) => Err(err.into()),
+        Err(err) => Err(ApiError::unprocessable(err.to_string())),
+    }
+}
@@ -0,0 +1,226 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::header,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Brazil;
+use shared::utc_now;
+use ulid::Ulid;
+
+use domain::{
+    get_meet_up, get_meet_up_revision, list_calendar_meet_ups, Location, MeetUp, MeetUpRevision,
+    MeetUpState,
+};
+
+use crate::app::AppState;
+
+use super::HtmlError;
+
+/// Single meet up, to be added to a calendar.
+pub async fn meet_up_calendar(
+    Path(id): Path<Ulid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, HtmlError> {
+    let meet_up = get_meet_up(&state.database_gateway, id).await?;
+    let revision = get_meet_up_revision(&state.database_gateway, &id).await?;
+    Ok(calendar_response(
+        render_calendar(&[(meet_up, revision)], &state),
+        Some(format!("meetup-{id}.ics")),
+    ))
+}
+
+/// Every meet up, to be subscribed to in calendar apps.
+pub async fn calendar(State(state): State<Arc<AppState>>) -> Result<impl IntoResponse, HtmlError> {
+    let mut meet_ups = vec![];
+    for meet_up in list_calendar_meet_ups(&state.database_gateway).await? {
+        let revision = get_meet_up_revision(&state.database_gateway, &meet_up.id).await?;
+        meet_ups.push((meet_up, revision));
+    }
+    Ok(calendar_response(render_calendar(&meet_ups, &state), None))
+}
+
+fn calendar_response(calendar: String, filename: Option<String>) -> impl IntoResponse {
+    let disposition = match filename {
+        Some(filename) => format!("attachment; filename=\"{filename}\""),
+        None => "inline".to_string(),
+    };
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                "text/calendar; charset=utf-8".to_string(),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        calendar,
+    )
+}
+
+fn render_calendar(meet_ups: &[(MeetUp, MeetUpRevision)], state: &AppState) -> String {
+    let now = utc_now();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//RustCWB//Meetups//PT".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        "X-WR-CALNAME:RustCWB".to_string(),
+    ];
+    for (meet_up, revision) in meet_ups {
+        lines.extend(render_event(meet_up, revision, state, now));
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+fn render_event(
+    meet_up: &MeetUp,
+    revision: &MeetUpRevision,
+    state: &AppState,
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    let url = state
+        .site_url
+        .join(&format!("meetUp/{}", meet_up.id))
+        .map(String::from)
+        .unwrap_or_else(|_| state.site_url.to_string());
+    let talks = meet_up.talks();
+    let summary = match talks.is_empty() {
+        true => "Meetup RustCWB".to_string(),
+        false => format!(
+            "Meetup RustCWB: {}",
+            talks
+                .iter()
+                .map(|talk| talk.paper.title.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let mut description = talks
+        .iter()
+        .map(|talk| {
+            format!(
+                "{} - {} ({})",
+                talk.start.with_timezone(&Brazil::West).format("%H:%M"),
+                talk.paper.title,
+                talk.paper.speaker
+            )
+        })
+        .collect::<Vec<_>>();
+    description.push(url.clone());
+    let location = match &meet_up.location {
+        Location::OnSite(address) => address.clone(),
+        Location::Online {
+            video_conference_link,
+            ..
+        } => video_conference_link.to_string(),
+    };
+    let status = match meet_up.state {
+        MeetUpState::CallForPapers | MeetUpState::Voting => "TENTATIVE",
+        MeetUpState::Scheduled(_) | MeetUpState::Done { .. } => "CONFIRMED",
+        MeetUpState::Cancelled => "CANCELLED",
+    };
+    vec![
+        "BEGIN:VEVENT".to_string(),
+        // The same uid on every export lets calendars update the event instead of duplicating it.
+        format!("UID:{}@rustcwb", meet_up.id),
+        format!("DTSTAMP:{}", format_date(now)),
+        // Calendars only take the changes of an event with a higher sequence.
+        format!("SEQUENCE:{}", revision.sequence),
+        format!("LAST-MODIFIED:{}", format_date(revision.last_modified)),
+        format!("DTSTART:{}", format_date(meet_up.date)),
+        format!("DTEND:{}", format_date(meet_up.end())),
+        format!("SUMMARY:{}", escape_text(&summary)),
+        format!("DESCRIPTION:{}", escape_text(&description.join("\n"))),
+        format!("LOCATION:{}", escape_text(&location)),
+        format!("URL:{url}"),
+        format!("STATUS:{status}"),
+        "END:VEVENT".to_string(),
+    ]
+}
+
+fn format_date(date: DateTime<Utc>) -> String {
+    date.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a TEXT value as RFC 5545 requires.
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Ends the line with CRLF, splitting it into lines of at most 75 bytes that continue with a
+/// space, without breaking UTF-8 characters.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 8);
+    let mut line_length = 0;
+    for char in line.chars() {
+        if line_length + char.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(char);
+        line_length += char.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_text_escapes_the_special_characters() {
+        assert_eq!(
+            r"Rust\\Go\; Zig\, C\nCabeçalho\nçã",
+            escape_text("Rust\\Go; Zig, C\r\nCabeçalho\nçã")
+        );
+        assert_eq!(
+            "Sem escapes: ação às 19h",
+            escape_text("Sem escapes: ação às 19h")
+        );
+    }
+
+    #[test]
+    fn fold_line_keeps_short_lines() {
+        assert_eq!("SUMMARY:Olá\r\n", fold_line("SUMMARY:Olá"));
+        let line = "X".repeat(75);
+        assert_eq!(format!("{line}\r\n"), fold_line(&line));
+    }
+
+    #[test]
+    fn fold_line_splits_at_75_octets() {
+        let line = "X".repeat(160);
+
+        let folded = fold_line(&line);
+
+        let lines = folded.split_terminator("\r\n").collect::<Vec<_>>();
+        assert_eq!(
+            vec![75, 75, 12],
+            lines.iter().map(|line| line.len()).collect::<Vec<_>>()
+        );
+        assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+        assert_eq!(line, folded.replace("\r\n ", "").trim_end());
+    }
+
+    #[test]
+    fn fold_line_does_not_split_multibyte_characters() {
+        // 74 octets and then a 2 octets character that does not fit in the first line.
+        let line = format!("{}ção{}", "X".repeat(74), "🦀".repeat(30));
+
+        let folded = fold_line(&line);
+
+        for line in folded.split_terminator("\r\n") {
+            assert!(line.len() <= 75, "{line}");
+        }
+        assert!(folded.starts_with(&format!("{}\r\n ç", "X".repeat(74))));
+        assert_eq!(line, folded.replace("\r\n ", "").trim_end());
+    }
+}
@@ -1,32 +1,42 @@
 use std::sync::Arc;
 
 use anyhow::anyhow;
-use axum::{extract::State, response::Html, Form};
+use axum::{
+    extract::{Path, State},
+    response::Html,
+    Form,
+};
 use axum_htmx::HxRequest;
 use minijinja::context;
 use serde::Deserialize;
 use ulid::Ulid;
 
-use domain::{show_call_for_papers, submit_paper, Paper, SubmitPaperError};
+use domain::{
+    edit_paper, notify_paper_received, show_call_for_papers, submit_paper, withdraw_paper,
+    ChangePaperError, Paper, SubmitPaperError,
+};
 
 use crate::{
     app::AppState,
     controllers::{MeetUpPresenter, UserPresenter},
     extractors::LoggedUser,
+    notifications::spawn_notification,
 };
 
 use super::HtmlError;
 
 pub async fn call_for_papers(
     user: LoggedUser,
+    Path(id): Path<Ulid>,
     HxRequest(is_hx_request): HxRequest,
     State(state): State<Arc<AppState>>,
 ) -> Result<Html<String>, HtmlError> {
-    call_for_papers_with_errors(&[], user, is_hx_request, &state).await
+    call_for_papers_with_errors(&[], &id, user, is_hx_request, &state).await
 }
 
 pub async fn save_call_for_papers(
     user: LoggedUser,
+    Path(id): Path<Ulid>,
     HxRequest(is_hx_request): HxRequest,
     State(state): State<Arc<AppState>>,
     Form(params): Form<PaperParams>,
@@ -45,23 +55,38 @@ pub async fn save_call_for_papers(
         errors.push("Speaker is required");
     }
     if !errors.is_empty() {
-        return call_for_papers_with_errors(&errors, user, is_hx_request, &state).await;
+        return call_for_papers_with_errors(&errors, &id, user, is_hx_request, &state).await;
     }
+    let paper = Paper {
+        id: Ulid::new(),
+        title: params.title,
+        email: params.email,
+        description: params.description,
+        speaker: params.speaker,
+        user_id: user.0.id,
+        keep_in_pool: params.keep_in_pool,
+    };
     match submit_paper(
         &state.database_gateway,
         &state.database_gateway,
-        Paper {
-            id: Ulid::new(),
-            title: params.title,
-            email: params.email,
-            description: params.description,
-            speaker: params.speaker,
-            user_id: user.0.id,
-        },
+        &state.database_gateway,
+        &id,
+        paper.clone(),
     )
     .await
     {
         Ok(_) => {
+            let notification_state = state.clone();
+            spawn_notification(async move {
+                notify_paper_received(
+                    &notification_state.mail_gateway,
+                    &notification_state.database_gateway,
+                    &notification_state.database_gateway,
+                    &id,
+                    &paper,
+                )
+                .await
+            });
             let tmpl = state.get_minijinja_env().get_template("success")?;
             let context = context! { message => "Paper submetido com sucesso"};
             match is_hx_request {
@@ -69,10 +94,10 @@ pub async fn save_call_for_papers(
                 false => Ok(Html(tmpl.render(context)?)),
             }
         }
-        Err(SubmitPaperError::InvalidMeetUpState(_))
-        | Err(SubmitPaperError::NoFutureMeetUpFound) => {
+        Err(SubmitPaperError::InvalidMeetUpState(_)) | Err(SubmitPaperError::MeetUpNotFound(_)) => {
             call_for_papers_with_errors(
                 &["Meet up is not accepting papers"],
+                &id,
                 user,
                 is_hx_request,
                 &state,
@@ -82,6 +107,27 @@ pub async fn save_call_for_papers(
         Err(SubmitPaperError::MoreThanLimitPapersPerUserPerMeetUp(_)) => {
             call_for_papers_with_errors(
                 &["You have already submitted the limit of papers for this meet up"],
+                &id,
+                user,
+                is_hx_request,
+                &state,
+            )
+            .await
+        }
+        Err(SubmitPaperError::MoreThanMaxPapersPerMeetUp(_)) => {
+            call_for_papers_with_errors(
+                &["This meet up already received the maximum number of papers"],
+                &id,
+                user,
+                is_hx_request,
+                &state,
+            )
+            .await
+        }
+        Err(SubmitPaperError::CallForPapersClosed(_)) => {
+            call_for_papers_with_errors(
+                &["The call for papers for this meet up is closed"],
+                &id,
                 user,
                 is_hx_request,
                 &state,
@@ -92,21 +138,107 @@ pub async fn save_call_for_papers(
     }
 }
 
+pub async fn update_paper(
+    user: LoggedUser,
+    Path((id, paper_id)): Path<(Ulid, Ulid)>,
+    HxRequest(is_hx_request): HxRequest,
+    State(state): State<Arc<AppState>>,
+    Form(params): Form<PaperParams>,
+) -> Result<Html<String>, HtmlError> {
+    let mut errors = Vec::new();
+    if params.title.is_empty() {
+        errors.push("Title is required");
+    }
+    if params.description.is_empty() {
+        errors.push("Description is required");
+    }
+    if params.speaker.is_empty() {
+        errors.push("Speaker is required");
+    }
+    if !errors.is_empty() {
+        return call_for_papers_with_errors(&errors, &id, user, is_hx_request, &state).await;
+    }
+    let result = edit_paper(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &id,
+        &user.0,
+        Paper {
+            id: paper_id,
+            title: params.title,
+            description: params.description,
+            speaker: params.speaker,
+            email: user.0.email.clone(),
+            user_id: user.0.id,
+            keep_in_pool: params.keep_in_pool,
+        },
+    )
+    .await
+    .map(|_| ());
+    call_for_papers_after_change(result, &id, user, is_hx_request, &state).await
+}
+
+pub async fn withdraw(
+    user: LoggedUser,
+    Path((id, paper_id)): Path<(Ulid, Ulid)>,
+    HxRequest(is_hx_request): HxRequest,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    let result = withdraw_paper(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &id,
+        &user.0,
+        &paper_id,
+    )
+    .await;
+    call_for_papers_after_change(result, &id, user, is_hx_request, &state).await
+}
+
+async fn call_for_papers_after_change(
+    result: Result<(), ChangePaperError>,
+    meet_up_id: &Ulid,
+    user: LoggedUser,
+    is_hx_request: bool,
+    state: &AppState,
+) -> Result<Html<String>, HtmlError> {
+    let errors: &[&str] = match result {
+        Ok(_) => &[],
+        Err(ChangePaperError::InvalidMeetUpState(_)) | Err(ChangePaperError::MeetUpNotFound(_)) => {
+            &["Meet up is not accepting papers"]
+        }
+        Err(ChangePaperError::PaperNotFound(_)) => &["Paper not found"],
+        Err(ChangePaperError::CallForPapersClosed(_)) => {
+            &["The call for papers for this meet up is closed"]
+        }
+        Err(ChangePaperError::Unknown(err)) => return Err(HtmlError::from(anyhow!("{err}"))),
+    };
+    call_for_papers_with_errors(errors, meet_up_id, user, is_hx_request, state).await
+}
+
 async fn call_for_papers_with_errors(
     errors: &[&str],
+    meet_up_id: &Ulid,
     user: LoggedUser,
     is_hx_request: bool,
     state: &AppState,
 ) -> Result<Html<String>, HtmlError> {
     let tmpl = state.get_minijinja_env().get_template("call_for_papers")?;
-    let (future_meet_up, papers, is_papers_limit) =
-        show_call_for_papers(&state.database_gateway, &state.database_gateway, &user.0).await?;
+    let (future_meet_up, papers, carried_over_papers, is_papers_limit) = show_call_for_papers(
+        &state.database_gateway,
+        &state.database_gateway,
+        meet_up_id,
+        &user.0,
+    )
+    .await?;
 
     let context = context! {
         user => UserPresenter::from(user.0),
-        client_id => state.github_client_id.clone(),
         future_meet_up => MeetUpPresenter::from(future_meet_up),
         papers => papers,
+        carried_over_papers => carried_over_papers,
         is_papers_limit => is_papers_limit,
         errors => errors,
     };
@@ -119,7 +251,11 @@ async fn call_for_papers_with_errors(
 #[derive(Debug, Clone, Deserialize)]
 pub struct PaperParams {
     pub title: String,
+    /// Not in the edit form, a paper keeps the email it was submitted with.
+    #[serde(default)]
     pub email: String,
     pub description: String,
     pub speaker: String,
+    #[serde(default)]
+    pub keep_in_pool: bool,
 }
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::header, response::IntoResponse};
+use chrono::{DateTime, SecondsFormat, Utc};
+use chrono_tz::Brazil;
+
+use domain::{list_feed_entries, FeedEntry, FeedEntryKind};
+
+use crate::app::AppState;
+
+use super::HtmlError;
+
+/// Atom feed with every state change of the meet ups.
+pub async fn feed(State(state): State<Arc<AppState>>) -> Result<impl IntoResponse, HtmlError> {
+    let entries = list_feed_entries(&state.database_gateway).await?;
+    Ok((
+        [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+        render_feed(&entries, &state),
+    ))
+}
+
+fn render_feed(entries: &[FeedEntry], state: &AppState) -> String {
+    let feed_url = link(state, "feed.atom");
+    // Feeds need an update date even when they have no entries.
+    let updated = entries
+        .first()
+        .map(|entry| entry.date)
+        .unwrap_or(DateTime::UNIX_EPOCH);
+    let mut feed = format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom" xml:lang="pt">
+<id>{}</id>
+<title>RustCWB</title>
+<subtitle>Meetups da comunidade RustCWB</subtitle>
+<updated>{}</updated>
+<link rel="self" href="{}"/>
+<link rel="alternate" type="text/html" href="{}"/>
+<author><name>RustCWB</name></author>
+"#,
+        escape_xml(&feed_url),
+        format_date(updated),
+        escape_xml(&feed_url),
+        escape_xml(state.site_url.as_str()),
+    );
+    for entry in entries {
+        feed.push_str(&render_entry(entry, state));
+    }
+    feed.push_str("</feed>\n");
+    feed
+}
+
+fn render_entry(entry: &FeedEntry, state: &AppState) -> String {
+    let meet_up = &entry.meet_up;
+    let meet_up_date = meet_up.date.with_timezone(&Brazil::West).format("%d/%m/%Y");
+    let (id, title, summary, url) = match &entry.kind {
+        FeedEntryKind::CallForPapersOpened => (
+            state_entry_id(entry, "callForPapers"),
+            format!("Chamada de trabalhos aberta para o meetup de {meet_up_date}"),
+            "Envie sua proposta de palestra!".to_string(),
+            link(state, &format!("callForPapers/{}", meet_up.id)),
+        ),
+        FeedEntryKind::VotingOpened => (
+            state_entry_id(entry, "voting"),
+            format!("Votação aberta para o meetup de {meet_up_date}"),
+            "Escolha as palestras que você quer ver.".to_string(),
+            link(state, &format!("voting/{}", meet_up.id)),
+        ),
+        FeedEntryKind::TalkScheduled(talk) => (
+            // The same talk keeps its entry even when rescheduled.
+            format!("urn:rustcwb:meetup:{}:talk:{}", meet_up.id, talk.paper.id),
+            format!("Palestra confirmada: {}", talk.paper.title),
+            format!(
+                "{} apresenta \"{}\" no meetup de {meet_up_date}, às {}.",
+                talk.paper.speaker,
+                talk.paper.title,
+                talk.start.with_timezone(&Brazil::West).format("%H:%M"),
+            ),
+            link(state, &format!("meetUp/{}", meet_up.id)),
+        ),
+        FeedEntryKind::Done(recording) => (
+            state_entry_id(entry, "done"),
+            format!("Gravação do meetup de {meet_up_date}"),
+            format!("O meetup aconteceu, assista à gravação em {recording}."),
+            recording.to_string(),
+        ),
+        FeedEntryKind::Cancelled => (
+            state_entry_id(entry, "cancelled"),
+            format!("Meetup de {meet_up_date} cancelado"),
+            "O meetup foi cancelado.".to_string(),
+            link(state, ""),
+        ),
+    };
+    format!(
+        r#"<entry>
+<id>{}</id>
+<title>{}</title>
+<updated>{}</updated>
+<link href="{}"/>
+<summary>{}</summary>
+</entry>
+"#,
+        escape_xml(&id),
+        escape_xml(&title),
+        format_date(entry.date),
+        escape_xml(&url),
+        escape_xml(&summary),
+    )
+}
+
+/// A meet up can go through the same state more than once, like when the voting is reopened, so
+/// the date is part of the id.
+fn state_entry_id(entry: &FeedEntry, kind: &str) -> String {
+    format!(
+        "urn:rustcwb:meetup:{}:{kind}:{}",
+        entry.meet_up.id,
+        entry.date.timestamp_micros()
+    )
+}
+
+fn link(state: &AppState, path: &str) -> String {
+    state
+        .site_url
+        .join(path)
+        .map(String::from)
+        .unwrap_or_else(|_| state.site_url.to_string())
+}
+
+fn format_date(date: DateTime<Utc>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
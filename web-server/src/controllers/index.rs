@@ -12,7 +12,7 @@ use crate::{
     extractors::MaybeUser,
 };
 
-use super::{HtmlError, MeetUpMetadataPresenter};
+use super::{HtmlError, MeetUpMetadataPresenter, RegistrationPresenter};
 
 pub async fn index(
     maybe_user: MaybeUser,
@@ -20,7 +20,7 @@ pub async fn index(
     State(state): State<Arc<AppState>>,
 ) -> Result<Html<String>, HtmlError> {
     let tmpl = state.get_minijinja_env().get_template("home")?;
-    let (future_meet_up, past_meet_ups, is_registered_user) = show_home_page(
+    let (future_meet_ups, past_meet_ups) = show_home_page(
         &state.database_gateway,
         &state.database_gateway,
         maybe_user.0.as_ref().map(|user| &user.id),
@@ -29,9 +29,10 @@ pub async fn index(
 
     let context = context! {
         user => maybe_user.0.map(UserPresenter::from),
-        registered_user => is_registered_user,
-        client_id => state.github_client_id.clone(),
-        future_meet_up => future_meet_up.map(MeetUpPresenter::from),
+        future_meet_ups => future_meet_ups
+            .into_iter()
+            .map(|(meet_up, registration)| (MeetUpPresenter::from(meet_up), RegistrationPresenter::from(registration)))
+            .collect::<Vec<(MeetUpPresenter, RegistrationPresenter)>>(),
         past_meetups => past_meet_ups.into_iter().map(MeetUpMetadataPresenter::from).collect::<Vec<MeetUpMetadataPresenter>>(),
     };
     match is_hx_request {
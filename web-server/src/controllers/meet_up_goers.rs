@@ -1,26 +1,94 @@
 use std::sync::Arc;
 
-use axum::extract::State;
+use axum::extract::{Path, State};
 use axum::response::Html;
 use minijinja::context;
+use ulid::Ulid;
 
-use domain::register_event_goer;
+use domain::{
+    get_meet_up, get_meet_up_registration, notify_registration_confirmed, register_event_goer,
+    unregister_event_goer, RegistrationStatus,
+};
 
 use crate::app::AppState;
-use crate::controllers::HtmlError;
+use crate::controllers::{HtmlError, RegistrationPresenter};
 use crate::extractors::LoggedUser;
+use crate::notifications::spawn_notification;
 
 pub async fn register(
     LoggedUser(user): LoggedUser,
+    Path(id): Path<Ulid>,
     State(state): State<Arc<AppState>>,
 ) -> Result<Html<String>, HtmlError> {
     let tmpl = state
         .get_minijinja_env()
         .get_template("components/future_meet_ups/register_button")?;
-    register_event_goer(&state.database_gateway, &state.database_gateway, &user.id).await?;
+    let status = register_event_goer(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &id,
+        &user.id,
+    )
+    .await?;
+    if status == RegistrationStatus::Attending {
+        let (notification_state, user_id) = (state.clone(), user.id);
+        spawn_notification(async move {
+            notify_registration_confirmed(
+                &notification_state.mail_gateway,
+                &notification_state.database_gateway,
+                &notification_state.database_gateway,
+                &id,
+                &[user_id],
+            )
+            .await
+        });
+    }
+    let meet_up = get_meet_up(&state.database_gateway, id).await?;
+    let registration =
+        get_meet_up_registration(&state.database_gateway, &meet_up, Some(&user.id)).await?;
     let context = context! {
         user => user,
-        registered_user => true,
+        future_meet_up => context! { id => id },
+        registration => RegistrationPresenter::from(registration),
+    };
+    Ok(Html(tmpl.render(context)?))
+}
+
+pub async fn unregister(
+    LoggedUser(user): LoggedUser,
+    Path(id): Path<Ulid>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    let tmpl = state
+        .get_minijinja_env()
+        .get_template("components/future_meet_ups/register_button")?;
+    let promoted_user_ids = unregister_event_goer(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &id,
+        &user.id,
+    )
+    .await?;
+    let notification_state = state.clone();
+    spawn_notification(async move {
+        notify_registration_confirmed(
+            &notification_state.mail_gateway,
+            &notification_state.database_gateway,
+            &notification_state.database_gateway,
+            &id,
+            &promoted_user_ids,
+        )
+        .await
+    });
+    let meet_up = get_meet_up(&state.database_gateway, id).await?;
+    let registration =
+        get_meet_up_registration(&state.database_gateway, &meet_up, Some(&user.id)).await?;
+    let context = context! {
+        user => user,
+        future_meet_up => context! { id => id },
+        registration => RegistrationPresenter::from(registration),
     };
     Ok(Html(tmpl.render(context)?))
 }
@@ -1,12 +1,20 @@
 use axum::response::{Html, IntoResponse};
+use chrono::{DateTime, Utc};
 use chrono_tz::Brazil;
 use serde::{Deserialize, Serialize};
+use shared::utc_now;
 use ulid::Ulid;
 
-use domain::{Location, MeetUp, MeetUpMetadata, MeetUpState, User};
+use domain::{
+    ApiToken, Location, MeetUp, MeetUpMetadata, MeetUpRegistration, MeetUpState,
+    RegistrationStatus, Session, Talk, User,
+};
 
 pub mod admin;
+pub mod api;
+pub mod calendar;
 pub mod call_for_papers;
+pub mod feed;
 pub mod index;
 pub mod meet_up;
 pub mod meet_up_goers;
@@ -68,6 +76,14 @@ where
     }
 }
 
+/// Dates shown on the pages, in the time zone of the meet ups.
+pub fn format_time(date: DateTime<Utc>) -> String {
+    format!(
+        "{} BRT",
+        date.with_timezone(&Brazil::West).format("%Y-%m-%d %H:%M")
+    )
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct UserPresenter {
     nickname: String,
@@ -83,65 +99,147 @@ impl From<User> for UserPresenter {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ApiTokenPresenter {
+    id: Ulid,
+    name: String,
+    scopes: Vec<String>,
+    created_at: String,
+    last_used_at: Option<String>,
+    revoked: bool,
+}
+
+impl From<ApiToken> for ApiTokenPresenter {
+    fn from(api_token: ApiToken) -> Self {
+        Self {
+            id: api_token.id,
+            name: api_token.name,
+            scopes: api_token.scopes.iter().map(ToString::to_string).collect(),
+            created_at: format_time(api_token.created_at),
+            last_used_at: api_token.last_used_at.map(format_time),
+            revoked: api_token.revoked_at.is_some(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SessionPresenter {
+    id: Ulid,
+    device: String,
+    created_at: String,
+    last_seen_at: String,
+    expire_at: String,
+    /// The session of the browser showing the page.
+    current: bool,
+}
+
+impl SessionPresenter {
+    fn new(session: Session, current_session_id: &Ulid) -> Self {
+        Self {
+            current: session.id == *current_session_id,
+            id: session.id,
+            device: session.device,
+            created_at: format_time(session.created_at),
+            last_seen_at: format_time(session.last_seen_at),
+            expire_at: format_time(session.expire_at),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct MeetUpPresenter {
     id: Ulid,
-    title: String,
     state: String,
-    description: String,
-    speaker: String,
+    talks: Vec<TalkPresenter>,
+    number_of_talks: u8,
+    capacity: Option<u32>,
     date: String,
+    date_input: String,
     link: String,
     location: Location,
+    voting_method: String,
+    papers_per_user: u8,
+    max_papers: Option<u32>,
+    call_for_papers_deadline: Option<String>,
+    voting_deadline: Option<String>,
+    call_for_papers_closed: bool,
+    voting_closed: bool,
 }
 
 impl From<MeetUp> for MeetUpPresenter {
     fn from(meetup: MeetUp) -> Self {
-        let (state, title, description, speaker, link) = match meetup.state {
-            MeetUpState::Done { paper, link } => (
-                "Done".into(),
-                paper.title,
-                paper.description,
-                paper.speaker,
-                link.as_str().to_owned(),
-            ),
-            MeetUpState::Scheduled(paper) => (
-                "Scheduled".into(),
-                paper.title,
-                paper.description,
-                paper.speaker,
-                String::new(),
-            ),
-            MeetUpState::CallForPapers => (
-                "CallForPapers".into(),
-                String::new(),
-                String::new(),
-                String::new(),
-                String::new(),
-            ),
-            MeetUpState::Voting => (
-                "Voting".into(),
-                String::new(),
-                String::new(),
-                String::new(),
-                String::new(),
-            ),
+        let (state, talks, link) = match meetup.state {
+            MeetUpState::Done { talks, link } => ("Done".into(), talks, link.as_str().to_owned()),
+            MeetUpState::Scheduled(talks) => ("Scheduled".into(), talks, String::new()),
+            MeetUpState::CallForPapers => ("CallForPapers".into(), vec![], String::new()),
+            MeetUpState::Voting => ("Voting".into(), vec![], String::new()),
+            MeetUpState::Cancelled => ("Cancelled".into(), vec![], String::new()),
         };
+        let date = meetup.date.with_timezone(&Brazil::West);
+        let now = utc_now();
         Self {
             id: meetup.id,
-            title,
             state,
-            description,
-            speaker,
+            talks: talks.into_iter().map(TalkPresenter::from).collect(),
+            number_of_talks: meetup.number_of_talks,
+            capacity: meetup.capacity,
             link,
-            date: format!(
-                "{} BRT",
-                meetup
-                    .date
-                    .with_timezone(&Brazil::West)
-                    .format("%Y-%m-%d %H:%M:%S")
-            ),
+            date: format!("{} BRT", date.format("%Y-%m-%d %H:%M:%S")),
+            date_input: date.format("%Y-%m-%dT%H:%M").to_string(),
             location: meetup.location,
+            voting_method: meetup.voting_method.to_string(),
+            papers_per_user: meetup.settings.papers_per_user,
+            max_papers: meetup.settings.max_papers,
+            call_for_papers_deadline: meetup.settings.call_for_papers_deadline.map(format_time),
+            voting_deadline: meetup.settings.voting_deadline.map(format_time),
+            call_for_papers_closed: meetup.settings.is_call_for_papers_closed(now),
+            voting_closed: meetup.settings.is_voting_closed(now),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TalkPresenter {
+    paper_id: Ulid,
+    title: String,
+    description: String,
+    speaker: String,
+    start: String,
+    start_input: String,
+    duration_minutes: u32,
+}
+
+impl From<Talk> for TalkPresenter {
+    fn from(talk: Talk) -> Self {
+        let start = talk.start.with_timezone(&Brazil::West);
+        Self {
+            paper_id: talk.paper.id,
+            title: talk.paper.title,
+            description: talk.paper.description,
+            speaker: talk.paper.speaker,
+            start: start.format("%H:%M").to_string(),
+            start_input: start.format("%Y-%m-%dT%H:%M").to_string(),
+            duration_minutes: talk.duration_minutes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RegistrationPresenter {
+    registered: bool,
+    waitlist_position: Option<usize>,
+    remaining_seats: Option<usize>,
+}
+
+impl From<MeetUpRegistration> for RegistrationPresenter {
+    fn from(registration: MeetUpRegistration) -> Self {
+        Self {
+            registered: registration.status.is_some(),
+            waitlist_position: match registration.status {
+                Some(RegistrationStatus::Waitlisted(position)) => Some(position),
+                _ => None,
+            },
+            remaining_seats: registration.remaining_seats,
         }
     }
 }
@@ -1,30 +1,232 @@
-use std::{collections::HashMap, sync::Arc};
+use std::sync::Arc;
 
-use anyhow::anyhow;
 use axum::{
-    extract::{Query, State},
-    response::{Html, Redirect},
+    extract::{Path, Query, State},
+    http::{header::USER_AGENT, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
+    Form,
+};
+use axum_extra::extract::{
+    cookie::{Cookie, SameSite},
+    CookieJar,
 };
-use axum_extra::extract::{cookie::Cookie, CookieJar};
 use axum_htmx::HxRequest;
+use chrono::{DateTime, Utc};
 use minijinja::context;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use ulid::Ulid;
+use url::{form_urlencoded, Url};
 
-use domain::login_with_github_code;
+use domain::{
+    bootstrap_admin, create_api_token, end_session, get_user_role, has_mail_notifications,
+    issue_login_state, list_api_tokens, list_sessions, login_with_github_code,
+    login_with_oidc_code, revoke_api_token, revoke_session as revoke_user_session,
+    set_mail_notifications, start_session, verify_login_state, ApiTokenScope, CreateApiTokenError,
+    OidcGateway, RevokeApiTokenError, RevokeSessionError, Session, User,
+};
 
-use crate::{app::AppState, controllers::UserPresenter, extractors::MaybeUser};
+use crate::{
+    app::AppState,
+    controllers::{ApiTokenPresenter, SessionPresenter, UserPresenter},
+    extractors::{LoggedSession, MaybeSession, SESSION_COOKIE},
+};
 
 use super::HtmlError;
 
 pub async fn user(
-    maybe_user: MaybeUser,
+    MaybeSession(user_session): MaybeSession,
+    HxRequest(is_hx_request): HxRequest,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    render_user(
+        user_session,
+        UserPageMessages::default(),
+        is_hx_request,
+        &state,
+    )
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MailNotificationsParams {
+    enabled: bool,
+}
+
+pub async fn update_mail_notifications(
+    LoggedSession(user, session): LoggedSession,
     HxRequest(is_hx_request): HxRequest,
     State(state): State<Arc<AppState>>,
+    Form(params): Form<MailNotificationsParams>,
+) -> Result<Html<String>, HtmlError> {
+    set_mail_notifications(&state.database_gateway, &user, params.enabled).await?;
+    render_user(
+        Some((user, session)),
+        UserPageMessages::default(),
+        is_hx_request,
+        &state,
+    )
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApiTokenParams {
+    name: String,
+    #[serde(default)]
+    read_only: bool,
+    #[serde(default)]
+    submit_paper: bool,
+    #[serde(default)]
+    vote: bool,
+    #[serde(default)]
+    admin: bool,
+}
+
+pub async fn new_api_token(
+    LoggedSession(user, session): LoggedSession,
+    HxRequest(is_hx_request): HxRequest,
+    State(state): State<Arc<AppState>>,
+    Form(params): Form<ApiTokenParams>,
+) -> Result<Html<String>, HtmlError> {
+    let scopes = [
+        (params.read_only, ApiTokenScope::ReadOnly),
+        (params.submit_paper, ApiTokenScope::SubmitPaper),
+        (params.vote, ApiTokenScope::Vote),
+        (params.admin, ApiTokenScope::Admin),
+    ]
+    .into_iter()
+    .filter_map(|(checked, scope)| checked.then_some(scope))
+    .collect::<Vec<_>>();
+    let is_admin = get_user_role(&state.database_gateway, &user)
+        .await?
+        .is_some();
+    render_new_api_token(
+        user,
+        session,
+        &params.name,
+        &scopes,
+        is_admin,
+        is_hx_request,
+        &state,
+    )
+    .await
+}
+
+async fn render_new_api_token(
+    user: User,
+    session: Session,
+    name: &str,
+    scopes: &[ApiTokenScope],
+    is_admin: bool,
+    is_hx_request: bool,
+    state: &AppState,
+) -> Result<Html<String>, HtmlError> {
+    let messages = match create_api_token(
+        &state.database_gateway,
+        &state.database_gateway,
+        &user,
+        name,
+        scopes,
+        is_admin,
+    )
+    .await
+    {
+        Ok((_, secret)) => UserPageMessages {
+            new_api_token_secret: Some(secret),
+            ..Default::default()
+        },
+        Err(CreateApiTokenError::Unknown(err)) => return Err(HtmlError::from(err)),
+        Err(err) => UserPageMessages {
+            errors: vec![err.to_string()],
+            ..Default::default()
+        },
+    };
+    render_user(Some((user, session)), messages, is_hx_request, state).await
+}
+
+pub async fn revoke(
+    LoggedSession(user, session): LoggedSession,
+    Path(id): Path<Ulid>,
+    HxRequest(is_hx_request): HxRequest,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    let messages = match revoke_api_token(
+        &state.database_gateway,
+        &state.database_gateway,
+        &user,
+        &id,
+    )
+    .await
+    {
+        Ok(()) => UserPageMessages::default(),
+        Err(RevokeApiTokenError::Unknown(err)) => return Err(HtmlError::from(err)),
+        Err(err) => UserPageMessages {
+            errors: vec![err.to_string()],
+            ..Default::default()
+        },
+    };
+    render_user(Some((user, session)), messages, is_hx_request, &state).await
+}
+
+/// Logs another device out. Revoking the current session works like the logout.
+pub async fn revoke_session(
+    LoggedSession(user, session): LoggedSession,
+    Path(id): Path<Ulid>,
+    HxRequest(is_hx_request): HxRequest,
+    State(state): State<Arc<AppState>>,
+) -> Result<Html<String>, HtmlError> {
+    let messages =
+        match revoke_user_session(&state.database_gateway, &state.database_gateway, &user, &id)
+            .await
+        {
+            Ok(()) => UserPageMessages::default(),
+            Err(RevokeSessionError::Unknown(err)) => return Err(HtmlError::from(err)),
+            Err(err) => UserPageMessages {
+                errors: vec![err.to_string()],
+                ..Default::default()
+            },
+        };
+    let user_session = (session.id != id).then_some((user, session));
+    render_user(user_session, messages, is_hx_request, &state).await
+}
+
+#[derive(Debug, Default)]
+struct UserPageMessages {
+    errors: Vec<String>,
+    /// Shown only once, right after the token is created.
+    new_api_token_secret: Option<String>,
+}
+
+async fn render_user(
+    user_session: Option<(User, Session)>,
+    messages: UserPageMessages,
+    is_hx_request: bool,
+    state: &AppState,
 ) -> Result<Html<String>, HtmlError> {
     let tmpl = state.get_minijinja_env().get_template("user")?;
+    let (mail_notifications, api_tokens, role, sessions) = match &user_session {
+        Some((user, session)) => (
+            has_mail_notifications(&state.database_gateway, user).await?,
+            list_api_tokens(&state.database_gateway, user).await?,
+            get_user_role(&state.database_gateway, user).await?,
+            list_sessions(&state.database_gateway, user)
+                .await?
+                .into_iter()
+                .map(|other| SessionPresenter::new(other, &session.id))
+                .collect(),
+        ),
+        None => (false, vec![], None, vec![]),
+    };
+    let user = user_session.map(|(user, _)| user);
 
     let context = context! {
-        user => maybe_user.0.map(UserPresenter::from),
-        client_id => state.github_client_id.clone(),
+        user => user.map(UserPresenter::from),
+        mail_notifications => mail_notifications,
+        api_tokens => api_tokens.into_iter().map(ApiTokenPresenter::from).collect::<Vec<_>>(),
+        role => role.map(|role| role.to_string()),
+        sessions => sessions,
+        new_api_token_secret => messages.new_api_token_secret,
+        errors => messages.errors,
     };
     match is_hx_request {
         true => Ok(Html(tmpl.eval_to_state(context)?.render_block("content")?)),
@@ -33,24 +235,207 @@ pub async fn user(
 }
 
 pub async fn logout(
+    MaybeSession(user_session): MaybeSession,
     cookie_jar: CookieJar,
-    _: State<Arc<AppState>>,
+    State(state): State<Arc<AppState>>,
 ) -> Result<(CookieJar, Redirect), HtmlError> {
-    Ok((cookie_jar.remove("access_token"), Redirect::to("/")))
+    if let Some((_, session)) = user_session {
+        end_session(&state.database_gateway, &session).await?;
+    }
+    Ok((
+        cookie_jar.remove(Cookie::build(SESSION_COOKIE).path("/")),
+        Redirect::to("/"),
+    ))
+}
+
+/// Cookie with the nonce the login state is tied to, only sent back to the login callbacks.
+const LOGIN_NONCE_COOKIE: &str = "login_nonce";
+
+#[derive(Debug, Deserialize)]
+pub struct LoginParams {
+    return_to: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct LoginProviderPresenter {
+    name: String,
+    href: String,
+}
+
+/// Lets the user pick GitHub or one of the OpenID Connect providers, going straight to GitHub
+/// when it is the only one.
+pub async fn login(
+    Query(params): Query<LoginParams>,
+    HxRequest(is_hx_request): HxRequest,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response, HtmlError> {
+    let href = |provider: &str| match &params.return_to {
+        Some(return_to) => format!(
+            "/login/{provider}?{}",
+            form_urlencoded::Serializer::new(String::new())
+                .append_pair("return_to", return_to)
+                .finish()
+        ),
+        None => format!("/login/{provider}"),
+    };
+    if state.oidc_gateways.is_empty() {
+        return Ok(Redirect::to(&href("github")).into_response());
+    }
+    let providers = std::iter::once(("GitHub", "github"))
+        .chain(
+            state
+                .oidc_gateways
+                .iter()
+                .map(|oidc_gateway| (oidc_gateway.name(), oidc_gateway.id())),
+        )
+        .map(|(name, id)| LoginProviderPresenter {
+            name: name.to_string(),
+            href: href(id),
+        })
+        .collect::<Vec<_>>();
+    let tmpl = state.get_minijinja_env().get_template("login")?;
+    let context = context! { providers => providers };
+    match is_hx_request {
+        true => Ok(Html(tmpl.eval_to_state(context)?.render_block("content")?).into_response()),
+        false => Ok(Html(tmpl.render(context)?).into_response()),
+    }
+}
+
+/// Starts the login on the provider with a signed `state`, so the callback only finishes logins
+/// started in the same browser.
+pub async fn login_with_provider(
+    Path(provider): Path<String>,
+    Query(params): Query<LoginParams>,
+    cookie_jar: CookieJar,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response, HtmlError> {
+    let login_state = issue_login_state(&state.secret_key, params.return_to.as_deref());
+    let (authorize_url, callback_path) = if provider == "github" {
+        let authorize_url = Url::parse_with_params(
+            "https://github.com/login/oauth/authorize",
+            [
+                ("client_id", state.github_client_id.as_str()),
+                ("state", login_state.state.as_str()),
+            ],
+        )?;
+        (authorize_url, "/github")
+    } else {
+        let Some(oidc_gateway) = state.oidc_gateway(&provider) else {
+            return Ok(StatusCode::NOT_FOUND.into_response());
+        };
+        let authorize_url = oidc_gateway
+            .authorize_url(&login_state.state, &login_state.nonce)
+            .await?;
+        (authorize_url, "/oidc")
+    };
+    let cookie = Cookie::build((LOGIN_NONCE_COOKIE, login_state.nonce))
+        .path(callback_path)
+        .http_only(true)
+        .secure(state.secure_cookies())
+        // The callback is a navigation from the provider, which Strict would not send the cookie
+        // to.
+        .same_site(SameSite::Lax)
+        .expires(cookie_expiration(&login_state.expire_at)?);
+    Ok((cookie_jar.add(cookie), Redirect::to(authorize_url.as_str())).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthorizeParams {
+    code: String,
+    state: String,
 }
 
 pub async fn github_login(
-    Query(query): Query<HashMap<String, String>>,
+    Query(params): Query<AuthorizeParams>,
+    headers: HeaderMap,
+    cookie_jar: CookieJar,
+    State(state): State<Arc<AppState>>,
+) -> Result<(CookieJar, Redirect), Response> {
+    let (return_to, _) = verify_login_callback(&state, &params, &cookie_jar, "/github")
+        .map_err(IntoResponse::into_response)?;
+    let cookie_jar = cookie_jar.remove(Cookie::build(LOGIN_NONCE_COOKIE).path("/github"));
+    let user = login_with_github_code(&state.database_gateway, &state.github_gateway, params.code)
+        .await
+        .map_err(|err| HtmlError::from(err).into_response())?;
+    finish_login(user, &headers, cookie_jar, &return_to, &state)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+pub async fn oidc_login(
+    Path(provider): Path<String>,
+    Query(params): Query<AuthorizeParams>,
+    headers: HeaderMap,
     cookie_jar: CookieJar,
     State(state): State<Arc<AppState>>,
+) -> Result<(CookieJar, Redirect), Response> {
+    let Some(oidc_gateway) = state.oidc_gateway(&provider) else {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    };
+    let (return_to, nonce) = verify_login_callback(&state, &params, &cookie_jar, "/oidc")
+        .map_err(IntoResponse::into_response)?;
+    let cookie_jar = cookie_jar.remove(Cookie::build(LOGIN_NONCE_COOKIE).path("/oidc"));
+    let user = login_with_oidc_code(&state.database_gateway, oidc_gateway, &params.code, &nonce)
+        .await
+        .map_err(|err| HtmlError::from(err).into_response())?;
+    finish_login(user, &headers, cookie_jar, &return_to, &state)
+        .await
+        .map_err(IntoResponse::into_response)
+}
+
+/// Checks the `state` against the nonce cookie, returning where to go after the login and the
+/// nonce.
+fn verify_login_callback(
+    state: &AppState,
+    params: &AuthorizeParams,
+    cookie_jar: &CookieJar,
+    callback_path: &str,
+) -> Result<(String, String), (StatusCode, HtmlError)> {
+    let nonce = cookie_jar
+        .get(LOGIN_NONCE_COOKIE)
+        .map(|cookie| cookie.value().to_string())
+        .unwrap_or_default();
+    let return_to =
+        verify_login_state(&state.secret_key, &params.state, &nonce).map_err(|err| {
+            tracing::warn!("Rejected login on {callback_path}: {err}");
+            (StatusCode::BAD_REQUEST, HtmlError)
+        })?;
+    Ok((return_to, nonce))
+}
+
+async fn finish_login(
+    user: User,
+    headers: &HeaderMap,
+    cookie_jar: CookieJar,
+    return_to: &str,
+    state: &AppState,
 ) -> Result<(CookieJar, Redirect), HtmlError> {
-    let code = query
-        .get("code")
-        .ok_or_else(|| anyhow!("No code in query"))?
-        .to_string();
-    let user = login_with_github_code(&state.database_gateway, &state.github_gateway, code).await?;
-    // TODO: Add an expiration time
-    let mut cookie = Cookie::new("access_token", user.access_token.token().to_string());
-    cookie.set_path("/");
-    Ok((cookie_jar.add(cookie), Redirect::to("/")))
+    if bootstrap_admin(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &user,
+        &state.admin_emails,
+    )
+    .await?
+    {
+        tracing::info!("Granted the admin role from ADMIN_EMAILS to {}", user.id);
+    }
+    let device = headers
+        .get(USER_AGENT)
+        .and_then(|user_agent| user_agent.to_str().ok())
+        .unwrap_or_default();
+    let (session, token) = start_session(&state.database_gateway, &user, device).await?;
+    let cookie = Cookie::build((SESSION_COOKIE, token))
+        .path("/")
+        .http_only(true)
+        .secure(state.secure_cookies())
+        // Strict would not send the cookie when following links from other sites.
+        .same_site(SameSite::Lax)
+        .expires(cookie_expiration(&session.expire_at)?);
+    Ok((cookie_jar.add(cookie), Redirect::to(return_to)))
+}
+
+fn cookie_expiration(expire_at: &DateTime<Utc>) -> anyhow::Result<OffsetDateTime> {
+    Ok(OffsetDateTime::from_unix_timestamp(expire_at.timestamp())?)
 }
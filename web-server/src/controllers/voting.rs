@@ -1,12 +1,16 @@
 use std::sync::Arc;
 
 use axum::extract::Path;
-use axum::{extract::State, response::Html, Form};
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse, Response},
+    Form,
+};
 use axum_htmx::HxRequest;
 use minijinja::context;
 use ulid::Ulid;
 
-use domain::{get_paper, show_voting, store_votes};
+use domain::{get_paper, show_voting, store_votes, StoreVotesError};
 
 use crate::controllers::MeetUpPresenter;
 use crate::{app::AppState, controllers::UserPresenter, extractors::LoggedUser};
@@ -15,6 +19,7 @@ use super::HtmlError;
 
 pub async fn voting(
     user: LoggedUser,
+    Path(id): Path<Ulid>,
     HxRequest(is_hx_request): HxRequest,
     State(state): State<Arc<AppState>>,
 ) -> Result<Html<String>, HtmlError> {
@@ -24,13 +29,13 @@ pub async fn voting(
         &state.database_gateway,
         &state.database_gateway,
         &state.database_gateway,
+        &id,
         &user.0.id,
     )
     .await?;
 
     let context = context! {
         user => UserPresenter::from(user.0),
-        client_id => state.github_client_id.clone(),
         future_meet_up => MeetUpPresenter::from(future_meet_up),
         papers => papers,
         errors => Vec::<String>::new(),
@@ -43,33 +48,51 @@ pub async fn voting(
 
 pub async fn store_vote(
     user: LoggedUser,
+    Path(id): Path<Ulid>,
     _: HxRequest,
     State(state): State<Arc<AppState>>,
     Form(form): Form<Vec<(String, Ulid)>>,
-) -> Result<Html<String>, HtmlError> {
+) -> Result<Response, HtmlError> {
     let tmpl = state.get_minijinja_env().get_template("voting")?;
-    store_votes(
+    let errors = match store_votes(
+        &state.database_gateway,
         &state.database_gateway,
         &state.database_gateway,
+        &state.database_gateway,
+        &id,
         &user.0.id,
         form.into_iter().map(|(_, paper_id)| paper_id).collect(),
     )
-    .await?;
+    .await
+    {
+        // Renders the ballot as it was stored before the deadline.
+        Ok(_) | Err(StoreVotesError::VotingClosed(_)) => vec![],
+        Err(err @ StoreVotesError::InvalidPaper(_)) => vec![err.to_string()],
+        Err(err) => return Err(HtmlError::from(err)),
+    };
     let (future_meet_up, papers) = show_voting(
         &state.database_gateway,
         &state.database_gateway,
         &state.database_gateway,
+        &id,
         &user.0.id,
     )
     .await?;
     let context = context! {
         user => UserPresenter::from(user.0),
-        client_id => state.github_client_id.clone(),
         future_meet_up => MeetUpPresenter::from(future_meet_up),
         papers => papers,
-        errors => Vec::<String>::new(),
+        errors => errors,
     };
-    Ok(Html(tmpl.eval_to_state(context)?.render_block("papers")?))
+    if errors.is_empty() {
+        return Ok(Html(tmpl.eval_to_state(context)?.render_block("papers")?).into_response());
+    }
+    // The form only swaps the list of papers, so the whole page is swapped to show the error.
+    Ok((
+        [("HX-Retarget", "#main-content"), ("HX-Reswap", "innerHTML")],
+        Html(tmpl.eval_to_state(context)?.render_block("content")?),
+    )
+        .into_response())
 }
 
 pub async fn paper_details(
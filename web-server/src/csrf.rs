@@ -0,0 +1,213 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{Html, IntoResponse, Response},
+};
+use axum_extra::extract::CookieJar;
+
+use domain::{issue_csrf_token, verify_csrf_token};
+
+use crate::{
+    app::AppState,
+    controllers::api::ApiError,
+    extractors::{bearer_token, SESSION_COOKIE},
+};
+
+/// Header htmx sends with every request, see the `hx-headers` of the base template.
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+tokio::task_local! {
+    static CSRF_TOKEN: String;
+}
+
+/// Template function with the token of the session of the request, empty when logged out.
+pub fn csrf_token() -> String {
+    CSRF_TOKEN.try_with(Clone::clone).unwrap_or_default()
+}
+
+/// Rejects requests that change something with the session cookie but without its CSRF token,
+/// so other sites can not vote or register in the name of the user. Requests with an API token
+/// are not sent by browsers on their own and do not need it.
+pub async fn csrf_protection(
+    State(state): State<Arc<AppState>>,
+    cookie_jar: CookieJar,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(session_token) = cookie_jar.get(SESSION_COOKIE).map(|cookie| cookie.value()) else {
+        return CSRF_TOKEN.scope(String::new(), next.run(request)).await;
+    };
+    if !request.method().is_safe() && bearer_token(request.headers()).is_none() {
+        let csrf_token = request
+            .headers()
+            .get(CSRF_HEADER)
+            .and_then(|csrf_token| csrf_token.to_str().ok())
+            .unwrap_or_default();
+        if !verify_csrf_token(&state.secret_key, session_token, csrf_token) {
+            tracing::warn!(
+                "Rejected {} {} without a valid CSRF token",
+                request.method(),
+                request.uri().path()
+            );
+            return csrf_rejection(request.uri().path());
+        }
+    }
+    let csrf_token = issue_csrf_token(&state.secret_key, session_token);
+    CSRF_TOKEN.scope(csrf_token, next.run(request)).await
+}
+
+fn csrf_rejection(path: &str) -> Response {
+    if path.starts_with("/api/") {
+        return ApiError::forbidden("Missing or invalid CSRF token").into_response();
+    }
+    (
+        StatusCode::FORBIDDEN,
+        // Shown above the element the request would update, keeping what the user typed.
+        [("HX-Reswap", "beforebegin")],
+        Html(
+            r#"<div class="bg-red-500 text-white rounded-lg p-2">Formulário expirado, recarregue a página e tente de novo.</div>"#,
+        ),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use ulid::Ulid;
+
+    use domain::ApiTokenScope;
+
+    use crate::test_support::{
+        api_token, body_text, create_user, send, session_token, test_state, SECRET_KEY,
+    };
+
+    use super::*;
+
+    fn post(uri: &str, session: &str) -> axum::http::request::Builder {
+        Request::post(uri)
+            .header("cookie", format!("{SESSION_COOKIE}={session}"))
+            .header("content-type", "application/x-www-form-urlencoded")
+    }
+
+    #[tokio::test]
+    async fn rejects_forms_without_the_csrf_token() -> anyhow::Result<()> {
+        let state = test_state().await?;
+        let user = create_user(&state, None).await?;
+        let session = session_token(&state, &user).await?;
+
+        for request in [
+            post("/user/mailNotifications", &session).body(Body::from("enabled=true"))?,
+            post("/user/mailNotifications", &session)
+                .header(CSRF_HEADER, issue_csrf_token(SECRET_KEY, "other_session"))
+                .body(Body::from("enabled=true"))?,
+        ] {
+            let response = send(&state, request).await?;
+
+            assert_eq!(StatusCode::FORBIDDEN, response.status());
+            assert_eq!("beforebegin", response.headers()["HX-Reswap"]);
+            assert!(body_text(response).await?.contains("Formulário expirado"));
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn accepts_forms_with_the_csrf_token() -> anyhow::Result<()> {
+        let state = test_state().await?;
+        let user = create_user(&state, None).await?;
+        let session = session_token(&state, &user).await?;
+
+        let response = send(
+            &state,
+            post("/user/mailNotifications", &session)
+                .header(CSRF_HEADER, issue_csrf_token(SECRET_KEY, &session))
+                .body(Body::from("enabled=true"))?,
+        )
+        .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rejects_api_requests_with_the_cookie_as_json() -> anyhow::Result<()> {
+        let state = test_state().await?;
+        let user = create_user(&state, None).await?;
+        let session = session_token(&state, &user).await?;
+
+        let response = send(
+            &state,
+            post(&format!("/api/v1/meetUps/{}/papers", Ulid::new()), &session)
+                .body(Body::empty())?,
+        )
+        .await?;
+
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        assert!(!response.headers().contains_key("HX-Reswap"));
+        assert_eq!(
+            r#"{"error":{"code":"forbidden","message":"Missing or invalid CSRF token"}}"#,
+            body_text(response).await?
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn api_tokens_do_not_need_the_csrf_token() -> anyhow::Result<()> {
+        let state = test_state().await?;
+        let user = create_user(&state, None).await?;
+        let session = session_token(&state, &user).await?;
+        let secret = api_token(&state, &user, &[ApiTokenScope::SubmitPaper]).await?;
+
+        let response = send(
+            &state,
+            Request::post(format!("/api/v1/meetUps/{}/papers", Ulid::new()))
+                .header("cookie", format!("{SESSION_COOKIE}={session}"))
+                .header("authorization", format!("Bearer {secret}"))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    r#"{"title":"t","description":"d","speaker":"s","email":"e"}"#,
+                ))?,
+        )
+        .await?;
+
+        // Past the CSRF protection, the meet up does not exist.
+        assert_eq!(StatusCode::NOT_FOUND, response.status());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn safe_requests_do_not_need_the_csrf_token() -> anyhow::Result<()> {
+        let state = test_state().await?;
+        let user = create_user(&state, None).await?;
+        let session = session_token(&state, &user).await?;
+
+        let response = send(
+            &state,
+            Request::get("/user")
+                .header("cookie", format!("{SESSION_COOKIE}={session}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        // The pages get the token to send it back.
+        assert!(body_text(response)
+            .await?
+            .contains(&issue_csrf_token(SECRET_KEY, &session)));
+        Ok(())
+    }
+
+    #[test]
+    fn csrf_rejection_depends_on_the_path() {
+        let response = csrf_rejection("/api/v1/meetUps");
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        assert_eq!("application/json", response.headers()["content-type"]);
+
+        let response = csrf_rejection("/register/01J0000000000000000000000");
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        assert_eq!("beforebegin", response.headers()["HX-Reswap"]);
+    }
+}
@@ -1,65 +1,63 @@
 use std::sync::Arc;
 
-use anyhow::Result;
 use async_trait::async_trait;
 use axum::{
     extract::FromRequestParts,
-    http::{
-        header::{AUTHORIZATION, WWW_AUTHENTICATE},
-        request::Parts,
-        HeaderMap, HeaderName, StatusCode,
-    },
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap, StatusCode},
     response::{IntoResponse, Redirect, Response},
 };
 use axum_extra::extract::CookieJar;
-use base64::{prelude::BASE64_STANDARD, Engine};
 
-use domain::{login_with_access_token, User};
+use domain::{
+    get_user_role, login_with_api_token, login_with_session, ApiToken, ApiTokenScope, Role,
+    Session, User,
+};
+
+use crate::{
+    app::AppState,
+    controllers::{api::ApiError, HtmlError},
+};
 
-use crate::app::AppState;
+/// Cookie with the token of the [`Session`] of the browser.
+pub const SESSION_COOKIE: &str = "session";
 
-pub struct AdminUser();
+/// Logged user with a [`Role`], from the session cookie or an API token with the
+/// admin scope.
+#[derive(Debug)]
+pub struct AdminUser(pub User, pub Role);
 
 #[async_trait]
 impl FromRequestParts<Arc<AppState>> for AdminUser {
-    type Rejection = (StatusCode, HeaderMap);
+    type Rejection = Response;
 
     async fn from_request_parts(
         parts: &mut Parts,
         state: &Arc<AppState>,
     ) -> Result<Self, Self::Rejection> {
-        parts
-            .headers
-            .get(AUTHORIZATION)
-            .and_then(|header| {
-                let header = header.to_str().ok()?;
-                let encoded = header.strip_prefix("Basic ")?;
-                let decoded = BASE64_STANDARD.decode(encoded).ok()?;
-                let decoded = String::from_utf8_lossy(&decoded);
-                let (user, pass) = decoded.split_once(':')?;
-                if user == state.admin_details.0 && pass == state.admin_details.1 {
-                    Some(AdminUser)
-                } else {
-                    None
-                }
-            })
-            .ok_or((
-                StatusCode::UNAUTHORIZED,
-                headers_map(&[(WWW_AUTHENTICATE, "Basic realm=\"admin\", charset=\"UTF-8\"")])
-                    .map_err(|err| {
-                        tracing::error!("Error creating headers map: {err}");
-                        (StatusCode::INTERNAL_SERVER_ERROR, HeaderMap::new())
-                    })?,
-            ))?;
-        Ok(AdminUser())
+        let user = if bearer_token(&parts.headers).is_some() {
+            match TokenUser::from_request_parts(parts, state).await {
+                Ok(TokenUser(user, api_token)) if api_token.has_scope(ApiTokenScope::Admin) => user,
+                Ok(_) => return Err(StatusCode::FORBIDDEN.into_response()),
+                Err(_) => return Err(StatusCode::UNAUTHORIZED.into_response()),
+            }
+        } else {
+            let LoggedUser(user) = LoggedUser::from_request_parts(parts, state).await?;
+            user
+        };
+        match get_user_role(&state.database_gateway, &user).await {
+            Ok(Some(role)) => Ok(Self(user, role)),
+            Ok(None) => Err((StatusCode::FORBIDDEN, HtmlError).into_response()),
+            Err(err) => Err(HtmlError::from(err).into_response()),
+        }
     }
 }
 
+/// Session of the browser and its user, when the session cookie is still valid.
 #[derive(Debug)]
-pub struct MaybeUser(pub Option<User>);
+pub struct MaybeSession(pub Option<(User, Session)>);
 
 #[async_trait]
-impl FromRequestParts<Arc<AppState>> for MaybeUser {
+impl FromRequestParts<Arc<AppState>> for MaybeSession {
     type Rejection = (StatusCode, HeaderMap);
 
     async fn from_request_parts(
@@ -69,47 +67,332 @@ impl FromRequestParts<Arc<AppState>> for MaybeUser {
         let cookie_jar = CookieJar::from_request_parts(parts, state)
             .await
             .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, HeaderMap::new()))?;
-        match cookie_jar.get("access_token") {
+        match cookie_jar.get(SESSION_COOKIE) {
             Some(cookie) => {
-                let access_token = cookie.value();
-                let user = login_with_access_token(
+                let user_session = login_with_session(
+                    &state.database_gateway,
                     &state.database_gateway,
-                    &state.github_gateway,
-                    access_token,
+                    cookie.value(),
                 )
                 .await
                 .ok();
-                Ok(MaybeUser(user))
+                Ok(MaybeSession(user_session))
             }
-            None => Ok(MaybeUser(None)),
+            None => Ok(MaybeSession(None)),
         }
     }
 }
 
 #[derive(Debug)]
-pub struct LoggedUser(pub User);
+pub struct MaybeUser(pub Option<User>);
 
 #[async_trait]
-impl FromRequestParts<Arc<AppState>> for LoggedUser {
+impl FromRequestParts<Arc<AppState>> for MaybeUser {
+    type Rejection = (StatusCode, HeaderMap);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let MaybeSession(user_session) = MaybeSession::from_request_parts(parts, state).await?;
+        Ok(MaybeUser(user_session.map(|(user, _)| user)))
+    }
+}
+
+#[derive(Debug)]
+pub struct LoggedSession(pub User, pub Session);
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for LoggedSession {
     type Rejection = Response;
 
     async fn from_request_parts(
         parts: &mut Parts,
         state: &Arc<AppState>,
     ) -> Result<Self, Self::Rejection> {
-        let user = MaybeUser::from_request_parts(parts, state)
+        let (user, session) = MaybeSession::from_request_parts(parts, state)
             .await
             .map_err(|_| Redirect::to("/user").into_response())?
             .0
             .ok_or(Redirect::to("/user").into_response())?;
+        Ok(Self(user, session))
+    }
+}
+
+#[derive(Debug)]
+pub struct LoggedUser(pub User);
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for LoggedUser {
+    type Rejection = Response;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let LoggedSession(user, _) = LoggedSession::from_request_parts(parts, state).await?;
         Ok(Self(user))
     }
 }
 
-fn headers_map(headers: &[(HeaderName, &str)]) -> Result<HeaderMap> {
-    let mut header_map = HeaderMap::new();
-    for (header_name, value) in headers {
-        header_map.insert(header_name.clone(), value.parse()?);
+/// User of a personal API token sent as `Authorization: Bearer <secret>`.
+#[derive(Debug)]
+pub struct TokenUser(pub User, pub ApiToken);
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for TokenUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let secret = bearer_token(&parts.headers).ok_or(ApiError::unauthorized())?;
+        let (user, api_token) =
+            login_with_api_token(&state.database_gateway, &state.database_gateway, secret)
+                .await
+                .map_err(|_| ApiError::unauthorized())?;
+        Ok(Self(user, api_token))
+    }
+}
+
+/// User of the JSON API, logged in with a personal API token or else the session cookie.
+/// Rejects with a JSON error instead of redirecting to the login page like [`LoggedUser`].
+#[derive(Debug)]
+pub struct ApiUser {
+    pub user: User,
+    /// `None` when logged in with the cookie, which allows everything but admin actions.
+    pub api_token: Option<ApiToken>,
+}
+
+impl ApiUser {
+    pub fn require_scope(&self, scope: ApiTokenScope) -> Result<(), ApiError> {
+        let allowed = match &self.api_token {
+            Some(api_token) => api_token.has_scope(scope),
+            None => scope != ApiTokenScope::Admin,
+        };
+        match allowed {
+            true => Ok(()),
+            false => Err(ApiError::forbidden(format!("Missing the `{scope}` scope"))),
+        }
+    }
+}
+
+#[async_trait]
+impl FromRequestParts<Arc<AppState>> for ApiUser {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        if bearer_token(&parts.headers).is_some() {
+            let TokenUser(user, api_token) = TokenUser::from_request_parts(parts, state).await?;
+            return Ok(Self {
+                user,
+                api_token: Some(api_token),
+            });
+        }
+        let user = MaybeUser::from_request_parts(parts, state)
+            .await
+            .map_err(|_| ApiError::unauthorized())?
+            .0
+            .ok_or(ApiError::unauthorized())?;
+        Ok(Self {
+            user,
+            api_token: None,
+        })
+    }
+}
+
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use ulid::Ulid;
+
+    use domain::{LoginMethod, RoleGateway};
+    use shared::utc_now;
+
+    use crate::test_support::{api_token, body_text, create_user, send, session_token, test_state};
+
+    use super::*;
+
+    fn api_user(scopes: Option<Vec<ApiTokenScope>>) -> ApiUser {
+        let user = User::new(
+            Ulid::new(),
+            "nickname".into(),
+            "email@rustcwb.dev".into(),
+            LoginMethod::Oidc {
+                provider: "test".into(),
+                subject: "subject".into(),
+            },
+        );
+        let api_token = scopes.map(|scopes| ApiToken {
+            id: Ulid::new(),
+            user_id: user.id,
+            name: "token".into(),
+            scopes,
+            created_at: utc_now(),
+            last_used_at: None,
+            revoked_at: None,
+        });
+        ApiUser { user, api_token }
+    }
+
+    fn get(uri: &str, authorization: (&str, String)) -> Request<Body> {
+        Request::get(uri)
+            .header(authorization.0, authorization.1)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[test]
+    fn require_scope_checks_the_token_scopes() {
+        let api_user = api_user(Some(vec![ApiTokenScope::Vote]));
+        assert!(api_user.require_scope(ApiTokenScope::Vote).is_ok());
+        for scope in [ApiTokenScope::ReadOnly, ApiTokenScope::Admin] {
+            let err = api_user.require_scope(scope).unwrap_err();
+            assert_eq!(StatusCode::FORBIDDEN, err.into_response().status());
+        }
+    }
+
+    #[test]
+    fn require_scope_allows_the_session_everything_but_admin() {
+        let api_user = api_user(None);
+        for scope in [
+            ApiTokenScope::ReadOnly,
+            ApiTokenScope::SubmitPaper,
+            ApiTokenScope::Vote,
+        ] {
+            assert!(api_user.require_scope(scope).is_ok());
+        }
+        assert!(api_user.require_scope(ApiTokenScope::Admin).is_err());
+    }
+
+    #[tokio::test]
+    async fn api_user_reads_with_the_read_only_scope() -> anyhow::Result<()> {
+        let state = test_state().await?;
+        let user = create_user(&state, None).await?;
+        let secret = api_token(&state, &user, &[ApiTokenScope::ReadOnly]).await?;
+
+        let response = send(
+            &state,
+            get(
+                "/api/v1/user",
+                ("authorization", format!("Bearer {secret}")),
+            ),
+        )
+        .await?;
+
+        assert_eq!(StatusCode::OK, response.status());
+        assert!(body_text(response).await?.contains(&user.email));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn api_user_without_the_read_only_scope_is_forbidden() -> anyhow::Result<()> {
+        let state = test_state().await?;
+        let user = create_user(&state, None).await?;
+        let secret = api_token(&state, &user, &[ApiTokenScope::Vote]).await?;
+
+        for uri in [
+            "/api/v1/user".to_string(),
+            format!("/api/v1/meetUps/{}/me", Ulid::new()),
+        ] {
+            let response = send(
+                &state,
+                get(&uri, ("authorization", format!("Bearer {secret}"))),
+            )
+            .await?;
+
+            assert_eq!(StatusCode::FORBIDDEN, response.status(), "{uri}");
+            assert!(body_text(response).await?.contains("`ReadOnly`"), "{uri}");
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn api_user_rejects_unknown_tokens_instead_of_the_cookie() -> anyhow::Result<()> {
+        let state = test_state().await?;
+        let user = create_user(&state, None).await?;
+        let session = session_token(&state, &user).await?;
+
+        let response = send(
+            &state,
+            Request::get("/api/v1/user")
+                .header("authorization", "Bearer rcwb_unknown")
+                .header("cookie", format!("{SESSION_COOKIE}={session}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+        assert_eq!(StatusCode::UNAUTHORIZED, response.status());
+
+        let response = send(
+            &state,
+            get(
+                "/api/v1/user",
+                ("cookie", format!("{SESSION_COOKIE}={session}")),
+            ),
+        )
+        .await?;
+        assert_eq!(StatusCode::OK, response.status());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn admin_user_needs_the_admin_scope() -> anyhow::Result<()> {
+        let state = test_state().await?;
+        let admin = create_user(&state, Some(Role::Admin)).await?;
+        let admin_secret = api_token(&state, &admin, &[ApiTokenScope::Admin]).await?;
+        let read_only_secret = api_token(&state, &admin, &[ApiTokenScope::ReadOnly]).await?;
+
+        for (secret, status) in [
+            (admin_secret, StatusCode::OK),
+            (read_only_secret, StatusCode::FORBIDDEN),
+            ("rcwb_unknown".to_string(), StatusCode::UNAUTHORIZED),
+        ] {
+            let response = send(
+                &state,
+                get(
+                    "/admin/audit",
+                    ("authorization", format!("Bearer {secret}")),
+                ),
+            )
+            .await?;
+
+            assert_eq!(status, response.status(), "{secret}");
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn admin_user_needs_a_role() -> anyhow::Result<()> {
+        let state = test_state().await?;
+        let admin = create_user(&state, Some(Role::Admin)).await?;
+        let secret = api_token(&state, &admin, &[ApiTokenScope::Admin]).await?;
+        state
+            .database_gateway
+            .set_user_role(&admin.id, None, None)
+            .await?;
+
+        let response = send(
+            &state,
+            get(
+                "/admin/audit",
+                ("authorization", format!("Bearer {secret}")),
+            ),
+        )
+        .await?;
+
+        assert_eq!(StatusCode::FORBIDDEN, response.status());
+        Ok(())
     }
-    Ok(header_map)
 }
@@ -1,14 +1,22 @@
 use std::env::var;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use gateway::mail::LettreMailGateway;
+use gateway::oidc::OidcRestGateway;
 use tower_http::{compression::CompressionLayer, trace::TraceLayer};
 use tracing_subscriber::EnvFilter;
+use url::Url;
 
 use crate::app::build_app;
 
 mod app;
 mod controllers;
+mod csrf;
 mod extractors;
+mod notifications;
+mod scheduler;
+#[cfg(test)]
+mod test_support;
 
 #[::tokio::main]
 async fn main() -> Result<()> {
@@ -20,11 +28,31 @@ async fn main() -> Result<()> {
         .with_env_filter(EnvFilter::from_default_env())
         .finish();
     tracing::subscriber::set_global_default(subscriber)?;
+    let site_url: Url = var("SITE_URL")?.parse()?;
+    let mail_gateway = match var("SMTP_URL") {
+        Ok(smtp_url) => LettreMailGateway::smtp(&smtp_url, &var("MAIL_FROM")?, site_url.clone())?,
+        // Without an SMTP server the emails are only written to a folder.
+        Err(_) => LettreMailGateway::file_drop(
+            var("MAIL_DROP_FOLDER")?,
+            &var("MAIL_FROM")?,
+            site_url.clone(),
+        )?,
+    };
     let app = build_app(
         var("PUBLIC_FILES_FOLDER")?,
         var("DATABASE_URL")?,
-        (var("ADMIN_USER")?, var("ADMIN_PASSWORD")?),
+        var("ADMIN_EMAILS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|email| !email.is_empty())
+            .map(str::to_string)
+            .collect(),
         (var("GITHUB_CLIENT_ID")?, var("GITHUB_CLIENT_SECRET")?),
+        mail_gateway,
+        site_url.clone(),
+        secret_key()?,
+        oidc_gateways(&site_url)?,
     )
     .await?
     .layer(CompressionLayer::new())
@@ -36,3 +64,44 @@ async fn main() -> Result<()> {
     axum::serve(listener, app).await?;
     Ok(())
 }
+
+/// Value of `SECRET_KEY` in `.env.sample`, which must not sign anything.
+const SAMPLE_SECRET_KEY: &str = "change-me";
+const MIN_SECRET_KEY_LEN: usize = 32;
+
+/// The key signs the login state and the CSRF tokens, so a guessable one lets anyone forge both.
+fn secret_key() -> Result<String> {
+    let secret_key = var("SECRET_KEY")?;
+    if secret_key == SAMPLE_SECRET_KEY || secret_key.len() < MIN_SECRET_KEY_LEN {
+        bail!(
+            "SECRET_KEY must be a random value of at least {MIN_SECRET_KEY_LEN} bytes, e.g. `openssl rand -hex 32`"
+        );
+    }
+    Ok(secret_key)
+}
+
+/// Reads the providers listed in `OIDC_PROVIDERS`, each configured by the variables prefixed with
+/// `OIDC_{ID}_`.
+fn oidc_gateways(site_url: &Url) -> Result<Vec<OidcRestGateway>> {
+    let mut oidc_gateways = Vec::new();
+    for id in var("OIDC_PROVIDERS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+    {
+        if id == "github" || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            bail!("Invalid OpenID Connect provider id: {id}");
+        }
+        let prefix = format!("OIDC_{}_", id.to_uppercase().replace('-', "_"));
+        oidc_gateways.push(OidcRestGateway::new(
+            id.to_string(),
+            var(format!("{prefix}NAME"))?,
+            var(format!("{prefix}DISCOVERY_URL"))?.parse()?,
+            var(format!("{prefix}CLIENT_ID"))?,
+            var(format!("{prefix}CLIENT_SECRET"))?,
+            site_url,
+        ));
+    }
+    Ok(oidc_gateways)
+}
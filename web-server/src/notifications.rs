@@ -0,0 +1,13 @@
+use std::future::Future;
+
+/// Sends the notification in the background, so a slow mail server does not hold the response.
+/// Failures are only logged.
+pub fn spawn_notification<T: Send + 'static>(
+    notification: impl Future<Output = anyhow::Result<T>> + Send + 'static,
+) {
+    tokio::spawn(async move {
+        if let Err(err) = notification.await {
+            tracing::error!("Could not send notification: {err}");
+        }
+    });
+}
@@ -0,0 +1,115 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use tokio::{
+    task::JoinHandle,
+    time::{interval, MissedTickBehavior},
+};
+
+use ulid::Ulid;
+
+use domain::{
+    advance_meet_ups, notify_lineup, notify_voting_opened, send_meet_up_reminders, MeetUp,
+    MeetUpState,
+};
+
+use crate::{app::AppState, notifications::spawn_notification};
+
+/// How often the meet up deadlines are checked.
+const SCHEDULER_PERIOD: Duration = Duration::from_secs(60);
+
+/// Advances meet ups past their deadlines and sends the meet up reminders in the background,
+/// starting right away so the ones missed while the server was down are caught up.
+pub fn spawn_scheduler(state: Arc<AppState>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = interval(SCHEDULER_PERIOD);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // Meet ups that could not be scheduled, retried after a restart.
+        let mut stalled = HashSet::new();
+        loop {
+            interval.tick().await;
+            advance(&state, &mut stalled).await;
+            remind(&state).await;
+        }
+    })
+}
+
+async fn advance(state: &Arc<AppState>, stalled: &mut HashSet<Ulid>) {
+    let transitions = match advance_meet_ups(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        stalled,
+    )
+    .await
+    {
+        Ok(transitions) => transitions,
+        Err(err) => {
+            tracing::error!("Could not list meet ups to advance: {err}");
+            return;
+        }
+    };
+    for transition in transitions {
+        match transition.result {
+            Ok(meet_up) => {
+                tracing::info!(
+                    "Meet up `{}` automatically moved to {}",
+                    transition.meet_up_id,
+                    meet_up.state
+                );
+                notify_transition(state.clone(), meet_up);
+            }
+            Err(err) => tracing::warn!(
+                "Could not automatically advance meet up `{}`, it has to be moved by hand: {err}",
+                transition.meet_up_id
+            ),
+        }
+    }
+}
+
+fn notify_transition(state: Arc<AppState>, meet_up: MeetUp) {
+    match meet_up.state {
+        MeetUpState::Voting => spawn_notification(async move {
+            notify_voting_opened(&state.mail_gateway, &state.database_gateway, &meet_up).await
+        }),
+        MeetUpState::Scheduled(_) => spawn_notification(async move {
+            notify_lineup(
+                &state.mail_gateway,
+                &state.database_gateway,
+                &state.database_gateway,
+                &meet_up,
+            )
+            .await
+        }),
+        _ => {}
+    }
+}
+
+async fn remind(state: &AppState) {
+    let reminders = match send_meet_up_reminders(
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.database_gateway,
+        &state.mail_gateway,
+    )
+    .await
+    {
+        Ok(reminders) => reminders,
+        Err(err) => {
+            tracing::error!("Could not send meet up reminders: {err}");
+            return;
+        }
+    };
+    for reminder in reminders {
+        match reminder.result {
+            Ok(sent) => tracing::info!(
+                "Reminder of meet up `{}` sent to {sent} attendees",
+                reminder.meet_up_id
+            ),
+            Err(err) => tracing::error!(
+                "Could not send the reminder of meet up `{}`: {err}",
+                reminder.meet_up_id
+            ),
+        }
+    }
+}
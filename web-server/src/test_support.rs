@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body},
+    http::Request,
+    response::Response,
+    Router,
+};
+use tower::ServiceExt;
+use ulid::Ulid;
+
+use domain::{
+    create_api_token, start_session, ApiTokenScope, LoginMethod, Role, RoleGateway, User,
+    UserGateway,
+};
+use gateway::github::GithubRestGateway;
+use gateway::mail::LettreMailGateway;
+use gateway::SqliteDatabaseGateway;
+
+use crate::app::{routes, AppState};
+
+pub const SECRET_KEY: &[u8] = b"test-secret-test-secret-test-secret";
+
+/// Empty in memory database, without login providers and dropping the emails in a temporary
+/// folder.
+pub async fn test_state() -> anyhow::Result<Arc<AppState>> {
+    Ok(Arc::new(AppState::new(
+        SqliteDatabaseGateway::new("sqlite::memory:").await?,
+        GithubRestGateway::new("client_id".into(), "client_secret".into()),
+        LettreMailGateway::file_drop(
+            std::env::temp_dir().join("rustcwb-test-mails"),
+            "RustCWB <no-reply@localhost>",
+            "http://localhost:3000".parse()?,
+        )?,
+        "client_id".into(),
+        vec![],
+        "http://localhost:3000".parse()?,
+        SECRET_KEY.to_vec(),
+        vec![],
+    )?))
+}
+
+pub async fn create_user(state: &AppState, role: Option<Role>) -> anyhow::Result<User> {
+    let id = Ulid::new();
+    let user = state
+        .database_gateway
+        .store_user(User::new(
+            id,
+            format!("user-{id}"),
+            format!("{id}@rustcwb.dev"),
+            LoginMethod::Oidc {
+                provider: "test".into(),
+                subject: id.to_string(),
+            },
+        ))
+        .await?;
+    if role.is_some() {
+        state
+            .database_gateway
+            .set_user_role(&user.id, role, None)
+            .await?;
+    }
+    Ok(user)
+}
+
+/// Token of the session cookie.
+pub async fn session_token(state: &AppState, user: &User) -> anyhow::Result<String> {
+    let (_, token) = start_session(&state.database_gateway, user, "test").await?;
+    Ok(token)
+}
+
+/// Secret of a new API token of the user.
+pub async fn api_token(
+    state: &AppState,
+    user: &User,
+    scopes: &[ApiTokenScope],
+) -> anyhow::Result<String> {
+    let is_admin = scopes.contains(&ApiTokenScope::Admin);
+    let (_, secret) = create_api_token(
+        &state.database_gateway,
+        &state.database_gateway,
+        user,
+        "test",
+        scopes,
+        is_admin,
+    )
+    .await?;
+    Ok(secret)
+}
+
+pub async fn send(state: &Arc<AppState>, request: Request<Body>) -> anyhow::Result<Response> {
+    let router: Router = routes(state.clone());
+    Ok(router.oneshot(request).await?)
+}
+
+pub async fn body_text(response: Response) -> anyhow::Result<String> {
+    let bytes = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok(String::from_utf8(bytes.to_vec())?)
+}